@@ -0,0 +1,348 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Granice temperatur, strefy oraz kolory i wskaźnik słupkowy wyznaczane na ich podstawie.
+//! Moduł nie zależy od HAL, więc można go testować na komputerze.
+
+use core::fmt;
+use core::str::FromStr;
+
+/// Dolna granica zakresu pomiarowego czujnika DHT11 (°C)
+pub const SENSOR_MIN: f32 = 0.0;
+/// Górna granica zakresu pomiarowego czujnika DHT11 (°C)
+pub const SENSOR_MAX: f32 = 50.0;
+
+/// Pola struktury TemperatureBoundaries, używane w komunikatach o błędach
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Cold,
+    Optimal,
+    Hot,
+    Critical,
+}
+
+impl Field {
+    /// Kolejność pól w zapisie tekstowym "cold,optimal,hot,critical"
+    pub const ALL: [Field; 4] = [Field::Cold, Field::Optimal, Field::Hot, Field::Critical];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Cold => "cold",
+            Field::Optimal => "optimal",
+            Field::Hot => "hot",
+            Field::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Błędy walidacji granic temperatur
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryError {
+    /// W ciągu brakuje pola
+    MissingField(Field),
+    /// W ciągu jest więcej niż 4 pola
+    TooManyFields,
+    /// Nie udało się sparsować wartości pola
+    Parse(Field),
+    /// Pole nie jest większe od poprzedniego (cold < optimal < hot < critical)
+    Order(Field, Field),
+    /// Wartość poza zakresem pomiarowym czujnika (lub NaN)
+    OutOfRange(Field),
+}
+
+impl fmt::Display for BoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundaryError::MissingField(field) => write!(f, "missing field '{}'", field),
+            BoundaryError::TooManyFields => f.write_str("too many fields, expected 4"),
+            BoundaryError::Parse(field) => write!(f, "cannot parse field '{}'", field),
+            BoundaryError::Order(lower, upper) => {
+                write!(f, "'{}' must be greater than '{}'", upper, lower)
+            }
+            BoundaryError::OutOfRange(field) => write!(
+                f,
+                "'{}' out of sensor range {}..{}",
+                field, SENSOR_MIN, SENSOR_MAX
+            ),
+        }
+    }
+}
+
+/// Struct zawierający tablicę poziomów grnicznych temperatur
+/// Wartości są zawsze poprawne: mieszczą się w zakresie czujnika i spełniają
+/// `cold < optimal < hot < critical`, dlatego pola są dostępne tylko przez gettery.
+/// # Examples
+/// ```
+/// use boundaries::TemperatureBoundaries;
+/// # fn main() -> Result<(), boundaries::BoundaryError> {
+///
+/// let mut tb = TemperatureBoundaries::new(0.0, 25.0, 30.0, 35.0)?;
+/// // Zmiana wszystkich granic naraz, przy błędzie wartości pozostają bez zmian
+/// tb.set_from_string("5,20,28,40")?;
+/// assert!(tb.set_from_string("5,30,28,40").is_err());
+/// // Zapis tekstowy "5,20,28,40" można sparsować z powrotem
+/// assert_eq!(tb.to_string(), "5,20,28,40");
+/// let copy: TemperatureBoundaries = "5,20,28,40".parse()?;
+/// assert_eq!(copy, tb);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TemperatureBoundaries {
+    cold: f32,
+    optimal: f32,
+    hot: f32,
+    critical: f32,
+}
+
+impl Default for TemperatureBoundaries {
+    fn default() -> Self {
+        Self {
+            cold: 0.0,
+            optimal: 25.0,
+            hot: 30.0,
+            critical: 35.0,
+        }
+    }
+}
+
+impl TemperatureBoundaries {
+    /// Tworzy granice po sprawdzeniu ich poprawności
+    pub fn new(cold: f32, optimal: f32, hot: f32, critical: f32) -> Result<Self, BoundaryError> {
+        let tb = Self {
+            cold,
+            optimal,
+            hot,
+            critical,
+        };
+        tb.validate()?;
+        Ok(tb)
+    }
+
+    pub fn cold(&self) -> f32 {
+        self.cold
+    }
+    pub fn optimal(&self) -> f32 {
+        self.optimal
+    }
+    pub fn hot(&self) -> f32 {
+        self.hot
+    }
+    pub fn critical(&self) -> f32 {
+        self.critical
+    }
+
+    pub fn set_cold(&mut self, cold: f32) -> Result<(), BoundaryError> {
+        self.apply(Self { cold, ..*self })
+    }
+    pub fn set_optimal(&mut self, optimal: f32) -> Result<(), BoundaryError> {
+        self.apply(Self { optimal, ..*self })
+    }
+    pub fn set_hot(&mut self, hot: f32) -> Result<(), BoundaryError> {
+        self.apply(Self { hot, ..*self })
+    }
+    pub fn set_critical(&mut self, critical: f32) -> Result<(), BoundaryError> {
+        self.apply(Self { critical, ..*self })
+    }
+
+    /// Ustawia wszystkie granice z ciągu "cold,optimal,hot,critical".
+    /// Przy błędzie żadna wartość nie zostaje zmieniona.
+    pub fn set_from_string(&mut self, string: &str) -> Result<(), BoundaryError> {
+        *self = string.parse()?;
+        Ok(())
+    }
+
+    /// Wartości w kolejności pól z `Field::ALL`
+    pub fn values(&self) -> [f32; 4] {
+        [self.cold, self.optimal, self.hot, self.critical]
+    }
+
+    /// Sprawdza zakres czujnika oraz kolejność granic
+    pub fn validate(&self) -> Result<(), BoundaryError> {
+        let values = self.values();
+        for (field, value) in Field::ALL.iter().zip(values.iter()) {
+            // Porównanie z NaN zawsze daje false, więc NaN również zostaje odrzucone
+            if !(SENSOR_MIN..=SENSOR_MAX).contains(value) {
+                return Err(BoundaryError::OutOfRange(*field));
+            }
+        }
+        for i in 1..values.len() {
+            if values[i] <= values[i - 1] {
+                return Err(BoundaryError::Order(Field::ALL[i - 1], Field::ALL[i]));
+            }
+        }
+        Ok(())
+    }
+
+    // Najpierw walidacja, potem przypisanie - struktura nigdy nie jest w połowie zmieniona
+    fn apply(&mut self, candidate: Self) -> Result<(), BoundaryError> {
+        candidate.validate()?;
+        *self = candidate;
+        Ok(())
+    }
+}
+
+impl FromStr for TemperatureBoundaries {
+    type Err = BoundaryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut iter = s.split(',');
+        let mut values = [0.0f32; 4];
+        for (field, value) in Field::ALL.iter().zip(values.iter_mut()) {
+            let raw = iter.next().ok_or(BoundaryError::MissingField(*field))?.trim();
+            if raw.is_empty() {
+                return Err(BoundaryError::MissingField(*field));
+            }
+            *value = raw.parse::<f32>().map_err(|_| BoundaryError::Parse(*field))?;
+        }
+        if iter.next().is_some() {
+            return Err(BoundaryError::TooManyFields);
+        }
+        Self::new(values[0], values[1], values[2], values[3])
+    }
+}
+
+/// Zapis w formacie akceptowanym przez `FromStr`
+impl fmt::Display for TemperatureBoundaries {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.cold, self.optimal, self.hot, self.critical)
+    }
+}
+
+/// Strefa temperatury wyznaczona przez TemperatureBoundaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    /// Poniżej `cold`
+    Cold,
+    /// Od `cold` do `optimal`
+    Normal,
+    /// Od `optimal` do `hot`
+    Optimal,
+    /// Od `hot` do `critical`
+    Hot,
+    /// Od `critical` w górę
+    Critical,
+}
+
+impl Zone {
+    /// Nazwa strefy w zdarzeniach telemetrii
+    pub fn name(self) -> &'static str {
+        match self {
+            Zone::Cold => "cold",
+            Zone::Normal => "normal",
+            Zone::Optimal => "optimal",
+            Zone::Hot => "hot",
+            Zone::Critical => "critical",
+        }
+    }
+}
+
+impl TemperatureBoundaries {
+    /// Funkcja wyznaczająca strefę dla podanej temperatury
+    pub fn zone(&self, temperature: f32) -> Zone {
+        if temperature >= self.critical {
+            Zone::Critical
+        } else if temperature >= self.hot {
+            Zone::Hot
+        } else if temperature >= self.optimal {
+            Zone::Optimal
+        } else if temperature < self.cold {
+            Zone::Cold
+        } else {
+            Zone::Normal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::string::ToString;
+
+    fn tb() -> TemperatureBoundaries {
+        TemperatureBoundaries::new(10.0, 20.0, 30.0, 40.0).unwrap()
+    }
+
+    #[test]
+    fn nan_is_out_of_range() {
+        assert_eq!(
+            TemperatureBoundaries::new(f32::NAN, 25.0, 30.0, 35.0),
+            Err(BoundaryError::OutOfRange(Field::Cold))
+        );
+        assert_eq!(tb().set_hot(f32::NAN), Err(BoundaryError::OutOfRange(Field::Hot)));
+    }
+
+    #[test]
+    fn boundaries_must_be_strictly_increasing() {
+        assert_eq!(
+            TemperatureBoundaries::new(10.0, 20.0, 20.0, 40.0),
+            Err(BoundaryError::Order(Field::Optimal, Field::Hot))
+        );
+        assert_eq!(tb().set_cold(25.0), Err(BoundaryError::Order(Field::Cold, Field::Optimal)));
+        assert_eq!(tb().set_critical(30.0), Err(BoundaryError::Order(Field::Hot, Field::Critical)));
+    }
+
+    #[test]
+    fn values_outside_sensor_range_are_rejected() {
+        assert_eq!(
+            TemperatureBoundaries::new(-1.0, 20.0, 30.0, 40.0),
+            Err(BoundaryError::OutOfRange(Field::Cold))
+        );
+        assert_eq!(tb().set_critical(SENSOR_MAX + 0.5), Err(BoundaryError::OutOfRange(Field::Critical)));
+        assert!(TemperatureBoundaries::new(SENSOR_MIN, 20.0, 30.0, SENSOR_MAX).is_ok());
+    }
+
+    #[test]
+    fn failed_update_leaves_boundaries_unchanged() {
+        let mut tb = tb();
+        assert_eq!(tb.set_from_string("5,20,15,40"), Err(BoundaryError::Order(Field::Optimal, Field::Hot)));
+        assert_eq!(tb.set_from_string("5,20,abc,40"), Err(BoundaryError::Parse(Field::Hot)));
+        assert_eq!(tb.set_optimal(45.0), Err(BoundaryError::Order(Field::Optimal, Field::Hot)));
+        assert_eq!(tb, self::tb());
+
+        tb.set_from_string("5,20,28,40").unwrap();
+        assert_eq!(tb.values(), [5.0, 20.0, 28.0, 40.0]);
+    }
+
+    #[test]
+    fn parse_errors_name_the_field() {
+        let parse = |s: &str| s.parse::<TemperatureBoundaries>();
+        assert_eq!(parse("10,x,30,40"), Err(BoundaryError::Parse(Field::Optimal)));
+        assert_eq!(parse("10,20,30"), Err(BoundaryError::MissingField(Field::Critical)));
+        assert_eq!(parse("10,,30,40"), Err(BoundaryError::MissingField(Field::Optimal)));
+        assert_eq!(parse("10,20,30,40,50"), Err(BoundaryError::TooManyFields));
+
+        assert_eq!(BoundaryError::Parse(Field::Optimal).to_string(), "cannot parse field 'optimal'");
+        assert_eq!(
+            BoundaryError::Order(Field::Hot, Field::Critical).to_string(),
+            "'critical' must be greater than 'hot'"
+        );
+    }
+
+    #[test]
+    fn display_round_trips_through_from_str() {
+        let tb = TemperatureBoundaries::new(5.5, 20.0, 28.25, 40.0).unwrap();
+        let text = tb.to_string();
+        assert_eq!(text, "5.5,20,28.25,40");
+        assert_eq!(text.parse::<TemperatureBoundaries>(), Ok(tb));
+        assert_eq!(" 5.5, 20 ,28.25,40 ".parse::<TemperatureBoundaries>(), Ok(tb));
+    }
+
+    #[test]
+    fn zones_start_at_their_boundary() {
+        let tb = tb();
+        assert_eq!(tb.zone(9.9), Zone::Cold);
+        assert_eq!(tb.zone(10.0), Zone::Normal);
+        assert_eq!(tb.zone(20.0), Zone::Optimal);
+        assert_eq!(tb.zone(30.0), Zone::Hot);
+        assert_eq!(tb.zone(40.0), Zone::Critical);
+    }
+}
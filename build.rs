@@ -0,0 +1,32 @@
+// Identyfikator kompilacji dostępny w programie przez env!("GIT_HASH") i env!("BUILD_PROFILE")
+// oraz układ pamięci memory.x dla skryptu linkera cortex-m-rt
+use std::path::PathBuf;
+use std::process::Command;
+
+fn main() {
+    // memory.x kopiowany jest do katalogu wyjściowego, skąd dołącza go link.x
+    let out = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    std::fs::copy("memory.x", out.join("memory.x")).unwrap();
+    println!("cargo:rustc-link-search={}", out.display());
+    println!("cargo:rerun-if-changed=memory.x");
+
+    let hash = git(&["rev-parse", "--short=8", "HEAD"]).unwrap_or_else(|| "unknown".to_string());
+    // Niezatwierdzone zmiany oznaczane są przyrostkiem "-dirty"
+    let dirty = git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|s| !s.is_empty());
+    println!("cargo:rustc-env=GIT_HASH={}{}", hash, if dirty { "-dirty" } else { "" });
+    println!("cargo:rustc-env=BUILD_PROFILE={}", std::env::var("PROFILE").unwrap_or_default());
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+    // Nowy commit zmienia plik gałęzi, na którą wskazuje HEAD
+    if let Some(branch) = std::fs::read_to_string(".git/HEAD").ok().and_then(|head| head.strip_prefix("ref: ").map(|r| r.trim().to_string())) {
+        println!("cargo:rerun-if-changed=.git/{}", branch);
+    }
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
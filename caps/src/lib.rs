@@ -0,0 +1,210 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Możliwości oprogramowania termometru zwracane przez polecenie `caps`:
+//! wersja protokołu, obsługiwane polecenia, formaty pomiarów i typy czujników.
+//!
+//! Odpowiedź ma postać `OK caps protocol=1.1 commands=help,version,... formats=human,... sensors=dht11`.
+//! Polecenia zapisywane są z `-` zamiast spacji (`get-bounds`). Crate używany jest przez oprogramowanie
+//! (wysłanie odpowiedzi) i przez narzędzia PC (odczyt odpowiedzi i sprawdzenie zgodności wersji).
+
+use core::fmt;
+
+/// Wersja protokołu tekstowego. Zmiana `major` oznacza niezgodne zmiany,
+/// zmiana `minor` - nowe polecenia lub pola przy zachowaniu dotychczasowych.
+/// 1.1: numer kolejny i czas w zdarzeniach stref i błędów, ramka binarna `Record`.
+pub const PROTOCOL_VERSION: Version = Version { major: 1, minor: 1 };
+
+// Polecenia dostępne w każdej wersji oprogramowania
+macro_rules! commands {
+    () => {
+        "help,version,info,caps,reset,get-bounds,set-bounds,get-reading,get-interval,set-interval,\
+get-protocol,set-protocol,get-format,set-format,get-address,set-address,get-serial,set-serial,\
+get-uart,clear-uart,get-shell,set-shell,get-rs485,set-rs485,get-report,set-report,\
+get-subscriptions,subscribe,unsubscribe,unlock,lock,get-lock,set-pin"
+    };
+}
+
+/// Polecenia tekstowe obsługiwane przez oprogramowanie
+#[cfg(not(feature = "debug-shell"))]
+pub const COMMANDS: List<'static> = List(commands!());
+/// Polecenia tekstowe obsługiwane przez oprogramowanie, z poleceniami diagnostycznymi
+#[cfg(feature = "debug-shell")]
+pub const COMMANDS: List<'static> = List(concat!(commands!(), ",peek,poke,dump,stack"));
+
+/// Formaty pomiarów (`set format`)
+pub const FORMATS: List<'static> = List("human,csv,json,influx");
+
+/// Obsługiwane czujniki
+pub const SENSORS: List<'static> = List("dht11");
+
+/// Możliwości tej wersji oprogramowania
+pub const CAPS: Caps<'static> = Caps { protocol: PROTOCOL_VERSION, commands: COMMANDS, formats: FORMATS, sensors: SENSORS };
+
+/// Wersja protokołu `major.minor`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl Version {
+    /// Czy urządzenie z tą wersją protokołu obsługuje wszystko, czego oczekuje narzędzie
+    /// napisane dla wersji `required` (ta sama wersja główna, nie starsza podwersja)
+    /// # Examples
+    /// ```
+    /// use caps::Version;
+    ///
+    /// let device = Version::parse("1.2").unwrap();
+    /// assert!(device.supports(Version { major: 1, minor: 0 }));
+    /// assert!(!device.supports(Version { major: 2, minor: 0 }));
+    /// ```
+    pub fn supports(self, required: Version) -> bool {
+        self.major == required.major && self.minor >= required.minor
+    }
+
+    /// Wersja z tekstu "1.0"
+    pub fn parse(s: &str) -> Option<Version> {
+        let (major, minor) = s.split_once('.')?;
+        Some(Version { major: major.parse().ok()?, minor: minor.parse().ok()? })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Lista nazw rozdzielonych przecinkami, np. "human,csv,json"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct List<'a>(pub &'a str);
+
+impl<'a> List<'a> {
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> {
+        self.0.split(',').filter(|name| !name.is_empty())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.iter().any(|item| item.eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for List<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Błąd odczytu odpowiedzi `caps`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapsError {
+    /// Odpowiedź nie zaczyna się od `caps`
+    NotCaps,
+    /// Brak pola `protocol` lub wersja w złym formacie
+    BadVersion,
+}
+
+impl fmt::Display for CapsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CapsError::NotCaps => f.write_str("not a caps answer"),
+            CapsError::BadVersion => f.write_str("missing or invalid protocol version"),
+        }
+    }
+}
+
+/// Możliwości urządzenia
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caps<'a> {
+    pub protocol: Version,
+    pub commands: List<'a>,
+    pub formats: List<'a>,
+    pub sensors: List<'a>,
+}
+
+impl<'a> Caps<'a> {
+    /// Funkcja odczytująca treść odpowiedzi (bez `OK `), np. "caps protocol=1.0 commands=help,...".
+    /// Nieznane pola są pomijane, aby starsze narzędzia rozumiały nowsze oprogramowanie.
+    /// # Examples
+    /// ```
+    /// use caps::Caps;
+    /// # fn main() -> Result<(), caps::CapsError> {
+    ///
+    /// let caps = Caps::parse("caps protocol=1.0 commands=help,version formats=csv sensors=dht11")?;
+    /// assert!(caps.commands.contains("version"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parse(body: &'a str) -> Result<Caps<'a>, CapsError> {
+        let mut words = body.split_ascii_whitespace();
+        if words.next() != Some("caps") {
+            return Err(CapsError::NotCaps);
+        }
+        let mut protocol = None;
+        let mut caps = Caps {
+            protocol: Version { major: 0, minor: 0 },
+            commands: List::default(),
+            formats: List::default(),
+            sensors: List::default(),
+        };
+        for (key, value) in words.filter_map(|word| word.split_once('=')) {
+            match key {
+                "protocol" => protocol = Version::parse(value),
+                "commands" => caps.commands = List(value),
+                "formats" => caps.formats = List(value),
+                "sensors" => caps.sensors = List(value),
+                _ => {}
+            }
+        }
+        caps.protocol = protocol.ok_or(CapsError::BadVersion)?;
+        Ok(caps)
+    }
+}
+
+/// Format odpowiedzi bez `OK `: "caps protocol=1.0 commands=... formats=... sensors=..."
+impl fmt::Display for Caps<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "caps protocol={} commands={} formats={} sensors={}",
+            self.protocol, self.commands, self.formats, self.sensors
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn caps_round_trip() {
+        let text = CAPS.to_string();
+        assert!(text.starts_with("caps protocol=1.1 commands=help,version,"));
+        assert_eq!(Caps::parse(&text), Ok(CAPS));
+    }
+
+    #[test]
+    fn parse_skips_unknown_fields_and_needs_a_version() {
+        let caps = Caps::parse("caps protocol=1.3 sensors=dht11,dht22 units=si formats=csv").unwrap();
+        assert_eq!(caps.protocol, Version { major: 1, minor: 3 });
+        assert!(caps.sensors.contains("DHT22"));
+        assert!(caps.formats.contains("csv"));
+        assert_eq!(caps.commands.iter().count(), 0);
+        assert_eq!(Caps::parse("caps commands=help"), Err(CapsError::BadVersion));
+        assert_eq!(Caps::parse("caps protocol=one"), Err(CapsError::BadVersion));
+        assert_eq!(Caps::parse("version 0.1.0"), Err(CapsError::NotCaps));
+    }
+
+    #[test]
+    fn version_compatibility() {
+        let device = Version { major: 1, minor: 2 };
+        assert!(device.supports(Version { major: 1, minor: 0 }));
+        assert!(device.supports(Version { major: 1, minor: 2 }));
+        assert!(!device.supports(Version { major: 1, minor: 3 }));
+        assert!(!device.supports(Version { major: 2, minor: 0 }));
+        assert_eq!(Version::parse("2.10"), Some(Version { major: 2, minor: 10 }));
+    }
+}
@@ -0,0 +1,49 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+use stm32f3xx_hal::{
+    pac::TIM2,
+    rcc::{Clocks, APB1},
+    timer::{Instance, Timer},
+};
+
+/// Funkcja uruchamiająca zegar monotoniczny: 32-bitowy licznik TIM2 zwiększany co 1 ms.
+/// Licznik przepełnia się po ~49 dniach, dlatego odstępy czasu należy liczyć przez `elapsed_ms`.
+/// # Examples
+/// ```
+/// // W funkcji init()
+/// clock::start(dp.TIM2, clocks, &mut rcc.apb1);
+/// ```
+pub fn start(tim2: TIM2, clocks: Clocks, apb1: &mut APB1) {
+    let timer_clock = <TIM2 as Instance>::clock(&clocks).0;
+    // Timer::new włącza zegar peryferium i resetuje jego rejestry
+    let mut timer = Timer::new(tim2, clocks, apb1);
+    let tim2 = unsafe { timer.peripheral() };
+    tim2.psc.write(|w| w.psc().bits((timer_clock / 1_000 - 1) as u16));
+    tim2.arr.write(|w| unsafe { w.bits(u32::MAX) });
+    // Wymuszenie zdarzenia update ładuje preskaler, flaga zdarzenia jest czyszczona
+    tim2.egr.write(|w| w.ug().set_bit());
+    tim2.sr.modify(|_, w| w.uif().clear_bit());
+    tim2.cr1.modify(|_, w| w.cen().set_bit());
+}
+
+/// Funkcja zwracająca liczbę milisekund od uruchomienia zegara
+/// # Examples
+/// ```
+/// let start = clock::now_ms();
+/// ```
+pub fn now_ms() -> u32 {
+    // SAFETY: odczyt licznika nie ma efektów ubocznych, rejestr zapisywany jest tylko w `start`
+    unsafe { (*TIM2::ptr()).cnt.read().bits() }
+}
+
+/// Funkcja zwracająca liczbę milisekund od chwili `since`, z uwzględnieniem przepełnienia licznika
+/// # Examples
+/// ```
+/// if clock::elapsed_ms(start) > 1_000 {
+///     // minęła sekunda
+/// }
+/// ```
+pub fn elapsed_ms(since: u32) -> u32 {
+    now_ms().wrapping_sub(since)
+}
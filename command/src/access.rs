@@ -0,0 +1,218 @@
+use core::fmt;
+use sha2::{Digest, Sha256};
+
+/// Długość soli zapisywanej razem ze skrótem PIN-u
+pub const SALT_LEN: usize = 8;
+/// Długość skrótu PIN-u (SHA-256)
+pub const HASH_LEN: usize = 32;
+/// Dopuszczalna liczba cyfr PIN-u
+pub const PIN_DIGITS: core::ops::RangeInclusive<usize> = 4..=8;
+/// Czas bez poleceń, po którym urządzenie blokuje się ponownie
+pub const RELOCK_MS: u32 = 5 * 60_000;
+/// Liczba kolejnych błędnych prób, po której próby są wstrzymywane
+pub const MAX_FAILURES: u8 = 3;
+/// Czas wstrzymania prób po MAX_FAILURES błędach
+pub const BACKOFF_MS: u32 = 30_000;
+// Liczba powtórzeń skrótu, spowalnia zgadywanie PIN-u z odczytanej pamięci flash
+const HASH_ROUNDS: u32 = 256;
+
+/// Funkcja sprawdzająca format PIN-u: 4 do 8 cyfr
+pub fn is_valid_pin(pin: &str) -> bool {
+    PIN_DIGITS.contains(&pin.len()) && pin.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// PIN zapisany jako solony skrót SHA-256, sam PIN nie jest przechowywany
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinHash {
+    pub salt: [u8; SALT_LEN],
+    pub hash: [u8; HASH_LEN],
+}
+
+impl PinHash {
+    /// Funkcja tworząca skrót PIN-u z podaną solą (np. z licznika cykli w chwili polecenia)
+    pub fn new(pin: &str, salt: [u8; SALT_LEN]) -> Self {
+        PinHash { salt, hash: hash_pin(pin, &salt) }
+    }
+
+    /// Porównanie bez wczesnego wyjścia, czas nie zdradza zgodnych bajtów
+    pub fn matches(&self, pin: &str) -> bool {
+        let hash = hash_pin(pin, &self.salt);
+        hash.iter().zip(self.hash.iter()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+}
+
+// Pierwsza runda liczona jest z soli i PIN-u, kolejne także z poprzedniego skrótu
+fn hash_pin(pin: &str, salt: &[u8; SALT_LEN]) -> [u8; HASH_LEN] {
+    let pin = &pin.as_bytes()[..pin.len().min(*PIN_DIGITS.end())];
+    let mut hash: [u8; HASH_LEN] = Sha256::new().chain_update(salt).chain_update(pin).finalize().into();
+    for _ in 1..HASH_ROUNDS {
+        hash = Sha256::new().chain_update(hash).chain_update(salt).chain_update(pin).finalize().into();
+    }
+    hash
+}
+
+/// Błędy odblokowania
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessError {
+    /// Błędny PIN
+    WrongPin,
+    /// Zbyt wiele błędnych prób, kolejna możliwa po podanej liczbie ms
+    Backoff(u32),
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessError::WrongPin => f.write_str("wrong pin"),
+            AccessError::Backoff(ms) => write!(f, "too many attempts, retry in {} s", ms.div_ceil(1_000)),
+        }
+    }
+}
+
+/// Stan blokady poleceń zmieniających konfigurację. Bez ustawionego PIN-u urządzenie
+/// jest zawsze odblokowane. Czas `now_ms` pochodzi z zegara monotonicznego.
+/// # Examples
+/// ```ignore
+/// if command.is_write() && access.is_locked(clock::now_ms()) {
+///     command::err(serial, ErrorCode::Locked, format_args!("unlock first")).ok();
+/// }
+/// ```
+pub struct Access {
+    pin: Option<PinHash>,
+    // Czas ostatniego polecenia po odblokowaniu
+    unlocked_at: Option<u32>,
+    failures: u8,
+    last_failure_ms: u32,
+}
+
+impl Access {
+    pub const fn new(pin: Option<PinHash>) -> Self {
+        Access { pin, unlocked_at: None, failures: 0, last_failure_ms: 0 }
+    }
+
+    pub fn pin(&self) -> Option<PinHash> {
+        self.pin
+    }
+
+    /// Ustawienie lub usunięcie PIN-u, urządzenie pozostaje odblokowane do upływu RELOCK_MS
+    pub fn set_pin(&mut self, pin: Option<PinHash>, now_ms: u32) {
+        self.pin = pin;
+        self.unlocked_at = Some(now_ms);
+    }
+
+    /// Funkcja sprawdzająca blokadę, po RELOCK_MS bez poleceń urządzenie blokuje się samo
+    pub fn is_locked(&mut self, now_ms: u32) -> bool {
+        if self.pin.is_none() {
+            return false;
+        }
+        match self.unlocked_at {
+            Some(at) if now_ms.wrapping_sub(at) < RELOCK_MS => false,
+            _ => {
+                self.unlocked_at = None;
+                true
+            }
+        }
+    }
+
+    /// Przedłużenie odblokowania po wykonanym poleceniu
+    pub fn touch(&mut self, now_ms: u32) {
+        if self.unlocked_at.is_some() && !self.is_locked(now_ms) {
+            self.unlocked_at = Some(now_ms);
+        }
+    }
+
+    /// Funkcja odblokowująca polecenia zapisu. Po MAX_FAILURES błędach kolejne próby
+    /// są odrzucane bez sprawdzania przez BACKOFF_MS od ostatniej błędnej próby.
+    pub fn unlock(&mut self, pin: &str, now_ms: u32) -> Result<(), AccessError> {
+        let Some(stored) = self.pin else {
+            return Ok(());
+        };
+        let since_failure = now_ms.wrapping_sub(self.last_failure_ms);
+        if self.failures >= MAX_FAILURES && since_failure < BACKOFF_MS {
+            return Err(AccessError::Backoff(BACKOFF_MS - since_failure));
+        }
+        if stored.matches(pin) {
+            self.failures = 0;
+            self.unlocked_at = Some(now_ms);
+            Ok(())
+        } else {
+            self.failures = self.failures.saturating_add(1);
+            self.last_failure_ms = now_ms;
+            self.unlocked_at = None;
+            Err(AccessError::WrongPin)
+        }
+    }
+
+    /// Natychmiastowe zablokowanie
+    pub fn lock(&mut self) {
+        self.unlocked_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SALT: [u8; SALT_LEN] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    #[test]
+    fn hash_is_stable_for_stored_pins() {
+        // Skrót zapisany w konfiguracji musi pozostać zgodny między wersjami oprogramowania
+        assert_eq!(
+            PinHash::new("2468", SALT).hash,
+            [
+                0xcd, 0x16, 0x5d, 0x36, 0x6f, 0xd2, 0x28, 0x0e, 0xe3, 0xb0, 0x34, 0x23, 0x71, 0xec, 0x23, 0xd2,
+                0x90, 0xec, 0x78, 0xbc, 0xda, 0xaa, 0xf8, 0x89, 0x67, 0xd0, 0x45, 0x68, 0xc5, 0x32, 0x67, 0x24,
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_pin_format() {
+        assert!(is_valid_pin("1234"));
+        assert!(is_valid_pin("12345678"));
+        assert!(!is_valid_pin("123"));
+        assert!(!is_valid_pin("123456789"));
+        assert!(!is_valid_pin("12a4"));
+    }
+
+    #[test]
+    fn salted_hash_checks_pin() {
+        let pin = PinHash::new("2468", SALT);
+        assert!(pin.matches("2468"));
+        assert!(!pin.matches("2469"));
+        assert_ne!(PinHash::new("2468", [0; SALT_LEN]).hash, pin.hash);
+    }
+
+    #[test]
+    fn without_pin_access_is_open() {
+        let mut access = Access::new(None);
+        assert!(!access.is_locked(0));
+        assert_eq!(access.unlock("0000", 0), Ok(()));
+    }
+
+    #[test]
+    fn unlocks_and_relocks_after_inactivity() {
+        let mut access = Access::new(Some(PinHash::new("2468", SALT)));
+        assert!(access.is_locked(0));
+        assert_eq!(access.unlock("2468", 1_000), Ok(()));
+        assert!(!access.is_locked(1_000 + RELOCK_MS - 1));
+        access.touch(1_000 + RELOCK_MS - 1);
+        assert!(!access.is_locked(2 * RELOCK_MS));
+        assert!(access.is_locked(3 * RELOCK_MS));
+        access.lock();
+        assert!(access.is_locked(3 * RELOCK_MS));
+    }
+
+    #[test]
+    fn backs_off_after_repeated_failures() {
+        let mut access = Access::new(Some(PinHash::new("2468", SALT)));
+        for now in 0..MAX_FAILURES as u32 {
+            assert_eq!(access.unlock("0000", now), Err(AccessError::WrongPin));
+        }
+        let last = MAX_FAILURES as u32 - 1;
+        // Nawet poprawny PIN jest odrzucany do końca wstrzymania
+        assert_eq!(access.unlock("2468", last + 10_000), Err(AccessError::Backoff(BACKOFF_MS - 10_000)));
+        assert_eq!(access.unlock("2468", last + BACKOFF_MS), Ok(()));
+    }
+}
@@ -0,0 +1,973 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Parser tekstowego języka poleceń termometru.
+//! Każde polecenie to jedna linia, odpowiedź zaczyna się od `OK` lub `ERR <kod> <opis>`.
+//! Crate nie zależy od sprzętu, dzięki czemu może być testowany na komputerze
+//! i używany przez narzędzia po stronie PC.
+
+use core::fmt::{self, Write};
+use reporting::{Format, Policy, Stream};
+
+mod access;
+pub use access::*;
+
+/// Adres rozgłoszeniowy poleceń `@0 ...`, na który urządzenia nie odpowiadają
+pub const BROADCAST: u8 = 0;
+/// Zakres adresów urządzeń, taki sam jak w Modbus RTU
+pub const MIN_ADDRESS: u8 = 1;
+pub const MAX_ADDRESS: u8 = 247;
+/// Najkrótszy dopuszczalny okres próbkowania (DHT11 wymaga co najmniej 1 s)
+pub const MIN_INTERVAL_MS: u32 = 1_000;
+/// Najdłuższy dopuszczalny okres próbkowania
+pub const MAX_INTERVAL_MS: u32 = 3_600_000;
+
+// Polecenia dostępne w każdej wersji oprogramowania
+macro_rules! help {
+    () => {
+        "help, version, info, caps, reset, get bounds, set bounds <cold,optimal,hot,critical>, \
+get reading, get interval, set interval <ms>, get protocol, set protocol <text|binary|modbus>, \
+get format, set format <human|csv|json|influx>, get address, set address <1-247>, \
+get serial, set serial <baud> [8N1|8E1|8O1|8N2|8E2|8O2], get uart, clear uart, get shell, set shell <on|off>, \
+get rs485, set rs485 <on|off>, get report, set report interval <ms>, \
+set report change <temp> <hum> <keepalive ms>, get subscriptions, subscribe <readings|zones|errors>, \
+unsubscribe <readings|zones|errors>, unlock <pin>, lock, get lock, set pin <4-8 digits|off>, @<address> <command>"
+    };
+}
+
+/// Lista poleceń wysyłana w odpowiedzi na `help`
+#[cfg(not(feature = "debug-shell"))]
+pub const HELP: &str = help!();
+/// Lista poleceń wysyłana w odpowiedzi na `help`, z poleceniami diagnostycznymi
+#[cfg(feature = "debug-shell")]
+pub const HELP: &str = concat!(
+    help!(),
+    ", peek <address>, poke <address> <value>, dump <rcc|gpioa-gpiof|usart1|i2c1>, stack"
+);
+
+/// Prędkości akceptowane przez `set serial`
+pub const BAUD_RATES: [u32; 8] = [1_200, 2_400, 4_800, 9_600, 19_200, 38_400, 57_600, 115_200];
+
+/// Parzystość portu szeregowego
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+/// Liczba bitów stopu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopBits {
+    One,
+    Two,
+}
+
+/// Parametry portu szeregowego ustawiane poleceniem `set serial`, zawsze 8 bitów danych
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialSettings {
+    pub baud: u32,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+}
+
+impl Default for SerialSettings {
+    fn default() -> Self {
+        SerialSettings {
+            baud: 115_200,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+        }
+    }
+}
+
+/// Format "115200 8N1"
+impl fmt::Display for SerialSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Even => 'E',
+            Parity::Odd => 'O',
+        };
+        let stop_bits = match self.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        };
+        write!(f, "{} 8{}{}", self.baud, parity, stop_bits)
+    }
+}
+
+// Odczyt formatu znaku "8N1"
+fn parse_char_format(s: &str) -> Option<(Parity, StopBits)> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 3 || bytes[0] != b'8' {
+        return None;
+    }
+    let parity = match bytes[1].to_ascii_uppercase() {
+        b'N' => Parity::None,
+        b'E' => Parity::Even,
+        b'O' => Parity::Odd,
+        _ => return None,
+    };
+    let stop_bits = match bytes[2] {
+        b'1' => StopBits::One,
+        b'2' => StopBits::Two,
+        _ => return None,
+    };
+    Some((parity, stop_bits))
+}
+
+/// Protokół portu szeregowego wybierany poleceniem `set protocol`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolMode {
+    Text,
+    Binary,
+    Modbus,
+}
+
+/// Polecenie odczytane z linii
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command<'a> {
+    /// `help` - lista poleceń
+    Help,
+    /// `version` - wersja oprogramowania
+    Version,
+    /// `info` - wersja, commit, profil budowania, czas pracy i przyczyna restartu
+    Info,
+    /// `caps` - wersja protokołu, obsługiwane polecenia, formaty i czujniki (crate `caps`)
+    Caps,
+    /// `reset` - restart mikrokontrolera
+    Reset,
+    /// `get bounds` - granice temperatur
+    GetBounds,
+    /// `set bounds <cold,optimal,hot,critical>` - ciąg granic do sprawdzenia przez urządzenie
+    SetBounds(&'a str),
+    /// `get reading` - ostatni pomiar
+    GetReading,
+    /// `get interval` - okres próbkowania
+    GetInterval,
+    /// `set interval <ms>` - zmiana okresu próbkowania
+    SetInterval(u32),
+    /// `get protocol` - aktualny protokół
+    GetProtocol,
+    /// `set protocol <text|binary|modbus>` - przełączenie protokołu (powrót wiadomością TextMode
+    /// lub zapisem rejestru Modbus)
+    SetProtocol(ProtocolMode),
+    /// `get format` - format wysyłanych pomiarów
+    GetFormat,
+    /// `set format <human|csv|json|influx>` - zmiana formatu pomiarów
+    SetFormat(Format),
+    /// `get address` - adres urządzenia na magistrali
+    GetAddress,
+    /// `set address <1-247>` - zmiana adresu
+    SetAddress(u8),
+    /// `get serial` - parametry portu szeregowego
+    GetSerial,
+    /// `set serial <baud> [8N1]` - zmiana parametrów po wysłaniu odpowiedzi
+    SetSerial(SerialSettings),
+    /// `get uart` - liczniki błędów odbioru i odrzuconych bajtów portu szeregowego
+    GetUart,
+    /// `clear uart` - wyzerowanie liczników `get uart`
+    ClearUart,
+    /// `get shell` - czy włączona jest interaktywna powłoka
+    GetShell,
+    /// `set shell on|off` - echo, edycja linii i znak zachęty dla terminali
+    SetShell(bool),
+    /// `get rs485` - czy włączony jest tryb magistrali RS-485
+    GetRs485,
+    /// `set rs485 on|off` - sterowanie nadajnikiem RS-485 i odpowiedzi tylko na polecenia adresowane
+    SetRs485(bool),
+    /// `get report` - zasada wysyłania pomiarów
+    GetReport,
+    /// `set report interval <ms>` lub `set report change <temp> <hum> <keepalive ms>`
+    SetReport(Policy),
+    /// `get subscriptions` - strumienie wysyłane bez zapytania
+    GetSubscriptions,
+    /// `subscribe <readings|zones|errors>` - włączenie strumienia
+    Subscribe(Stream),
+    /// `unsubscribe <readings|zones|errors>` - wyłączenie strumienia
+    Unsubscribe(Stream),
+    /// `unlock <pin>` - odblokowanie poleceń zapisu
+    Unlock(&'a str),
+    /// `lock` - natychmiastowe zablokowanie
+    Lock,
+    /// `get lock` - stan blokady
+    GetLock,
+    /// `set pin <pin>` lub `set pin off` - ustawienie lub usunięcie PIN-u
+    SetPin(Option<&'a str>),
+    /// `g` - dawne polecenie odczytu granic, odpowiedź w starym formacie
+    LegacyGet,
+    /// `s<cold,optimal,hot,critical>` - dawne polecenie zmiany granic
+    LegacySet(&'a str),
+    /// `peek <adres>` - odczyt słowa pamięci spod adresu podzielnego przez 4
+    #[cfg(feature = "debug-shell")]
+    Peek(u32),
+    /// `poke <adres> <wartość>` - zapis słowa pamięci
+    #[cfg(feature = "debug-shell")]
+    Poke(u32, u32),
+    /// `dump <blok>` - rejestry układu peryferyjnego o podanej nazwie, sprawdzanej przez urządzenie
+    #[cfg(feature = "debug-shell")]
+    Dump(&'a str),
+    /// `stack` - wskaźnik stosu i największe zużycie stosu
+    #[cfg(feature = "debug-shell")]
+    Stack,
+}
+
+impl Command<'_> {
+    /// Polecenia zmieniające konfigurację lub stan urządzenia, wymagające odblokowania PIN-em.
+    /// Odczyty oraz `unlock` i `lock` są zawsze dostępne.
+    pub fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Command::Reset
+                | Command::SetBounds(_)
+                | Command::SetInterval(_)
+                | Command::SetProtocol(_)
+                | Command::SetFormat(_)
+                | Command::SetAddress(_)
+                | Command::SetSerial(_)
+                | Command::SetShell(_)
+                | Command::SetRs485(_)
+                | Command::SetReport(_)
+                | Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::SetPin(_)
+                | Command::LegacySet(_)
+        ) || self.is_debug_write()
+    }
+
+    #[cfg(feature = "debug-shell")]
+    fn is_debug_write(&self) -> bool {
+        matches!(self, Command::Poke(_, _))
+    }
+
+    #[cfg(not(feature = "debug-shell"))]
+    fn is_debug_write(&self) -> bool {
+        false
+    }
+}
+
+/// Kody błędów wysyłane w odpowiedzi `ERR <kod> <opis>`
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Nieznane polecenie
+    UnknownCommand = 1,
+    /// Brak wymaganego argumentu
+    MissingArgument = 2,
+    /// Argument o złym formacie lub spoza zakresu
+    InvalidArgument = 3,
+    /// Nadmiarowe argumenty
+    TooManyArguments = 4,
+    /// Linia za długa lub niedokończona
+    BadLine = 5,
+    /// Urządzenie odrzuciło wartość (np. zła kolejność granic)
+    Rejected = 6,
+    /// Dane chwilowo niedostępne (np. brak poprawnego pomiaru)
+    Unavailable = 7,
+    /// Polecenie zapisu wymaga `unlock <pin>`, błędny PIN lub wstrzymane próby
+    Locked = 8,
+}
+
+impl ErrorCode {
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            ErrorCode::UnknownCommand => "unknown command",
+            ErrorCode::MissingArgument => "missing argument",
+            ErrorCode::InvalidArgument => "invalid argument",
+            ErrorCode::TooManyArguments => "too many arguments",
+            ErrorCode::BadLine => "bad line",
+            ErrorCode::Rejected => "rejected",
+            ErrorCode::Unavailable => "unavailable",
+            ErrorCode::Locked => "locked",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.code(), self.message())
+    }
+}
+
+// Podział na pierwsze słowo i resztę linii (bez białych znaków na początku)
+fn split_word(s: &str) -> (&str, &str) {
+    let s = s.trim_start();
+    match s.find(|c: char| c.is_ascii_whitespace()) {
+        Some(i) => (&s[..i], s[i..].trim_start()),
+        None => (s, ""),
+    }
+}
+
+// Słowo kluczowe bez rozróżniania wielkości liter
+fn is(word: &str, keyword: &str) -> bool {
+    word.eq_ignore_ascii_case(keyword)
+}
+
+fn no_arguments<'a>(rest: &str, command: Command<'a>) -> Result<Command<'a>, ErrorCode> {
+    if rest.is_empty() {
+        Ok(command)
+    } else {
+        Err(ErrorCode::TooManyArguments)
+    }
+}
+
+// Przełącznik "on" lub "off"
+fn parse_switch(switch: &str) -> Result<bool, ErrorCode> {
+    if switch.is_empty() {
+        Err(ErrorCode::MissingArgument)
+    } else if is(switch, "on") {
+        Ok(true)
+    } else if is(switch, "off") {
+        Ok(false)
+    } else {
+        Err(ErrorCode::InvalidArgument)
+    }
+}
+
+// Liczba wymaganego argumentu
+fn parse_number<T: core::str::FromStr>(word: &str) -> Result<T, ErrorCode> {
+    if word.is_empty() {
+        return Err(ErrorCode::MissingArgument);
+    }
+    word.parse().map_err(|_| ErrorCode::InvalidArgument)
+}
+
+// Zasada raportowania "interval <ms>" lub "change <temp> <hum> <keepalive ms>" i reszta linii
+fn parse_policy(s: &str) -> Result<(Policy, &str), ErrorCode> {
+    let (kind, rest) = split_word(s);
+    let (policy, rest) = if kind.is_empty() {
+        return Err(ErrorCode::MissingArgument);
+    } else if is(kind, "interval") {
+        let (ms, rest) = split_word(rest);
+        (Policy::Interval { interval_ms: parse_number(ms)? }, rest)
+    } else if is(kind, "change") {
+        let (temperature, rest) = split_word(rest);
+        let (humidity, rest) = split_word(rest);
+        let (keepalive_ms, rest) = split_word(rest);
+        let policy = Policy::Change {
+            temperature: parse_number(temperature)?,
+            humidity: parse_number(humidity)?,
+            keepalive_ms: parse_number(keepalive_ms)?,
+        };
+        (policy, rest)
+    } else {
+        return Err(ErrorCode::InvalidArgument);
+    };
+    if !policy.is_valid() {
+        return Err(ErrorCode::InvalidArgument);
+    }
+    Ok((policy, rest))
+}
+
+// Nazwa strumienia w `subscribe` i `unsubscribe`
+fn parse_stream(name: &str) -> Result<Stream, ErrorCode> {
+    if name.is_empty() {
+        Err(ErrorCode::MissingArgument)
+    } else {
+        Stream::from_name(name).ok_or(ErrorCode::InvalidArgument)
+    }
+}
+
+// Słowo 32-bitowe dziesiętnie lub szesnastkowo z przedrostkiem "0x"
+#[cfg(feature = "debug-shell")]
+fn parse_word(word: &str) -> Result<u32, ErrorCode> {
+    if word.is_empty() {
+        return Err(ErrorCode::MissingArgument);
+    }
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).map_err(|_| ErrorCode::InvalidArgument),
+        None => word.parse().map_err(|_| ErrorCode::InvalidArgument),
+    }
+}
+
+// Adres słowa pamięci, wyrównany do 4 bajtów
+#[cfg(feature = "debug-shell")]
+fn parse_word_address(word: &str) -> Result<u32, ErrorCode> {
+    let address = parse_word(word)?;
+    if address % 4 != 0 {
+        return Err(ErrorCode::InvalidArgument);
+    }
+    Ok(address)
+}
+
+// Polecenia diagnostyczne `peek`, `poke`, `dump` i `stack`, None dla pozostałych słów
+#[cfg(feature = "debug-shell")]
+fn parse_debug<'a>(word: &str, rest: &'a str) -> Result<Option<Command<'a>>, ErrorCode> {
+    let command = if is(word, "peek") {
+        let (address, rest) = split_word(rest);
+        no_arguments(rest, Command::Peek(parse_word_address(address)?))?
+    } else if is(word, "poke") {
+        let (address, rest) = split_word(rest);
+        let (value, rest) = split_word(rest);
+        no_arguments(rest, Command::Poke(parse_word_address(address)?, parse_word(value)?))?
+    } else if is(word, "dump") {
+        let (block, rest) = split_word(rest);
+        if block.is_empty() {
+            return Err(ErrorCode::MissingArgument);
+        }
+        no_arguments(rest, Command::Dump(block))?
+    } else if is(word, "stack") {
+        no_arguments(rest, Command::Stack)?
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(command))
+}
+
+#[cfg(not(feature = "debug-shell"))]
+fn parse_debug<'a>(_: &str, _: &'a str) -> Result<Option<Command<'a>>, ErrorCode> {
+    Ok(None)
+}
+
+/// Funkcja oddzielająca adres od polecenia adresowanego `@<adres> <polecenie>` (magistrala RS-485).
+/// Zwraca None dla linii bez adresu. Adres `BROADCAST` (0) oznacza wszystkie urządzenia.
+/// # Examples
+/// ```
+/// use command::ErrorCode;
+///
+/// assert_eq!(command::split_address("@12 get-bounds"), Ok(Some((12, "get-bounds"))));
+/// // Polecenie do wszystkich urządzeń
+/// assert_eq!(command::split_address("@0 version"), Ok(Some((0, "version"))));
+/// assert_eq!(command::split_address("get-bounds"), Ok(None));
+/// assert_eq!(command::split_address("@12"), Err(ErrorCode::MissingArgument));
+/// ```
+pub fn split_address(line: &str) -> Result<Option<(u8, &str)>, ErrorCode> {
+    let rest = match line.trim_start().strip_prefix('@') {
+        Some(rest) => rest,
+        None => return Ok(None),
+    };
+    let (address, command) = split_word(rest);
+    if address.is_empty() || command.is_empty() {
+        return Err(ErrorCode::MissingArgument);
+    }
+    let address: u8 = parse_number(address)?;
+    if address != BROADCAST && !(MIN_ADDRESS..=MAX_ADDRESS).contains(&address) {
+        return Err(ErrorCode::InvalidArgument);
+    }
+    Ok(Some((address, command)))
+}
+
+/// Funkcja parsująca linię polecenia (bez znaku końca linii)
+/// # Examples
+/// ```
+/// use command::{Command, ErrorCode};
+///
+/// assert_eq!(command::parse("set interval 2000"), Ok(Command::SetInterval(2000)));
+/// assert_eq!(command::parse("reboot"), Err(ErrorCode::UnknownCommand));
+/// ```
+pub fn parse(line: &str) -> Result<Command<'_>, ErrorCode> {
+    let line = line.trim();
+    // Dawne jednoznakowe polecenia "g" i "s<granice>"
+    if line == "g" {
+        return Ok(Command::LegacyGet);
+    }
+    if let Some(bounds) = line.strip_prefix('s') {
+        if bounds.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '.') {
+            return Ok(Command::LegacySet(bounds));
+        }
+    }
+
+    let (word, rest) = split_word(line);
+    if let Some(command) = parse_debug(word, rest)? {
+        return Ok(command);
+    }
+    if word.is_empty() {
+        Err(ErrorCode::UnknownCommand)
+    } else if is(word, "help") {
+        no_arguments(rest, Command::Help)
+    } else if is(word, "version") {
+        no_arguments(rest, Command::Version)
+    } else if is(word, "info") {
+        no_arguments(rest, Command::Info)
+    } else if is(word, "caps") {
+        no_arguments(rest, Command::Caps)
+    } else if is(word, "unlock") {
+        let (pin, rest) = split_word(rest);
+        if pin.is_empty() {
+            return Err(ErrorCode::MissingArgument);
+        }
+        no_arguments(rest, Command::Unlock(pin))
+    } else if is(word, "lock") {
+        no_arguments(rest, Command::Lock)
+    } else if is(word, "reset") {
+        no_arguments(rest, Command::Reset)
+    } else if is(word, "get") {
+        let (what, rest) = split_word(rest);
+        if what.is_empty() {
+            Err(ErrorCode::MissingArgument)
+        } else if is(what, "bounds") {
+            no_arguments(rest, Command::GetBounds)
+        } else if is(what, "reading") {
+            no_arguments(rest, Command::GetReading)
+        } else if is(what, "interval") {
+            no_arguments(rest, Command::GetInterval)
+        } else if is(what, "protocol") {
+            no_arguments(rest, Command::GetProtocol)
+        } else if is(what, "format") {
+            no_arguments(rest, Command::GetFormat)
+        } else if is(what, "address") {
+            no_arguments(rest, Command::GetAddress)
+        } else if is(what, "serial") {
+            no_arguments(rest, Command::GetSerial)
+        } else if is(what, "uart") {
+            no_arguments(rest, Command::GetUart)
+        } else if is(what, "shell") {
+            no_arguments(rest, Command::GetShell)
+        } else if is(what, "rs485") {
+            no_arguments(rest, Command::GetRs485)
+        } else if is(what, "report") {
+            no_arguments(rest, Command::GetReport)
+        } else if is(what, "subscriptions") {
+            no_arguments(rest, Command::GetSubscriptions)
+        } else if is(what, "lock") {
+            no_arguments(rest, Command::GetLock)
+        } else {
+            Err(ErrorCode::InvalidArgument)
+        }
+    } else if is(word, "subscribe") {
+        let (name, rest) = split_word(rest);
+        no_arguments(rest, Command::Subscribe(parse_stream(name)?))
+    } else if is(word, "unsubscribe") {
+        let (name, rest) = split_word(rest);
+        no_arguments(rest, Command::Unsubscribe(parse_stream(name)?))
+    } else if is(word, "clear") {
+        let (what, rest) = split_word(rest);
+        if what.is_empty() {
+            Err(ErrorCode::MissingArgument)
+        } else if is(what, "uart") {
+            no_arguments(rest, Command::ClearUart)
+        } else {
+            Err(ErrorCode::InvalidArgument)
+        }
+    } else if is(word, "set") {
+        let (what, value) = split_word(rest);
+        if what.is_empty() {
+            Err(ErrorCode::MissingArgument)
+        } else if is(what, "bounds") {
+            if value.is_empty() {
+                Err(ErrorCode::MissingArgument)
+            } else {
+                Ok(Command::SetBounds(value))
+            }
+        } else if is(what, "interval") {
+            let (ms, rest) = split_word(value);
+            let ms: u32 = parse_number(ms)?;
+            if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&ms) {
+                return Err(ErrorCode::InvalidArgument);
+            }
+            no_arguments(rest, Command::SetInterval(ms))
+        } else if is(what, "protocol") {
+            let (mode, rest) = split_word(value);
+            let mode = if mode.is_empty() {
+                return Err(ErrorCode::MissingArgument);
+            } else if is(mode, "text") {
+                ProtocolMode::Text
+            } else if is(mode, "binary") {
+                ProtocolMode::Binary
+            } else if is(mode, "modbus") {
+                ProtocolMode::Modbus
+            } else {
+                return Err(ErrorCode::InvalidArgument);
+            };
+            no_arguments(rest, Command::SetProtocol(mode))
+        } else if is(what, "format") {
+            let (name, rest) = split_word(value);
+            if name.is_empty() {
+                return Err(ErrorCode::MissingArgument);
+            }
+            let format = Format::from_name(name).ok_or(ErrorCode::InvalidArgument)?;
+            no_arguments(rest, Command::SetFormat(format))
+        } else if is(what, "address") {
+            let (address, rest) = split_word(value);
+            let address: u8 = parse_number(address)?;
+            if !(MIN_ADDRESS..=MAX_ADDRESS).contains(&address) {
+                return Err(ErrorCode::InvalidArgument);
+            }
+            no_arguments(rest, Command::SetAddress(address))
+        } else if is(what, "serial") {
+            let (baud, rest) = split_word(value);
+            let baud: u32 = parse_number(baud)?;
+            if !BAUD_RATES.contains(&baud) {
+                return Err(ErrorCode::InvalidArgument);
+            }
+            let (format, rest) = split_word(rest);
+            let (parity, stop_bits) = if format.is_empty() {
+                (Parity::None, StopBits::One)
+            } else {
+                parse_char_format(format).ok_or(ErrorCode::InvalidArgument)?
+            };
+            no_arguments(rest, Command::SetSerial(SerialSettings { baud, parity, stop_bits }))
+        } else if is(what, "shell") {
+            let (switch, rest) = split_word(value);
+            no_arguments(rest, Command::SetShell(parse_switch(switch)?))
+        } else if is(what, "rs485") {
+            let (switch, rest) = split_word(value);
+            no_arguments(rest, Command::SetRs485(parse_switch(switch)?))
+        } else if is(what, "report") {
+            let (policy, rest) = parse_policy(value)?;
+            no_arguments(rest, Command::SetReport(policy))
+        } else if is(what, "pin") {
+            let (pin, rest) = split_word(value);
+            let pin = if pin.is_empty() {
+                return Err(ErrorCode::MissingArgument);
+            } else if is(pin, "off") {
+                None
+            } else if is_valid_pin(pin) {
+                Some(pin)
+            } else {
+                return Err(ErrorCode::InvalidArgument);
+            };
+            no_arguments(rest, Command::SetPin(pin))
+        } else {
+            Err(ErrorCode::InvalidArgument)
+        }
+    } else {
+        Err(ErrorCode::UnknownCommand)
+    }
+}
+
+/// Funkcja wysyłająca odpowiedź pozytywną `OK <treść>`
+/// # Examples
+/// ```
+/// let mut reply = String::new();
+/// command::ok(&mut reply, format_args!("interval {}", 2000)).unwrap();
+/// assert_eq!(reply, "OK interval 2000\n");
+/// ```
+pub fn ok<W: Write>(w: &mut W, args: fmt::Arguments) -> fmt::Result {
+    w.write_str("OK")?;
+    let mut body = Body { w, separator: " ", empty: true };
+    fmt::write(&mut body, args)?;
+    w.write_str("\n")
+}
+
+/// Funkcja wysyłająca odpowiedź z błędem `ERR <kod> <opis>[: <szczegóły>]`
+/// # Examples
+/// ```
+/// use command::ErrorCode;
+///
+/// let mut reply = String::new();
+/// command::err(&mut reply, ErrorCode::Rejected, format_args!("{}", "interval too short")).unwrap();
+/// assert_eq!(reply, "ERR 6 rejected: interval too short\n");
+/// ```
+pub fn err<W: Write>(w: &mut W, code: ErrorCode, details: fmt::Arguments) -> fmt::Result {
+    write!(w, "ERR {}", code)?;
+    let mut body = Body { w, separator: ": ", empty: true };
+    fmt::write(&mut body, details)?;
+    w.write_str("\n")
+}
+
+// Dopisuje separator tylko wtedy, gdy treść odpowiedzi nie jest pusta
+struct Body<'w, W: Write> {
+    w: &'w mut W,
+    separator: &'static str,
+    empty: bool,
+}
+
+impl<W: Write> Write for Body<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        if self.empty {
+            self.empty = false;
+            self.w.write_str(self.separator)?;
+        }
+        self.w.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::string::String;
+    use std::vec::Vec;
+
+    #[test]
+    fn parses_simple_commands() {
+        assert_eq!(parse("help"), Ok(Command::Help));
+        assert_eq!(parse("version"), Ok(Command::Version));
+        assert_eq!(parse("info"), Ok(Command::Info));
+        assert_eq!(parse("reset"), Ok(Command::Reset));
+        assert_eq!(parse("get bounds"), Ok(Command::GetBounds));
+        assert_eq!(parse("get reading"), Ok(Command::GetReading));
+        assert_eq!(parse("get interval"), Ok(Command::GetInterval));
+    }
+
+    #[test]
+    fn ignores_case_and_extra_whitespace() {
+        assert_eq!(parse("  GET   Bounds "), Ok(Command::GetBounds));
+        assert_eq!(parse("Help"), Ok(Command::Help));
+        assert_eq!(parse("set\tinterval  2000"), Ok(Command::SetInterval(2000)));
+    }
+
+    #[test]
+    fn parses_set_bounds_argument_verbatim() {
+        assert_eq!(parse("set bounds 0,25,30,35"), Ok(Command::SetBounds("0,25,30,35")));
+        assert_eq!(parse("set bounds 0, 25, 30, 35"), Ok(Command::SetBounds("0, 25, 30, 35")));
+        assert_eq!(parse("set bounds"), Err(ErrorCode::MissingArgument));
+    }
+
+    #[test]
+    fn validates_interval() {
+        assert_eq!(parse("set interval 1000"), Ok(Command::SetInterval(MIN_INTERVAL_MS)));
+        assert_eq!(parse("set interval 3600000"), Ok(Command::SetInterval(MAX_INTERVAL_MS)));
+        assert_eq!(parse("set interval 999"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set interval 3600001"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set interval -5"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set interval 2s"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set interval"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("set interval 2000 3000"), Err(ErrorCode::TooManyArguments));
+    }
+
+    #[test]
+    fn parses_protocol_selection() {
+        assert_eq!(parse("get protocol"), Ok(Command::GetProtocol));
+        assert_eq!(parse("set protocol binary"), Ok(Command::SetProtocol(ProtocolMode::Binary)));
+        assert_eq!(parse("set protocol TEXT"), Ok(Command::SetProtocol(ProtocolMode::Text)));
+        assert_eq!(parse("set protocol modbus"), Ok(Command::SetProtocol(ProtocolMode::Modbus)));
+        assert_eq!(parse("set protocol can"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set protocol"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("set protocol text now"), Err(ErrorCode::TooManyArguments));
+    }
+
+    #[test]
+    fn parses_format_selection() {
+        assert_eq!(parse("get format"), Ok(Command::GetFormat));
+        assert_eq!(parse("set format csv"), Ok(Command::SetFormat(Format::Csv)));
+        assert_eq!(parse("set format JSON"), Ok(Command::SetFormat(Format::Json)));
+        assert_eq!(parse("set format influx"), Ok(Command::SetFormat(Format::Influx)));
+        assert_eq!(parse("set format human"), Ok(Command::SetFormat(Format::Human)));
+        assert_eq!(parse("set format xml"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set format"), Err(ErrorCode::MissingArgument));
+    }
+
+    #[test]
+    fn parses_address() {
+        assert_eq!(parse("get address"), Ok(Command::GetAddress));
+        assert_eq!(parse("set address 17"), Ok(Command::SetAddress(17)));
+        assert_eq!(parse("set address 0"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set address 248"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set address"), Err(ErrorCode::MissingArgument));
+    }
+
+    #[test]
+    fn parses_serial_settings() {
+        assert_eq!(parse("get serial"), Ok(Command::GetSerial));
+        assert_eq!(parse("set serial 9600"), Ok(Command::SetSerial(SerialSettings { baud: 9_600, ..Default::default() })));
+        assert_eq!(
+            parse("set serial 19200 8e2"),
+            Ok(Command::SetSerial(SerialSettings { baud: 19_200, parity: Parity::Even, stop_bits: StopBits::Two }))
+        );
+        assert_eq!(parse("set serial 9601"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set serial 9600 7N1"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set serial 9600 8N1 now"), Err(ErrorCode::TooManyArguments));
+        assert_eq!(parse("set serial"), Err(ErrorCode::MissingArgument));
+    }
+
+    #[test]
+    fn parses_rs485_switch() {
+        assert_eq!(parse("get rs485"), Ok(Command::GetRs485));
+        assert_eq!(parse("set rs485 on"), Ok(Command::SetRs485(true)));
+        assert_eq!(parse("set rs485 Off"), Ok(Command::SetRs485(false)));
+        assert_eq!(parse("set rs485"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("set rs485 maybe"), Err(ErrorCode::InvalidArgument));
+        assert!(parse("set rs485 on").unwrap().is_write());
+    }
+
+    #[test]
+    fn parses_report_policies_and_subscriptions() {
+        assert_eq!(parse("get report"), Ok(Command::GetReport));
+        assert_eq!(
+            parse("set report interval 10000"),
+            Ok(Command::SetReport(Policy::Interval { interval_ms: 10_000 }))
+        );
+        assert_eq!(
+            parse("set report change 0.5 2 60000"),
+            Ok(Command::SetReport(Policy::Change { temperature: 0.5, humidity: 2.0, keepalive_ms: 60_000 }))
+        );
+        assert_eq!(parse("set report"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("set report change 0.5 2"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("set report change 0.5 2 500"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set report change 0 2 60000"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set report often"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set report interval 1000 2"), Err(ErrorCode::TooManyArguments));
+
+        assert_eq!(parse("get subscriptions"), Ok(Command::GetSubscriptions));
+        assert_eq!(parse("subscribe zones"), Ok(Command::Subscribe(Stream::Zones)));
+        assert_eq!(parse("UNSUBSCRIBE readings"), Ok(Command::Unsubscribe(Stream::Readings)));
+        assert_eq!(parse("subscribe"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("subscribe weather"), Err(ErrorCode::InvalidArgument));
+        assert!(parse("subscribe errors").unwrap().is_write());
+    }
+
+    #[test]
+    fn advertised_capabilities_match_the_parser() {
+        for id in caps::COMMANDS.iter() {
+            let line = id.replace('-', " ");
+            // Bez argumentów polecenie może być niepełne, ale musi być rozpoznane
+            assert!(
+                !matches!(parse(&line), Err(ErrorCode::UnknownCommand | ErrorCode::InvalidArgument)),
+                "{} not parsed",
+                line
+            );
+            assert!(HELP.contains(line.as_str()), "{} missing from help", line);
+        }
+        let formats: Vec<&str> = caps::FORMATS.iter().collect();
+        let names: Vec<&str> = Format::ALL.iter().map(|f| f.name()).collect();
+        assert_eq!(formats, names);
+    }
+
+    #[test]
+    fn addresses_match_modbus() {
+        // Ten sam adres urządzenia obowiązuje w poleceniach tekstowych i w Modbus RTU
+        assert_eq!(BROADCAST, modbus::BROADCAST);
+        assert_eq!((MIN_ADDRESS, MAX_ADDRESS), (modbus::MIN_ADDRESS, modbus::MAX_ADDRESS));
+    }
+
+    #[test]
+    fn splits_addressed_commands() {
+        assert_eq!(split_address("@17 get reading"), Ok(Some((17, "get reading"))));
+        assert_eq!(split_address("@0 set interval 2000"), Ok(Some((BROADCAST, "set interval 2000"))));
+        assert_eq!(split_address("  @5   *IDN?"), Ok(Some((5, "*IDN?"))));
+        assert_eq!(split_address("get reading"), Ok(None));
+        assert_eq!(split_address("@17"), Err(ErrorCode::MissingArgument));
+        assert_eq!(split_address("@ get reading"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(split_address("@248 help"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(split_address("@x help"), Err(ErrorCode::InvalidArgument));
+    }
+
+    #[test]
+    fn parses_uart_diagnostics() {
+        assert_eq!(parse("get uart"), Ok(Command::GetUart));
+        assert_eq!(parse("clear uart"), Ok(Command::ClearUart));
+        assert_eq!(parse("clear"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("clear bounds"), Err(ErrorCode::InvalidArgument));
+        assert!(!parse("clear uart").unwrap().is_write());
+    }
+
+    #[test]
+    fn parses_shell_switch() {
+        assert_eq!(parse("get shell"), Ok(Command::GetShell));
+        assert_eq!(parse("set shell ON"), Ok(Command::SetShell(true)));
+        assert_eq!(parse("set shell off"), Ok(Command::SetShell(false)));
+        assert_eq!(parse("set shell maybe"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set shell"), Err(ErrorCode::MissingArgument));
+    }
+
+    #[test]
+    fn parses_access_commands() {
+        assert_eq!(parse("unlock 2468"), Ok(Command::Unlock("2468")));
+        assert_eq!(parse("unlock"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("lock"), Ok(Command::Lock));
+        assert_eq!(parse("get lock"), Ok(Command::GetLock));
+        assert_eq!(parse("set pin 2468"), Ok(Command::SetPin(Some("2468"))));
+        assert_eq!(parse("set pin off"), Ok(Command::SetPin(None)));
+        assert_eq!(parse("set pin 12"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set pin abcd"), Err(ErrorCode::InvalidArgument));
+    }
+
+    #[test]
+    fn only_writes_need_unlocking() {
+        assert!(parse("set bounds 0,25,30,35").unwrap().is_write());
+        assert!(parse("s0,25,30,35").unwrap().is_write());
+        assert!(parse("reset").unwrap().is_write());
+        assert!(parse("set pin off").unwrap().is_write());
+        assert!(!parse("get bounds").unwrap().is_write());
+        assert!(!parse("g").unwrap().is_write());
+        assert!(!parse("unlock 2468").unwrap().is_write());
+        assert!(!parse("lock").unwrap().is_write());
+    }
+
+    #[cfg(feature = "debug-shell")]
+    #[test]
+    fn parses_debug_commands() {
+        assert_eq!(parse("peek 0x40021000"), Ok(Command::Peek(0x4002_1000)));
+        assert_eq!(parse("PEEK 536870912"), Ok(Command::Peek(0x2000_0000)));
+        assert_eq!(parse("peek 0x40021002"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("peek"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("poke 0x48001014 0xff00"), Ok(Command::Poke(0x4800_1014, 0xff00)));
+        assert_eq!(parse("poke 0x48001014"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("poke 0x48001014 0x1ffffffff"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("dump gpioe"), Ok(Command::Dump("gpioe")));
+        assert_eq!(parse("dump rcc usart1"), Err(ErrorCode::TooManyArguments));
+        assert_eq!(parse("stack"), Ok(Command::Stack));
+        assert!(parse("poke 0x20000000 1").unwrap().is_write());
+        assert!(!parse("peek 0x20000000").unwrap().is_write());
+        assert!(HELP.ends_with("stack"));
+    }
+
+    #[cfg(not(feature = "debug-shell"))]
+    #[test]
+    fn debug_commands_are_compiled_out() {
+        assert_eq!(parse("peek 0x40021000"), Err(ErrorCode::UnknownCommand));
+        assert_eq!(parse("stack"), Err(ErrorCode::UnknownCommand));
+        assert!(!HELP.contains("peek"));
+    }
+
+    #[test]
+    fn formats_serial_settings() {
+        let mut out = String::new();
+        write!(out, "{}", SerialSettings { baud: 9_600, parity: Parity::Odd, stop_bits: StopBits::One }).unwrap();
+        assert_eq!(out, "9600 8O1");
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_commands() {
+        assert_eq!(parse(""), Err(ErrorCode::UnknownCommand));
+        assert_eq!(parse("hello"), Err(ErrorCode::UnknownCommand));
+        assert_eq!(parse("get"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("get humidity"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("set"), Err(ErrorCode::MissingArgument));
+        assert_eq!(parse("set colour red"), Err(ErrorCode::InvalidArgument));
+        assert_eq!(parse("help me"), Err(ErrorCode::TooManyArguments));
+        assert_eq!(parse("get bounds now"), Err(ErrorCode::TooManyArguments));
+    }
+
+    #[test]
+    fn keeps_legacy_commands() {
+        assert_eq!(parse("g"), Ok(Command::LegacyGet));
+        assert_eq!(parse("s0,25,30,35"), Ok(Command::LegacySet("0,25,30,35")));
+        assert_eq!(parse("s-5,0,10,20"), Ok(Command::LegacySet("-5,0,10,20")));
+        // słowa zaczynające się od 's' nie są dawnym poleceniem
+        assert_eq!(parse("set bounds 1,2,3,4"), Ok(Command::SetBounds("1,2,3,4")));
+        assert_eq!(parse("status"), Err(ErrorCode::UnknownCommand));
+    }
+
+    #[test]
+    fn formats_ok_responses() {
+        let mut out = String::new();
+        ok(&mut out, format_args!("")).unwrap();
+        ok(&mut out, format_args!("interval {}", 2000)).unwrap();
+        assert_eq!(out, "OK\nOK interval 2000\n");
+    }
+
+    #[test]
+    fn formats_error_responses() {
+        let mut out = String::new();
+        err(&mut out, ErrorCode::UnknownCommand, format_args!("")).unwrap();
+        err(&mut out, ErrorCode::Rejected, format_args!("'hot' must be greater than '{}'", "optimal")).unwrap();
+        assert_eq!(
+            out,
+            "ERR 1 unknown command\nERR 6 rejected: 'hot' must be greater than 'optimal'\n"
+        );
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(ErrorCode::UnknownCommand.code(), 1);
+        assert_eq!(ErrorCode::MissingArgument.code(), 2);
+        assert_eq!(ErrorCode::InvalidArgument.code(), 3);
+        assert_eq!(ErrorCode::TooManyArguments.code(), 4);
+        assert_eq!(ErrorCode::BadLine.code(), 5);
+        assert_eq!(ErrorCode::Rejected.code(), 6);
+        assert_eq!(ErrorCode::Unavailable.code(), 7);
+        assert_eq!(ErrorCode::Locked.code(), 8);
+    }
+}
@@ -0,0 +1,102 @@
+use stm32f3xx_hal::pac;
+
+use crate::{Config, ConfigError, RECORD_SIZE};
+
+/// Adres ostatniej strony (2 KB) pamięci flash STM32F303VC (256 KB), zarezerwowanej na konfigurację.
+/// Strona leży poza obszarem FLASH z memory.x, więc linker nie umieści w niej kodu.
+pub const PAGE_ADDRESS: u32 = 0x0803_F800;
+
+// Klucze odblokowujące zapis (RM0316, 4.2.1)
+const KEY1: u32 = 0x4567_0123;
+const KEY2: u32 = 0xCDEF_89AB;
+
+/// Błędy zapisu do pamięci flash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashError {
+    /// Strona jest chroniona przed zapisem
+    WriteProtected,
+    /// Zapis nie powiódł się (komórka nie była skasowana)
+    Programming,
+    /// Odczytany rekord różni się od zapisanego
+    Verify,
+}
+
+impl core::fmt::Display for FlashError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FlashError::WriteProtected => f.write_str("flash is write protected"),
+            FlashError::Programming => f.write_str("flash programming failed"),
+            FlashError::Verify => f.write_str("flash verification failed"),
+        }
+    }
+}
+
+/// Funkcja odczytująca konfigurację zapisaną w pamięci flash
+pub fn load() -> Result<Config, ConfigError> {
+    // SAFETY: strona konfiguracji leży w pamięci flash i jest zawsze dostępna do odczytu
+    let record = unsafe { core::slice::from_raw_parts(PAGE_ADDRESS as *const u8, RECORD_SIZE) };
+    Config::decode(record)
+}
+
+/// Funkcja zapisująca konfigurację: kasuje stronę i programuje rekord półsłowami.
+/// Podczas kasowania (ok. 40 ms) procesor czeka na pamięć flash, przerwania są opóźnione.
+pub fn store(config: &Config) -> Result<(), FlashError> {
+    let record = config.encode();
+    // SAFETY: moduł jest jedynym użytkownikiem rejestrów zapisu FLASH, `init` używa tylko ACR
+    let flash = unsafe { &*pac::FLASH::ptr() };
+    unlock(flash);
+    let result = erase(flash).and_then(|_| program(flash, &record));
+    flash.cr.modify(|_, w| w.lock().set_bit());
+    result?;
+
+    match load() {
+        Ok(stored) if stored == *config => Ok(()),
+        _ => Err(FlashError::Verify),
+    }
+}
+
+fn unlock(flash: &pac::flash::RegisterBlock) {
+    if flash.cr.read().lock().bit_is_set() {
+        flash.keyr.write(|w| w.fkeyr().bits(KEY1));
+        flash.keyr.write(|w| w.fkeyr().bits(KEY2));
+    }
+}
+
+fn erase(flash: &pac::flash::RegisterBlock) -> Result<(), FlashError> {
+    flash.cr.modify(|_, w| w.per().set_bit());
+    flash.ar.write(|w| w.far().bits(PAGE_ADDRESS));
+    flash.cr.modify(|_, w| w.strt().set_bit());
+    let result = wait(flash);
+    flash.cr.modify(|_, w| w.per().clear_bit());
+    result
+}
+
+fn program(flash: &pac::flash::RegisterBlock, record: &[u8; RECORD_SIZE]) -> Result<(), FlashError> {
+    flash.cr.modify(|_, w| w.pg().set_bit());
+    let mut result = Ok(());
+    for (i, half) in record.chunks_exact(2).enumerate() {
+        let address = (PAGE_ADDRESS as usize + 2 * i) as *mut u16;
+        // SAFETY: adres leży na skasowanej stronie konfiguracji, zapis półsłowa przy PG = 1
+        unsafe { core::ptr::write_volatile(address, u16::from_le_bytes([half[0], half[1]])) };
+        result = wait(flash);
+        if result.is_err() {
+            break;
+        }
+    }
+    flash.cr.modify(|_, w| w.pg().clear_bit());
+    result
+}
+
+// Oczekiwanie na koniec operacji i sprawdzenie flag błędów (czyszczonych przez zapis 1)
+fn wait(flash: &pac::flash::RegisterBlock) -> Result<(), FlashError> {
+    while flash.sr.read().bsy().bit_is_set() {}
+    let sr = flash.sr.read();
+    flash.sr.write(|w| w.eop().set_bit().pgerr().set_bit().wrprterr().set_bit());
+    if sr.wrprterr().bit_is_set() {
+        Err(FlashError::WriteProtected)
+    } else if sr.pgerr().bit_is_set() {
+        Err(FlashError::Programming)
+    } else {
+        Ok(())
+    }
+}
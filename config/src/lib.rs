@@ -0,0 +1,340 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Konfiguracja termometru zachowywana po restarcie.
+//!
+//! Rekord: `"TCFG"`, wersja, długość danych, dane (little endian), CRC-16 z całości.
+//! Nowe pola dopisywane są na końcu danych; pola brakujące w starszym rekordzie
+//! przyjmują wartości domyślne, więc dodanie ustawienia nie kasuje pozostałych.
+
+use core::fmt;
+use reporting::{Format, Policy, Streams};
+
+#[cfg(target_os = "none")]
+mod flash;
+#[cfg(target_os = "none")]
+pub use flash::*;
+
+const MAGIC: [u8; 4] = *b"TCFG";
+const VERSION: u8 = 1;
+// Magia, wersja i długość danych
+const HEADER: usize = 6;
+// Granice (4 x f32), okres próbkowania (u32), format (u8), protokół (u8), adres (u8),
+// prędkość portu (u32), parzystość (u8), bity stopu (u8), powłoka (u8),
+// PIN: znacznik (u8), sól i skrót, tryb RS-485 (u8),
+// zasada raportowania: rodzaj (u8), okres lub podtrzymanie (u32), progi (2 x f32), strumienie (u8)
+const PAYLOAD: usize = 16 + 4 + 1 + 1 + 1 + 4 + 1 + 1 + 1 + 1 + PIN_SALT_LEN + PIN_HASH_LEN + 1 + 1 + 4 + 8 + 1;
+/// Długość soli PIN-u
+pub const PIN_SALT_LEN: usize = 8;
+/// Długość skrótu PIN-u
+pub const PIN_HASH_LEN: usize = 32;
+/// Rozmiar zapisanego rekordu, zaokrąglony do półsłowa zapisywanego do flash
+pub const RECORD_SIZE: usize = (HEADER + PAYLOAD + 2 + 1) & !1;
+
+/// Ustawienia zmieniane poleceniami
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Config {
+    /// Granice temperatur cold, optimal, hot, critical (sprawdzane przez `TemperatureBoundaries`)
+    pub bounds: [f32; 4],
+    /// Okres próbkowania w ms
+    pub interval_ms: u32,
+    /// Format pomiarów wysyłanych przez port szeregowy
+    pub format: Format,
+    /// Protokół po uruchomieniu: 0 tekstowy, 1 binarny, 2 Modbus RTU
+    pub protocol: u8,
+    /// Adres urządzenia na magistrali (Modbus)
+    pub address: u8,
+    /// Prędkość portu szeregowego w bodach
+    pub baud: u32,
+    /// Parzystość: 0 brak, 1 parzysta, 2 nieparzysta
+    pub parity: u8,
+    /// Liczba bitów stopu: 1 lub 2
+    pub stop_bits: u8,
+    /// Interaktywna powłoka dla terminali (echo, edycja linii, znak zachęty)
+    pub shell: bool,
+    /// Solony skrót PIN-u odblokowującego polecenia zapisu, None gdy blokada jest wyłączona
+    pub pin: Option<PinRecord>,
+    /// Półdupleksowa magistrala RS-485: sterowanie nadajnikiem i polecenia adresowane `@<adres>`
+    pub rs485: bool,
+    /// Zasada wysyłania pomiarów (okresowo lub po zmianie)
+    pub report: Policy,
+    /// Strumienie wysyłane bez zapytania
+    pub streams: Streams,
+}
+
+/// Zapisany PIN: sól i skrót (sam PIN nie jest przechowywany)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinRecord {
+    pub salt: [u8; PIN_SALT_LEN],
+    pub hash: [u8; PIN_HASH_LEN],
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            bounds: [0.0, 25.0, 30.0, 35.0],
+            interval_ms: 1_000,
+            format: Format::Human,
+            protocol: 0,
+            address: 1,
+            baud: 115_200,
+            parity: 0,
+            stop_bits: 1,
+            shell: false,
+            pin: None,
+            rs485: false,
+            report: Policy::default(),
+            streams: Streams::default(),
+        }
+    }
+}
+
+/// Błędy odczytu konfiguracji
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// Pamięć jest skasowana, konfiguracja nie była jeszcze zapisana
+    Empty,
+    /// Niezgodna magia, wersja lub suma kontrolna
+    Corrupted,
+    /// Rekord jest poprawny, ale zawiera nieznaną wartość
+    Invalid,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Empty => f.write_str("no stored configuration"),
+            ConfigError::Corrupted => f.write_str("stored configuration is corrupted"),
+            ConfigError::Invalid => f.write_str("stored configuration has invalid values"),
+        }
+    }
+}
+
+impl Config {
+    /// Funkcja kodująca konfigurację do rekordu zapisywanego w pamięci flash
+    pub fn encode(&self) -> [u8; RECORD_SIZE] {
+        let mut record = [0xFF; RECORD_SIZE];
+        record[..4].copy_from_slice(&MAGIC);
+        record[4] = VERSION;
+        record[5] = PAYLOAD as u8;
+        let mut w = Writer { buf: &mut record[HEADER..HEADER + PAYLOAD], pos: 0 };
+        for bound in self.bounds {
+            w.put(&bound.to_le_bytes());
+        }
+        w.put(&self.interval_ms.to_le_bytes());
+        w.put(&[self.format.id()]);
+        w.put(&[self.protocol]);
+        w.put(&[self.address]);
+        w.put(&self.baud.to_le_bytes());
+        w.put(&[self.parity]);
+        w.put(&[self.stop_bits]);
+        w.put(&[self.shell as u8]);
+        match self.pin {
+            Some(pin) => {
+                w.put(&[1]);
+                w.put(&pin.salt);
+                w.put(&pin.hash);
+            }
+            None => w.put(&[0; 1 + PIN_SALT_LEN + PIN_HASH_LEN]),
+        }
+        w.put(&[self.rs485 as u8]);
+        let (kind, ms, temperature, humidity) = match self.report {
+            Policy::Interval { interval_ms } => (0, interval_ms, 0.0, 0.0),
+            Policy::Change { temperature, humidity, keepalive_ms } => (1, keepalive_ms, temperature, humidity),
+        };
+        w.put(&[kind]);
+        w.put(&ms.to_le_bytes());
+        w.put(&f32::to_le_bytes(temperature));
+        w.put(&f32::to_le_bytes(humidity));
+        w.put(&[self.streams.bits()]);
+        let crc = protocol::crc16(&record[..HEADER + PAYLOAD]);
+        record[HEADER + PAYLOAD..HEADER + PAYLOAD + 2].copy_from_slice(&crc.to_le_bytes());
+        record
+    }
+
+    /// Funkcja odczytująca konfigurację z rekordu, brakujące pola przyjmują wartości domyślne
+    pub fn decode(record: &[u8]) -> Result<Config, ConfigError> {
+        if record.iter().take(HEADER).all(|&b| b == 0xFF) {
+            return Err(ConfigError::Empty);
+        }
+        if record.len() < HEADER || record[..4] != MAGIC || record[4] != VERSION {
+            return Err(ConfigError::Corrupted);
+        }
+        let len = HEADER + record[5] as usize;
+        let stored = record.get(len..len + 2).ok_or(ConfigError::Corrupted)?;
+        if protocol::crc16(&record[..len]) != u16::from_le_bytes([stored[0], stored[1]]) {
+            return Err(ConfigError::Corrupted);
+        }
+
+        let mut config = Config::default();
+        let mut r = Reader { buf: &record[HEADER..len], pos: 0 };
+        for bound in config.bounds.iter_mut() {
+            if let Some(bytes) = r.take() {
+                *bound = f32::from_le_bytes(bytes);
+            }
+        }
+        if let Some(bytes) = r.take() {
+            config.interval_ms = u32::from_le_bytes(bytes);
+        }
+        if let Some([id]) = r.take() {
+            config.format = Format::from_id(id).ok_or(ConfigError::Invalid)?;
+        }
+        if let Some([protocol]) = r.take() {
+            config.protocol = protocol;
+        }
+        if let Some([address]) = r.take() {
+            config.address = address;
+        }
+        if let Some(bytes) = r.take() {
+            config.baud = u32::from_le_bytes(bytes);
+        }
+        if let Some([parity]) = r.take() {
+            config.parity = parity;
+        }
+        if let Some([stop_bits]) = r.take() {
+            config.stop_bits = stop_bits;
+        }
+        if let Some([shell]) = r.take() {
+            config.shell = match shell {
+                0 => false,
+                1 => true,
+                _ => return Err(ConfigError::Invalid),
+            };
+        }
+        if let (Some([set]), Some(salt), Some(hash)) = (r.take(), r.take(), r.take()) {
+            config.pin = match set {
+                0 => None,
+                1 => Some(PinRecord { salt, hash }),
+                _ => return Err(ConfigError::Invalid),
+            };
+        }
+        if let Some([rs485]) = r.take() {
+            config.rs485 = match rs485 {
+                0 => false,
+                1 => true,
+                _ => return Err(ConfigError::Invalid),
+            };
+        }
+        if let (Some([kind]), Some(ms), Some(temperature), Some(humidity)) = (r.take(), r.take(), r.take(), r.take()) {
+            let ms = u32::from_le_bytes(ms);
+            config.report = match kind {
+                0 => Policy::Interval { interval_ms: ms },
+                1 => Policy::Change {
+                    temperature: f32::from_le_bytes(temperature),
+                    humidity: f32::from_le_bytes(humidity),
+                    keepalive_ms: ms,
+                },
+                _ => return Err(ConfigError::Invalid),
+            };
+        }
+        if let Some([streams]) = r.take() {
+            config.streams = Streams::from_bits(streams).ok_or(ConfigError::Invalid)?;
+        }
+        Ok(config)
+    }
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    // None gdy pole nie mieści się w danych (rekord ze starszej wersji programu)
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.buf.get(self.pos..self.pos + N)?;
+        self.pos += N;
+        bytes.try_into().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom() -> Config {
+        Config {
+            bounds: [5.0, 20.5, 28.0, 40.0],
+            interval_ms: 5_000,
+            format: Format::Json,
+            protocol: 2,
+            address: 17,
+            baud: 9_600,
+            parity: 1,
+            stop_bits: 2,
+            shell: true,
+            pin: Some(PinRecord { salt: [7; PIN_SALT_LEN], hash: [0xA5; PIN_HASH_LEN] }),
+            rs485: true,
+            report: Policy::Change { temperature: 0.5, humidity: 2.0, keepalive_ms: 60_000 },
+            streams: Streams::NONE,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(Config::decode(&custom().encode()), Ok(custom()));
+        assert_eq!(Config::decode(&Config::default().encode()), Ok(Config::default()));
+    }
+
+    #[test]
+    fn erased_flash_is_empty() {
+        assert_eq!(Config::decode(&[0xFF; RECORD_SIZE]), Err(ConfigError::Empty));
+    }
+
+    #[test]
+    fn detects_corruption() {
+        let record = custom().encode();
+        for i in 0..HEADER + PAYLOAD + 2 {
+            let mut corrupted = record;
+            corrupted[i] ^= 0x04;
+            assert!(Config::decode(&corrupted).is_err(), "byte {} not checked", i);
+        }
+        assert_eq!(Config::decode(&record[..HEADER + 3]), Err(ConfigError::Corrupted));
+    }
+
+    #[test]
+    fn rejects_unknown_format() {
+        let mut record = custom().encode();
+        // Bajt formatu leży za granicami i okresem próbkowania
+        record[HEADER + 20] = 9;
+        let crc = protocol::crc16(&record[..HEADER + PAYLOAD]);
+        record[HEADER + PAYLOAD..HEADER + PAYLOAD + 2].copy_from_slice(&crc.to_le_bytes());
+        assert_eq!(Config::decode(&record), Err(ConfigError::Invalid));
+    }
+
+    #[test]
+    fn fields_missing_from_older_records_use_defaults() {
+        // Rekord zawierający tylko granice
+        let mut record = [0xFF; RECORD_SIZE];
+        record[..4].copy_from_slice(&MAGIC);
+        record[4] = VERSION;
+        record[5] = 16;
+        for (i, bound) in [1.0f32, 2.0, 3.0, 4.0].iter().enumerate() {
+            record[HEADER + 4 * i..HEADER + 4 * i + 4].copy_from_slice(&bound.to_le_bytes());
+        }
+        let crc = protocol::crc16(&record[..HEADER + 16]);
+        record[HEADER + 16..HEADER + 18].copy_from_slice(&crc.to_le_bytes());
+
+        let config = Config::decode(&record).unwrap();
+        assert_eq!(config.bounds, [1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(config.interval_ms, Config::default().interval_ms);
+        assert_eq!(config.format, Format::Human);
+        assert_eq!(config.address, Config::default().address);
+        assert_eq!(config.baud, Config::default().baud);
+        assert!(!config.rs485);
+        assert_eq!(config.report, Policy::default());
+        assert_eq!(config.streams, Streams::default());
+    }
+}
@@ -0,0 +1,194 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+use core::fmt;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// Kody błędów sygnalizowane miganiem diody LED.
+/// Wartość kodu to liczba mignięć w jednej grupie.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultCode {
+    /// Czujnik DHT11 nie odpowiada
+    SensorTimeout = 2,
+    /// Błędna suma kontrolna odczytu z DHT11
+    Checksum = 3,
+    /// Wyświetlacz LCD nie odpowiada na magistrali I2C
+    LcdNotFound = 4,
+    /// Zapisana konfiguracja jest uszkodzona
+    ConfigCorrupted = 5,
+}
+
+impl FaultCode {
+    /// Wszystkie kody w kolejności rosnącej
+    pub const ALL: [FaultCode; 4] = [
+        FaultCode::SensorTimeout,
+        FaultCode::Checksum,
+        FaultCode::LcdNotFound,
+        FaultCode::ConfigCorrupted,
+    ];
+
+    /// Liczba mignięć w grupie
+    pub fn blinks(self) -> u8 {
+        self as u8
+    }
+
+    /// Opis błędu
+    pub fn name(self) -> &'static str {
+        match self {
+            FaultCode::SensorTimeout => "sensor timeout",
+            FaultCode::Checksum => "checksum error",
+            FaultCode::LcdNotFound => "LCD not found",
+            FaultCode::ConfigCorrupted => "config corrupted",
+        }
+    }
+
+    fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+impl fmt::Display for FaultCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Zbiór aktywnych błędów (maska bitowa, bit = kod błędu)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Faults(u8);
+
+impl Faults {
+    /// Maska bitowa aktywnych błędów, bit n oznacza kod błędu n
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(self, code: FaultCode) -> bool {
+        self.0 & code.mask() != 0
+    }
+
+    /// Aktywne błędy w kolejności rosnącej
+    pub fn iter(self) -> impl Iterator<Item = FaultCode> {
+        FaultCode::ALL.into_iter().filter(move |code| self.contains(*code))
+    }
+
+    /// Następny aktywny błąd po `previous` (z zawinięciem), pozwala pokazywać kolejno wszystkie błędy
+    pub fn next_after(self, previous: Option<FaultCode>) -> Option<FaultCode> {
+        let after = previous.map_or(0, |code| code as u8);
+        self.iter()
+            .find(|code| *code as u8 > after)
+            .or_else(|| self.iter().next())
+    }
+}
+
+/// Ogólny stan urządzenia wynikający z aktywnych błędów
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Health {
+    /// Brak błędów
+    Ok,
+    /// Urządzenie mierzy, ale część funkcji nie działa (np. brak LCD)
+    Degraded,
+    /// Brak poprawnego pomiaru
+    Error,
+}
+
+impl Faults {
+    /// Funkcja wyznaczająca stan urządzenia: błędy czujnika oznaczają `Error`, pozostałe `Degraded`
+    pub fn health(self) -> Health {
+        if self.contains(FaultCode::SensorTimeout) || self.contains(FaultCode::Checksum) {
+            Health::Error
+        } else if self.is_empty() {
+            Health::Ok
+        } else {
+            Health::Degraded
+        }
+    }
+}
+
+// Rejestr błędów wspólny dla całego programu, dostępny również z przerwań
+static ACTIVE: AtomicU8 = AtomicU8::new(0);
+
+/// Funkcja zgłaszająca wystąpienie błędu
+/// # Examples
+/// ```
+/// use fault::FaultCode;
+///
+/// fault::report(FaultCode::SensorTimeout);
+/// assert!(fault::active().contains(FaultCode::SensorTimeout));
+/// ```
+pub fn report(code: FaultCode) {
+    ACTIVE.fetch_or(code.mask(), Ordering::Relaxed);
+}
+
+/// Funkcja usuwająca błąd z rejestru, np. po udanym odczycie z czujnika
+pub fn clear(code: FaultCode) {
+    ACTIVE.fetch_and(!code.mask(), Ordering::Relaxed);
+}
+
+/// Funkcja zwracająca aktualnie aktywne błędy
+/// # Examples
+/// ```
+/// use fault::FaultCode;
+///
+/// fault::report(FaultCode::Checksum);
+/// for code in fault::active().iter() {
+///     println!("Fault {}: {}", code.blinks(), code);
+/// }
+/// ```
+pub fn active() -> Faults {
+    Faults(ACTIVE.load(Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn faults(codes: &[FaultCode]) -> Faults {
+        Faults(codes.iter().fold(0, |bits, code| bits | code.mask()))
+    }
+
+    #[test]
+    fn next_after_cycles_through_active_faults() {
+        let f = faults(&[FaultCode::SensorTimeout, FaultCode::LcdNotFound]);
+        assert_eq!(f.next_after(None), Some(FaultCode::SensorTimeout));
+        assert_eq!(f.next_after(Some(FaultCode::SensorTimeout)), Some(FaultCode::LcdNotFound));
+        // Po ostatnim aktywnym błędzie wraca do pierwszego
+        assert_eq!(f.next_after(Some(FaultCode::LcdNotFound)), Some(FaultCode::SensorTimeout));
+        // Poprzedni błąd mógł zostać usunięty w międzyczasie
+        assert_eq!(f.next_after(Some(FaultCode::Checksum)), Some(FaultCode::LcdNotFound));
+        assert_eq!(Faults::default().next_after(Some(FaultCode::Checksum)), None);
+    }
+
+    #[test]
+    fn health_follows_the_most_severe_fault() {
+        assert_eq!(Faults::default().health(), Health::Ok);
+        assert_eq!(faults(&[FaultCode::LcdNotFound]).health(), Health::Degraded);
+        assert_eq!(faults(&[FaultCode::ConfigCorrupted]).health(), Health::Degraded);
+        assert_eq!(faults(&[FaultCode::LcdNotFound, FaultCode::Checksum]).health(), Health::Error);
+        assert_eq!(faults(&[FaultCode::SensorTimeout]).health(), Health::Error);
+    }
+
+    // Jedyny test korzystający ze wspólnego rejestru `ACTIVE`, testy działają równolegle
+    #[test]
+    fn report_and_clear_update_the_register() {
+        assert!(active().is_empty());
+        report(FaultCode::Checksum);
+        report(FaultCode::ConfigCorrupted);
+        report(FaultCode::Checksum);
+        assert_eq!(active(), faults(&[FaultCode::Checksum, FaultCode::ConfigCorrupted]));
+        assert_eq!(active().bits(), 0b0010_1000);
+
+        clear(FaultCode::Checksum);
+        clear(FaultCode::LcdNotFound);
+        assert!(!active().contains(FaultCode::Checksum));
+        assert!(active().iter().eq([FaultCode::ConfigCorrupted]));
+
+        clear(FaultCode::ConfigCorrupted);
+        assert!(active().is_empty());
+    }
+}
@@ -7,13 +7,128 @@ use hal::delay::Delay;
 use stm32f3xx_hal::{
     self as hal,
     prelude::*,
-    serial::Serial,
-    pac::{self, USART1, usart1, NVIC, I2C1},
+    rcc::Clocks,
+    serial::{config::{Config as SerialConfig, Parity}, Instance, Serial},
+    pac::{self, USART1, NVIC, I2C1},
     gpio::{Output, PushPull, Gpiob, Gpioc, Gpioe, Ux, Pin, U, Alternate, OpenDrain},
     interrupt,
     i2c::I2c,
 };
+#[cfg(feature = "rgb-strip")]
+use stm32f3xx_hal::{spi::Spi, pac::SPI2};
 
+/// Częstotliwość zegara systemowego (i licznika cykli DWT)
+pub const SYSCLK_HZ: u32 = 48_000_000;
+/// Prędkość USART1
+pub const BAUD_RATE: u32 = 115_200;
+
+/// Typ interfejsu USART1 (TX PC4, RX PC5) używanego przez `usart_1::SerialPort`
+pub type Usart1 = Serial<USART1, (Pin<Gpioc, U<4>, Alternate<PushPull, 7>>, Pin<Gpioc, U<5>, Alternate<PushPull, 7>>)>;
+
+/// Kanał DMA nadawania USART1 (DMA1, kanał 4)
+pub type Usart1TxDma = hal::dma::dma1::C4;
+
+/// Funkcja zmieniająca prędkość, parzystość i bity stopu działającego USART1,
+/// w tej samej kolejności co `Serial::new`. Bufor nadawczy powinien być wcześniej opróżniony.
+/// `clocks` to zegary zwrócone przez `init`.
+/// # Examples
+/// ```
+/// use stm32f3xx_hal::serial::config::{Config, Parity};
+///
+/// serial.flush();
+/// configure_usart1(serial.interface(), &clocks, Config::default().baudrate(9600.Bd()).parity(Parity::Even));
+/// ```
+pub fn configure_usart1(usart: &mut Usart1, clocks: &Clocks, config: SerialConfig) {
+    use pac::usart1::cr1::{M_A, PCE_A, PS_A};
+
+    // Jedyny dostęp do rejestrów, interfejs jest wyłączony na czas zmiany
+    let usart = unsafe { usart.peripheral() };
+    usart.cr1.modify(|_, w| w.ue().disabled());
+    let brr = usart_brr(USART1::clock(clocks).0, config.baudrate.0);
+    usart.brr.write(|w| w.brr().bits(brr));
+    // Bit parzystości liczony jest do długości słowa, dane mają zawsze 8 bitów
+    let (m0, ps, pce) = match config.parity {
+        Parity::None => (M_A::BIT8, PS_A::EVEN, PCE_A::DISABLED),
+        Parity::Even => (M_A::BIT9, PS_A::EVEN, PCE_A::ENABLED),
+        Parity::Odd => (M_A::BIT9, PS_A::ODD, PCE_A::ENABLED),
+    };
+    usart.cr2.modify(|_, w| w.stop().variant(config.stopbits.into()));
+    usart.cr1.modify(|_, w| {
+        w.ps().variant(ps);
+        w.pce().variant(pce);
+        w.m().variant(m0)
+    });
+    usart.cr1.modify(|_, w| w.ue().enabled());
+}
+
+// Dzielnik prędkości dla nadpróbkowania x16. Zegar USART1 wybierany jest tak jak w `Serial::new`
+// (PCLK2 lub źródło z RCC_CFGR3), zaokrąglenie do najbliższej wartości zmniejsza błąd prędkości.
+fn usart_brr(clock_hz: u32, baud: u32) -> u16 {
+    let brr = (clock_hz + baud / 2) / baud;
+    assert!(brr >= 16, "impossible baud rate");
+    brr as u16
+}
+
+/// Funkcja przełączająca PA12 na sygnał DE nadajnika RS-485 (USART1_DE, AF7) lub z powrotem
+/// na wejście, w którym pozostaje po restarcie. Na płytce STM32F3Discovery PA12 to także USB_DP,
+/// dlatego pin jest przejmowany tylko w trybie RS-485. Wywoływana razem z `set_driver_enable`.
+/// # Examples
+/// ```
+/// configure_de_pin(state.rs485);
+/// serial.interface().set_driver_enable(state.rs485);
+/// ```
+pub fn configure_de_pin(enable: bool) {
+    // SAFETY: pozostałe piny GPIOA nie są używane przez program, zmieniane są tylko pola PA12
+    let gpioa = unsafe { &*pac::GPIOA::ptr() };
+    gpioa.afrh.modify(|_, w| w.afrh12().af7());
+    if enable {
+        gpioa.moder.modify(|_, w| w.moder12().alternate());
+    } else {
+        gpioa.moder.modify(|_, w| w.moder12().input());
+    }
+}
+
+/// Funkcja zwracająca flagi przyczyny restartu z RCC_CSR i kasująca je, aby kolejny
+/// restart nie był mylony z poprzednim. Wywoływana raz, na początku programu.
+/// # Examples
+/// ```
+/// let reset_cause = protocol::ResetCause::from_rcc_csr(take_reset_flags());
+/// ```
+pub fn take_reset_flags() -> u32 {
+    // SAFETY: CSR nie jest używany przez HAL, zapis RMVF kasuje tylko flagi restartu
+    let rcc = unsafe { &*pac::RCC::ptr() };
+    let flags = rcc.csr.read().bits();
+    rcc.csr.modify(|_, w| w.rmvf().set_bit());
+    flags
+}
+
+/// Typ interfejsu SPI2 (SCK PB13, MISO PB14, MOSI PB15) używanego przez pasek diod WS2812
+#[cfg(feature = "rgb-strip")]
+pub type RgbSpi = Spi<SPI2, (Pin<Gpiob, U<13>, Alternate<PushPull, 5>>, Pin<Gpiob, U<14>, Alternate<PushPull, 5>>, Pin<Gpiob, U<15>, Alternate<PushPull, 5>>)>;
+
+
+/// Typ tablicy diod LED płytki (PE8-PE15)
+pub type LedPins = [Pin<Gpioe, Ux, Output<PushPull>>; 8];
+
+/// Typ interfejsu I2C1 (SCL PB6, SDA PB7) używanego przez wyświetlacz LCD
+pub type LcdI2c = I2c<I2C1, (Pin<Gpiob, U<6>, Alternate<OpenDrain, 4>>, Pin<Gpiob, U<7>, Alternate<OpenDrain, 4>>)>;
+
+/// Typ pinu danych czujnika DHT11 (PC1)
+pub type DhtPin = Pin<Gpioc, U<1>, Output<OpenDrain>>;
+
+/// Komponenty mikrokontrolera skonfigurowane przez `init`
+pub struct Board {
+    pub usart: Usart1,
+    pub usart_dma: Usart1TxDma,
+    pub leds: LedPins,
+    pub i2c: LcdI2c,
+    pub delay: Delay,
+    pub dht_pin: DhtPin,
+    #[cfg(feature = "rgb-strip")]
+    pub rgb_spi: RgbSpi,
+    /// Zegary potrzebne do zmiany prędkości USART1 (`configure_usart1`)
+    pub clocks: Clocks,
+}
 
 /// Funkcja ta inicjuje komponenty wymagane do działania termometru.
 /// # Examples
@@ -21,17 +136,16 @@ use stm32f3xx_hal::{
 /// // Załączenie bilioteki
 /// use init::*;
 /// // Inicjalizacja komponentów
-/// let (usart, leds_array) = init();
+/// let Board { usart, usart_dma, leds, i2c, delay, dht_pin, clocks, .. } = init();
 /// ```
-pub fn init() -> (&'static mut usart1::RegisterBlock, 
-                [Pin<Gpioe, Ux, Output<PushPull>>; 8],
-                I2c<I2C1, (Pin<Gpiob, U<6>, Alternate<OpenDrain, 4>>, Pin<Gpiob, U<7>, Alternate<OpenDrain, 4>>)>,
-                Delay,
-                Pin<Gpioc, U<1>, Output<OpenDrain>>) 
+pub fn init() -> Board
 {
     // Inicjalizacja komponentów
     let dp = pac::Peripherals::take().unwrap();
     let mut cp = cortex_m::peripheral::Peripherals::take().unwrap();
+    // Licznik cykli DWT (czas ramek Modbus, sól PIN-u) liczy tylko przy włączonym DEMCR.TRCENA,
+    // ustawianym inaczej wyłącznie przez podłączony debugger
+    cp.DCB.enable_trace();
     cp.DWT.enable_cycle_counter();
 
     let mut flash = dp.FLASH.constrain();
@@ -41,13 +155,17 @@ pub fn init() -> (&'static mut usart1::RegisterBlock,
     let clocks = rcc
             .cfgr
             .use_hse(8.MHz())
-            .sysclk(48.MHz())
+            .sysclk((SYSCLK_HZ / 1_000_000).MHz())
             .freeze(&mut flash.acr);
 
     let delay = stm32f3xx_hal::delay::Delay::new(cp.SYST, clocks);
+    // Uruchomienie zegara milisekundowego (TIM2), SysTick jest zajęty przez Delay
+    clock::start(dp.TIM2, clocks, &mut rcc.apb1);
     // Aktywacja nasłuchiwania przerwania USART1_EXTI25 (odbiór danych)
     unsafe {
         NVIC::unmask(interrupt::USART1_EXTI25);
+        // Zakończenie nadawania przez DMA
+        NVIC::unmask(interrupt::DMA1_CH4);
     }    
     // Przypisanie wyjśc GPIOX do zmiennych
     let mut gpioc = dp.GPIOC.split(&mut rcc.ahb);
@@ -57,9 +175,12 @@ pub fn init() -> (&'static mut usart1::RegisterBlock,
     // Przypisanie pinów rx/tx wraz z ustawieniem trybu
     let tx = gpioc.pc4.into_af_push_pull(&mut gpioc.moder, &mut gpioc.otyper, &mut gpioc.afrl);
     let rx = gpioc.pc5.into_af_push_pull(&mut gpioc.moder, &mut gpioc.otyper, &mut gpioc.afrl);
+    // Włączenie zegara GPIOA, PA12 (USART1_DE) przełącza dopiero `configure_de_pin`
+    let _ = dp.GPIOA.split(&mut rcc.ahb);
 
-    // Utworzenie komponentu USART1 z boundrate = 9600
-    Serial::new(dp.USART1, (tx, rx), 115_200.Bd(), clocks, &mut rcc.apb2);
+    // Utworzenie komponentu USART1 z boundrate = BAUD_RATE
+    let usart = Serial::new(dp.USART1, (tx, rx), BAUD_RATE.Bd(), clocks, &mut rcc.apb2);
+    let usart_dma = dp.DMA1.split(&mut rcc.ahb).ch4;
 
     // Tworzenie tablicy pinów LED
     let leds = [
@@ -93,7 +214,7 @@ pub fn init() -> (&'static mut usart1::RegisterBlock,
             .downgrade(),
         gpioe
             .pe8
-            .into_push_pull_output(&mut gpioe.moder, &mut &mut gpioe.otyper)
+            .into_push_pull_output(&mut gpioe.moder, &mut gpioe.otyper)
             .downgrade(),
     ];
     // Utworzenie pinów służących do komunikacji w interfejsie I2C
@@ -120,14 +241,31 @@ pub fn init() -> (&'static mut usart1::RegisterBlock,
     // Utworzenie pinu komunikującego się z DHT11
     let dht_pin = gpioc.pc1.into_open_drain_output(&mut gpioc.moder,&mut gpioc.otyper);
 
-    // Zwrócenie wskaznika do USART, tablicy LED
-    unsafe {
-        (
-            &mut *(USART1::ptr() as *mut _),
-            leds,
-            i2c,
-            delay,
-            dht_pin,
+    // Utworzenie interfejsu SPI2 dla paska WS2812, dane wysyłane są tylko linią MOSI (PB15)
+    #[cfg(feature = "rgb-strip")]
+    let rgb_spi = {
+        let sck = gpiob.pb13.into_af_push_pull(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrh);
+        let miso = gpiob.pb14.into_af_push_pull(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrh);
+        let mosi = gpiob.pb15.into_af_push_pull(&mut gpiob.moder, &mut gpiob.otyper, &mut gpiob.afrh);
+        Spi::new(
+            dp.SPI2,
+            (sck, miso, mosi),
+            ws2812::SPI_FREQUENCY_HZ.Hz(),
+            clocks,
+            &mut rcc.apb1,
         )
+    };
+
+    // Zwrócenie USART, tablicy LED oraz pozostałych komponentów
+    Board {
+        usart,
+        usart_dma,
+        leds,
+        i2c,
+        delay,
+        dht_pin,
+        #[cfg(feature = "rgb-strip")]
+        rgb_spi,
+        clocks,
     }
 }
\ No newline at end of file
@@ -4,8 +4,22 @@
 use cortex_m::prelude::_embedded_hal_blocking_delay_DelayMs;
 use stm32f3xx_hal::delay::Delay;
 use embedded_hal::digital::v2::{OutputPin};
+pub use boundaries::*;
+pub use ws2812::{Rgb, RgbWrite};
 
 
+/// Funkcja zwracająca liczbę zapalonych diod wskaźnika słupkowego o długości `len`:
+/// 0 poniżej `cold`, wszystkie od `critical` w górę
+pub fn gauge(tb: &TemperatureBoundaries, temperature: f32, len: usize) -> usize {
+    ws2812::gauge(temperature, tb.cold(), tb.critical(), len)
+}
+
+/// Funkcja zwracająca kolor temperatury: gradient od niebieskiego (`cold`)
+/// przez zielony (`optimal`) do czerwonego (`hot` i powyżej)
+pub fn color(tb: &TemperatureBoundaries, temperature: f32) -> Rgb {
+    ws2812::temperature_color(temperature, tb.cold(), tb.optimal(), tb.hot())
+}
+
 /// Struct zawierający możlwe błędy
 #[derive(Debug)]
 pub enum Error<E> {
@@ -13,54 +27,33 @@ pub enum Error<E> {
     Gpio(E),
 }
 
-/// Struct zawierający tablicę poziomów grnicznych temperatur
-pub struct TemperatureBoundaries {
-    pub cold: f32,
-    pub optimal: f32,
-    pub hot: f32,
-    pub critical: f32,
+/// Indeksy diod LED przypisanych do stref temperatury
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZoneLeds {
+    pub cold: usize,
+    pub optimal: usize,
+    pub hot: usize,
 }
 
-impl TemperatureBoundaries {
-    pub fn new(cold: f32, optimal: f32, hot: f32, critical: f32) -> Self {
-        Self {
-            cold,
-            optimal,
-            hot,
-            critical,
-        }
-    }
-    pub fn set_cold(&mut self, cold: f32) {
-        self.cold = cold;
-    }
-    pub fn set_optimal(&mut self, optimal: f32) {
-        self.optimal = optimal;
-    }
-    pub fn set_hot(&mut self, hot: f32) {
-        self.hot = hot;
-    }
-    pub fn set_critical(&mut self, critical: f32) {
-        self.critical = critical;
-    }
-
-    pub fn set_from_string(&mut self, string: &str) -> Result<(), Error<&str>> {
-        let mut iter = string.split(',');
-        let cold = iter.next();
-        let optimal = iter.next();
-        let hot = iter.next();
-        let critical = iter.next();
-        if cold.is_none() || optimal.is_none() || hot.is_none() || critical.is_none() {
-            return Err(Error::ArrayOutOfBounds);
+impl ZoneLeds {
+    /// Domyślne przypisanie dla N diod: na płytce STM32F3Discovery (8 LED)
+    /// niebieska, zielona i czerwona dioda, w pozostałych przypadkach kolejne diody od 0
+    pub const fn for_len(n: usize) -> Self {
+        if n == 8 {
+            ZoneLeds { cold: 3, optimal: 2, hot: 4 }
+        } else {
+            let last = if n == 0 { 0 } else { n - 1 };
+            ZoneLeds {
+                cold: 0,
+                optimal: if 1 < last { 1 } else { last },
+                hot: if 2 < last { 2 } else { last },
+            }
         }
-        self.cold = cold.unwrap().parse::<f32>().map_err(|_| Error::Gpio("Błąd parsowania"))?;
-        self.optimal = optimal.unwrap().parse::<f32>().map_err(|_| Error::Gpio("Błąd parsowania"))?;
-        self.hot = hot.unwrap().parse::<f32>().map_err(|_| Error::Gpio("Błąd parsowania"))?;
-        self.critical = critical.unwrap().parse::<f32>().map_err(|_| Error::Gpio("Błąd parsowania"))?;
-        Ok(())
     }
 }
 
-/// Kompoment opakowujący tablice ledów i dodający do niej funkcjonalność
+/// Kompoment opakowujący tablice ledów i dodający do niej funkcjonalność.
+/// Liczba diod `N` jest parametrem typu, więc ten sam kod obsługuje płytki z 3, 4 czy 8 LED.
 /// # Examples
 /// ```
 /// use leds::*
@@ -72,6 +65,8 @@ impl TemperatureBoundaries {
 /// leds.set(0, true).ok(); 
 /// // Wyłączenie LED 0
 /// leds.set(0, false).ok();
+/// // Indeks sprawdzany podczas kompilacji
+/// leds.set_const::<0>(true).ok();
 /// 
 /// // Wyłączenie wszystkich LED
 /// leds.set_all(false);
@@ -81,11 +76,14 @@ impl TemperatureBoundaries {
 /// ```
 /// leds.circle_animation(delay);
 /// ```
-pub struct LedArray<GPIO> {
-    pub leds: [GPIO; 8],
+pub struct LedArray<GPIO, const N: usize> {
+    pub leds: [GPIO; N],
+    zone_leds: ZoneLeds,
+    fault_led: usize,
+    heartbeat_led: Option<usize>,
 }
 
-impl <GPIO, E> LedArray<GPIO>
+impl <GPIO, E, const N: usize> LedArray<GPIO, N>
 where
     GPIO: OutputPin<Error = E>
 {
@@ -94,12 +92,78 @@ where
     /// // Tworzenie kompomentu LedArray
     /// let mut leds = = LedArray::new(leds_array);
     /// ```
-    pub fn new(leds: [GPIO; 8]) -> Self {
+    pub fn new(leds: [GPIO; N]) -> Self {
+        const { assert!(N > 0, "LedArray wymaga co najmniej jednej diody") };
         LedArray {
             leds,
+            zone_leds: ZoneLeds::for_len(N),
+            fault_led: 0,
+            heartbeat_led: None,
         }
     }
 
+    /// Zmiana diod przypisanych do stref, indeksy sprawdzane podczas kompilacji
+    /// # Examples
+    /// ```
+    /// // Niebieska LED 3, zielona LED 2, czerwona LED 4
+    /// let mut leds = LedArray::new(leds_array).zone_leds::<3, 2, 4>();
+    /// ```
+    pub fn zone_leds<const COLD: usize, const OPTIMAL: usize, const HOT: usize>(mut self) -> Self {
+        const { assert!(COLD < N && OPTIMAL < N && HOT < N, "indeks LED poza tablicą") };
+        self.zone_leds = ZoneLeds { cold: COLD, optimal: OPTIMAL, hot: HOT };
+        self
+    }
+
+    /// Zmiana diody używanej do migania kodem błędu (domyślnie LED 0)
+    /// # Examples
+    /// ```
+    /// let mut leds = LedArray::new(leds_array).fault_led::<4>();
+    /// ```
+    pub fn fault_led<const I: usize>(mut self) -> Self {
+        const { assert!(I < N, "indeks LED poza tablicą") };
+        self.fault_led = I;
+        self
+    }
+
+    /// Zarezerwowanie diody dla sygnału heartbeat (domyślnie brak).
+    /// Zarezerwowana dioda jest pomijana przez `set_all`, animacje i wskaźnik słupkowy,
+    /// a jeśli pokrywa się z diodą strefy lub diodą błędu, strefa lub kod błędu nie są na niej pokazywane.
+    /// # Examples
+    /// ```
+    /// let mut leds = LedArray::new(leds_array).heartbeat_led::<7>();
+    /// ```
+    pub fn heartbeat_led<const I: usize>(mut self) -> Self {
+        const { assert!(I < N, "indeks LED poza tablicą") };
+        self.heartbeat_led = Some(I);
+        self
+    }
+
+    // Diody dostępne dla stref i animacji (bez diody heartbeat)
+    fn outputs(&mut self) -> impl Iterator<Item = &mut GPIO> {
+        let reserved = self.heartbeat_led;
+        self.leds
+            .iter_mut()
+            .enumerate()
+            .filter(move |(i, _)| Some(*i) != reserved)
+            .map(|(_, led)| led)
+    }
+
+    // Włączenie diody strefy lub błędu, chyba że jest to dioda heartbeat
+    fn set_unreserved(&mut self, index: usize, state: bool) -> Result<(), E> {
+        if Some(index) == self.heartbeat_led {
+            return Ok(());
+        }
+        if state {
+            self.leds[index].set_high()
+        } else {
+            self.leds[index].set_low()
+        }
+    }
+
+    fn outputs_len(&self) -> usize {
+        if self.heartbeat_led.is_some() { N - 1 } else { N }
+    }
+
     /// Funkcja służąca do manipolacji pojedyńczą diodą LED
     /// # Przykład
     /// ```
@@ -108,17 +172,30 @@ where
     /// // Wyłączenie LED 0
     /// leds.set(0, false).ok();
     /// ```
-    pub fn set(&mut self, led_index: u8, state: bool) -> Result<(), Error<E>> {
-        if led_index > 7 {
-            return Err(Error::ArrayOutOfBounds);
+    pub fn set(&mut self, led_index: usize, state: bool) -> Result<(), Error<E>> {
+        let led = self.leds.get_mut(led_index).ok_or(Error::ArrayOutOfBounds)?;
+        if state {
+            led.set_high().map_err(Error::Gpio)?;
+        } else {
+            led.set_low().map_err(Error::Gpio)?;
         }
+        Ok(())
+    }
 
+    /// Wersja `set` z indeksem sprawdzanym podczas kompilacji
+    /// # Przykład
+    /// ```
+    /// leds.set_const::<2>(true).ok();
+    /// // Błąd kompilacji dla tablicy 4 diod
+    /// leds.set_const::<4>(true).ok();
+    /// ```
+    pub fn set_const<const I: usize>(&mut self, state: bool) -> Result<(), E> {
+        const { assert!(I < N, "indeks LED poza tablicą") };
         if state {
-            self.leds[led_index as usize].set_high().map_err(Error::Gpio)?;
+            self.leds[I].set_high()
         } else {
-            self.leds[led_index as usize].set_low().map_err(Error::Gpio)?;
+            self.leds[I].set_low()
         }
-        Ok(())
     }
 
     /// Funkcja służąca do manipulacji wszystkimi diodami LED
@@ -130,13 +207,11 @@ where
     /// leds.set_all(false).ok();
     /// ```
     pub fn set_all(&mut self, state: bool) -> Result<(), E> {
-        if state {
-            for i in 0..8 {
-                self.leds[i].set_high()?;
-            }
-        } else {
-            for i in 0..8 {
-                self.leds[i].set_low()?;
+        for led in self.outputs() {
+            if state {
+                led.set_high()?;
+            } else {
+                led.set_low()?;
             }
         }
         Ok(())
@@ -150,14 +225,15 @@ where
     /// ```
     pub fn circle_animation(&mut self, delay: &mut Delay) -> Result<(), E> 
     {
-        for i in 0..8 {
-            self.leds[i].set_high()?;
-            delay.delay_ms(25_u16);
+        // Całe kółko trwa tyle samo niezależnie od liczby diod
+        let step = (200 / self.outputs_len().clamp(1, 200)) as u16;
+        for led in self.outputs() {
+            led.set_high()?;
+            delay.delay_ms(step);
         }
-        //delay.delay_ms(50_u16);
-        for i in 0..8 {
-            self.leds[i].set_low()?;
-            delay.delay_ms(25_u16);
+        for led in self.outputs() {
+            led.set_low()?;
+            delay.delay_ms(step);
         }
         Ok(())
     }
@@ -165,18 +241,163 @@ where
     ///Funkcja służąca do usatwienia LEDów zgodnie z wartością temperatury
     pub fn set_from_tb(&mut self, delay: &mut Delay, tb: &TemperatureBoundaries, temperature: f32) -> Result<(), E> {
         self.set_all(false)?;
-        if temperature >= tb.critical { // Critical: Led spining
-            self.circle_animation(delay)?;
+        let zone_leds = self.zone_leds;
+        match tb.zone(temperature) {
+            Zone::Critical => self.circle_animation(delay)?, // Critical: Led spining
+            // Indeksy w zone_leds są zawsze mniejsze od N (for_len / zone_leds)
+            Zone::Hot => self.set_unreserved(zone_leds.hot, true)?, // High: Red led on
+            Zone::Optimal => self.set_unreserved(zone_leds.optimal, true)?, // Normal: Green led on
+            Zone::Cold => self.set_unreserved(zone_leds.cold, true)?, // Low: Blue led on
+            Zone::Normal => {}
+        }
+        Ok(())
+    }
+
+    /// Funkcja pokazująca temperaturę jako wskaźnik słupkowy: zapalone diody od 0
+    /// do `gauge(tb, temperature, N)` (bez diody heartbeat)
+    pub fn set_gauge(&mut self, tb: &TemperatureBoundaries, temperature: f32) -> Result<(), E> {
+        let lit = gauge(tb, temperature, self.outputs_len());
+        for (i, led) in self.outputs().enumerate() {
+            if i < lit {
+                led.set_high()?;
+            } else {
+                led.set_low()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Funkcja migająca kodem błędu: `blinks` krótkich mignięć diody błędu, pozostałe diody są wyłączone.
+    /// Wywoływana cyklicznie tworzy powtarzające się grupy, odstęp między grupami zapewnia wywołujący. <br/>
+    /// <b> Uwaga: </b> Funkcja ta blokuje wykonywanie pozostałego kodu w tle!
+    /// # Examples
+    /// ```
+    /// // Kod 3: trzy mignięcia
+    /// leds.blink_code(delay, 3).ok();
+    /// ```
+    pub fn blink_code(&mut self, delay: &mut Delay, blinks: u8) -> Result<(), E> {
+        self.set_all(false)?;
+        for _ in 0..blinks {
+            self.set_unreserved(self.fault_led, true)?;
+            delay.delay_ms(250_u16);
+            self.set_unreserved(self.fault_led, false)?;
+            delay.delay_ms(250_u16);
         }
-        else if temperature >= tb.hot { // High: Red led on
-            self.set(4, true).ok();
+        Ok(())
+    }
+
+    /// Funkcja wysyłająca sygnał heartbeat: `pulses` krótkich błysków diody heartbeat.
+    /// Wywoływana w każdej iteracji pętli głównej - brak błysków oznacza zawieszony program.
+    /// Bez zarezerwowanej diody (`heartbeat_led`) nic nie robi. <br/>
+    /// <b> Uwaga: </b> Funkcja ta blokuje wykonywanie pozostałego kodu w tle!
+    /// # Examples
+    /// ```
+    /// // Normalna praca: jeden błysk, stan błędu: więcej błysków
+    /// leds.heartbeat(delay, 1).ok();
+    /// ```
+    pub fn heartbeat(&mut self, delay: &mut Delay, pulses: u8) -> Result<(), E> {
+        if let Some(index) = self.heartbeat_led {
+            for _ in 0..pulses {
+                self.leds[index].set_high()?;
+                delay.delay_ms(60_u16);
+                self.leds[index].set_low()?;
+                delay.delay_ms(140_u16);
+            }
         }
-        else if temperature >= tb.optimal { // Normal: Green led on
-            self.set(2, true).ok();
+        Ok(())
+    }
+}
+
+/// Odpowiednik LedArray dla paska diod adresowalnych RGB (np. WS2812 na SPI).
+/// Kolory są buforowane i wysyłane funkcją `show`, strefy i wskaźnik słupkowy
+/// używają gradientu z `color`.
+/// # Examples
+/// ```
+/// use leds::*;
+/// use ws2812::Ws2812;
+///
+/// // Pasek 16 diod na SPI2 z jasnością 25%
+/// let mut strip = RgbStrip::<_, 16>::new(Ws2812::new(spi)).brightness(64);
+/// strip.set_gauge(&tb, temperature).ok();
+/// ```
+pub struct RgbStrip<DRIVER, const N: usize> {
+    driver: DRIVER,
+    pub colors: [Rgb; N],
+    brightness: u8,
+}
+
+impl<DRIVER, E, const N: usize> RgbStrip<DRIVER, N>
+where
+    DRIVER: RgbWrite<Error = E>
+{
+    pub fn new(driver: DRIVER) -> Self {
+        const { assert!(N > 0, "RgbStrip wymaga co najmniej jednej diody") };
+        RgbStrip {
+            driver,
+            colors: [Rgb::OFF; N],
+            brightness: 255,
         }
-        else if temperature < tb.cold { // Low: Blue led on
-            self.set(3, true).ok();
+    }
+
+    /// Ograniczenie jasności wszystkich diod (255 = pełna jasność)
+    pub fn brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Wysłanie bufora kolorów do paska
+    pub fn show(&mut self) -> Result<(), E> {
+        let mut scaled = self.colors;
+        for color in scaled.iter_mut() {
+            *color = color.scale(self.brightness);
+        }
+        self.driver.write(&scaled)
+    }
+
+    /// Funkcja ustawiająca kolor pojedynczej diody (zmiana widoczna po `show`)
+    pub fn set(&mut self, led_index: usize, color: Rgb) -> Result<(), Error<E>> {
+        *self.colors.get_mut(led_index).ok_or(Error::ArrayOutOfBounds)? = color;
+        Ok(())
+    }
+
+    /// Funkcja ustawiająca kolor wszystkich diod i wysyłająca go do paska
+    pub fn set_all(&mut self, color: Rgb) -> Result<(), E> {
+        self.colors = [color; N];
+        self.show()
+    }
+
+    /// Animacja kręcącego kółka w podanym kolorze <br/>
+    /// <b> Uwaga: </b> Funkcja ta blokuje wykonywanie pozostałego kodu w tle!
+    pub fn circle_animation(&mut self, delay: &mut Delay, color: Rgb) -> Result<(), E> {
+        let step = (200 / N.min(200)) as u16;
+        for state in [color, Rgb::OFF] {
+            for i in 0..N {
+                self.colors[i] = state;
+                self.show()?;
+                delay.delay_ms(step);
+            }
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Funkcja ustawiająca cały pasek na kolor temperatury, w strefie krytycznej czerwone kółko
+    pub fn set_from_tb(&mut self, delay: &mut Delay, tb: &TemperatureBoundaries, temperature: f32) -> Result<(), E> {
+        match tb.zone(temperature) {
+            Zone::Critical => {
+                self.set_all(Rgb::OFF)?;
+                self.circle_animation(delay, Rgb::RED)
+            }
+            _ => self.set_all(color(tb, temperature)),
+        }
+    }
+
+    /// Funkcja pokazująca temperaturę jako wskaźnik słupkowy w kolorze temperatury
+    pub fn set_gauge(&mut self, tb: &TemperatureBoundaries, temperature: f32) -> Result<(), E> {
+        let lit = gauge(tb, temperature, N);
+        let color = color(tb, temperature);
+        for (i, led) in self.colors.iter_mut().enumerate() {
+            *led = if i < lit { color } else { Rgb::OFF };
+        }
+        self.show()
+    }
+}
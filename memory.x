@@ -0,0 +1,8 @@
+/* STM32F303VC: 256 KB flash, 40 KB SRAM, 8 KB CCM SRAM */
+MEMORY
+{
+    /* Ostatnia strona flash (2 KB od 0x0803F800) zarezerwowana na konfigurację (config::PAGE_ADDRESS) */
+    FLASH (rx) : ORIGIN = 0x08000000, LENGTH = 254K
+    CCMRAM (rwx) : ORIGIN = 0x10000000, LENGTH = 8K
+    RAM (rwx) : ORIGIN = 0x20000000, LENGTH = 40K
+}
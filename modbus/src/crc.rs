@@ -0,0 +1,28 @@
+/// CRC-16/MODBUS (wielomian 0xA001 odwrócony, wartość początkowa 0xFFFF).
+/// W ramce zapisywane jest młodszym bajtem najpierw.
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xA001 } else { crc >> 1 };
+        }
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc16(b"123456789"), 0x4B37);
+    }
+
+    #[test]
+    fn known_frame() {
+        // Odczyt rejestrów 0x006B..0x006D ze slave 0x11 (przykład ze specyfikacji Modbus)
+        assert_eq!(crc16(&[0x11, 0x03, 0x00, 0x6B, 0x00, 0x03]).to_le_bytes(), [0x76, 0x87]);
+    }
+}
@@ -0,0 +1,317 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Slave Modbus RTU termometru.
+//!
+//! Crate nie zależy od sprzętu: ramki składa `RtuReceiver` według przerw 3,5 znaku,
+//! a `Slave` sprawdza adres i CRC, wykonuje funkcje 0x03, 0x04, 0x06 i 0x10
+//! na rejestrach udostępnionych przez implementację `Registers` i tworzy odpowiedź.
+
+mod crc;
+mod rtu;
+
+pub use crate::crc::crc16;
+pub use crate::rtu::*;
+
+/// Największa ramka RTU (adres, PDU, CRC)
+pub const MAX_ADU: usize = 256;
+/// Adres rozgłoszeniowy, zapisy są wykonywane bez odpowiedzi
+pub const BROADCAST: u8 = 0;
+/// Zakres adresów slave
+pub const MIN_ADDRESS: u8 = 1;
+pub const MAX_ADDRESS: u8 = 247;
+/// Wartość rejestru oznaczająca brak pomiaru
+pub const NO_VALUE: u16 = 0x8000;
+
+// Największa liczba rejestrów w jednym odczycie i zapisie (ograniczenie długości ramki)
+const MAX_READ: u16 = 125;
+const MAX_WRITE: u16 = 123;
+
+const READ_HOLDING: u8 = 0x03;
+const READ_INPUT: u8 = 0x04;
+const WRITE_SINGLE: u8 = 0x06;
+const WRITE_MULTIPLE: u8 = 0x10;
+
+/// Kody wyjątków Modbus
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exception {
+    IllegalFunction = 0x01,
+    IllegalDataAddress = 0x02,
+    IllegalDataValue = 0x03,
+    SlaveDeviceFailure = 0x04,
+}
+
+/// Rejestry udostępniane przez urządzenie, adresy liczone od 0
+pub trait Registers {
+    fn read_input(&mut self, address: u16) -> Result<u16, Exception>;
+    fn read_holding(&mut self, address: u16) -> Result<u16, Exception>;
+    /// Zapis ciągu rejestrów od `start` w całości albo wcale (np. granice sprawdzane razem)
+    fn write_holding(&mut self, start: u16, values: &[u16]) -> Result<(), Exception>;
+}
+
+/// Wartość w °C lub % zapisana w rejestrze jako liczba dziesiątych ze znakiem
+pub fn to_tenths(value: f32) -> u16 {
+    let tenths = value * 10.0;
+    // core nie ma f32::round
+    let rounded = if tenths < 0.0 { tenths - 0.5 } else { tenths + 0.5 };
+    rounded as i16 as u16
+}
+
+pub fn from_tenths(register: u16) -> f32 {
+    register as i16 as f32 / 10.0
+}
+
+/// Slave Modbus RTU o zadanym adresie
+/// # Examples
+/// ```
+/// use modbus::{crc16, Exception, Registers, Slave, MAX_ADU};
+///
+/// // Temperatura w rejestrze wejściowym 0
+/// struct Sensor(f32);
+///
+/// impl Registers for Sensor {
+///     fn read_input(&mut self, address: u16) -> Result<u16, Exception> {
+///         match address {
+///             0 => Ok(modbus::to_tenths(self.0)),
+///             _ => Err(Exception::IllegalDataAddress),
+///         }
+///     }
+///     fn read_holding(&mut self, _: u16) -> Result<u16, Exception> {
+///         Err(Exception::IllegalDataAddress)
+///     }
+///     fn write_holding(&mut self, _: u16, _: &[u16]) -> Result<(), Exception> {
+///         Err(Exception::IllegalDataAddress)
+///     }
+/// }
+///
+/// let slave = Slave::new(1);
+/// // Odczyt jednego rejestru wejściowego od adresu 0
+/// let mut adu = vec![1, 0x04, 0, 0, 0, 1];
+/// adu.extend_from_slice(&crc16(&adu).to_le_bytes());
+/// let mut response = [0u8; MAX_ADU];
+/// let reply = slave.handle(&adu, &mut Sensor(23.4), &mut response).unwrap();
+/// assert_eq!(reply[..5], [1, 0x04, 2, 0, 234]);
+/// ```
+pub struct Slave {
+    address: u8,
+}
+
+impl Slave {
+    pub fn new(address: u8) -> Self {
+        Slave { address }
+    }
+
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Funkcja obsługująca ramkę i tworząca odpowiedź w `response`.
+    /// Zwraca None dla ramek z błędnym CRC, do innego slave i rozgłoszeniowych - wtedy slave milczy.
+    pub fn handle<'a, R: Registers>(&self, request: &[u8], registers: &mut R, response: &'a mut [u8; MAX_ADU]) -> Option<&'a [u8]> {
+        if request.len() < 4 {
+            return None;
+        }
+        let (frame, crc) = request.split_at(request.len() - 2);
+        if crc16(frame).to_le_bytes() != [crc[0], crc[1]] {
+            return None;
+        }
+        let address = frame[0];
+        if address != self.address && address != BROADCAST {
+            return None;
+        }
+        let function = frame[1];
+        let data = &frame[2..];
+
+        response[0] = self.address;
+        response[1] = function;
+        let result = match function {
+            READ_HOLDING | READ_INPUT if address != BROADCAST => read(function, data, registers, &mut response[2..]),
+            WRITE_SINGLE => write_single(data, registers, &mut response[2..]),
+            WRITE_MULTIPLE => write_multiple(data, registers, &mut response[2..]),
+            _ => Err(Exception::IllegalFunction),
+        };
+        if address == BROADCAST {
+            return None;
+        }
+        let len = match result {
+            Ok(len) => 2 + len,
+            Err(exception) => {
+                response[1] = function | 0x80;
+                response[2] = exception as u8;
+                3
+            }
+        };
+        let crc = crc16(&response[..len]).to_le_bytes();
+        response[len..len + 2].copy_from_slice(&crc);
+        Some(&response[..len + 2])
+    }
+}
+
+fn word(data: &[u8], index: usize) -> u16 {
+    u16::from_be_bytes([data[index], data[index + 1]])
+}
+
+// Sprawdzenie, czy zakres rejestrów mieści się w przestrzeni adresów
+fn range(start: u16, count: u16) -> Result<(), Exception> {
+    match start.checked_add(count - 1) {
+        Some(_) => Ok(()),
+        None => Err(Exception::IllegalDataAddress),
+    }
+}
+
+fn read<R: Registers>(function: u8, data: &[u8], registers: &mut R, out: &mut [u8]) -> Result<usize, Exception> {
+    if data.len() != 4 {
+        return Err(Exception::IllegalDataValue);
+    }
+    let (start, count) = (word(data, 0), word(data, 2));
+    if count == 0 || count > MAX_READ {
+        return Err(Exception::IllegalDataValue);
+    }
+    range(start, count)?;
+    out[0] = (count * 2) as u8;
+    for i in 0..count {
+        let value = match function {
+            READ_INPUT => registers.read_input(start + i)?,
+            _ => registers.read_holding(start + i)?,
+        };
+        let at = 1 + 2 * i as usize;
+        out[at..at + 2].copy_from_slice(&value.to_be_bytes());
+    }
+    Ok(1 + 2 * count as usize)
+}
+
+fn write_single<R: Registers>(data: &[u8], registers: &mut R, out: &mut [u8]) -> Result<usize, Exception> {
+    if data.len() != 4 {
+        return Err(Exception::IllegalDataValue);
+    }
+    registers.write_holding(word(data, 0), &[word(data, 2)])?;
+    // Odpowiedź powtarza żądanie
+    out[..4].copy_from_slice(data);
+    Ok(4)
+}
+
+fn write_multiple<R: Registers>(data: &[u8], registers: &mut R, out: &mut [u8]) -> Result<usize, Exception> {
+    if data.len() < 5 {
+        return Err(Exception::IllegalDataValue);
+    }
+    let (start, count, bytes) = (word(data, 0), word(data, 2), data[4] as usize);
+    if count == 0 || count > MAX_WRITE || bytes != 2 * count as usize || data.len() != 5 + bytes {
+        return Err(Exception::IllegalDataValue);
+    }
+    range(start, count)?;
+    let mut values = [0u16; MAX_WRITE as usize];
+    for (i, value) in values.iter_mut().take(count as usize).enumerate() {
+        *value = word(data, 5 + 2 * i);
+    }
+    registers.write_holding(start, &values[..count as usize])?;
+    out[..4].copy_from_slice(&data[..4]);
+    Ok(4)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    // Trzy rejestry wejściowe i cztery rejestry zapisu, rejestr 3 przyjmuje tylko wartości < 100
+    struct Mock {
+        input: [u16; 3],
+        holding: [u16; 4],
+    }
+
+    impl Registers for Mock {
+        fn read_input(&mut self, address: u16) -> Result<u16, Exception> {
+            self.input.get(address as usize).copied().ok_or(Exception::IllegalDataAddress)
+        }
+
+        fn read_holding(&mut self, address: u16) -> Result<u16, Exception> {
+            self.holding.get(address as usize).copied().ok_or(Exception::IllegalDataAddress)
+        }
+
+        fn write_holding(&mut self, start: u16, values: &[u16]) -> Result<(), Exception> {
+            let start = start as usize;
+            let target = self.holding.get_mut(start..start + values.len()).ok_or(Exception::IllegalDataAddress)?;
+            if (start..).zip(values).any(|(address, &value)| address == 3 && value >= 100) {
+                return Err(Exception::IllegalDataValue);
+            }
+            target.copy_from_slice(values);
+            Ok(())
+        }
+    }
+
+    fn mock() -> Mock {
+        Mock { input: [235, 450, 0], holding: [0, 250, 300, 35] }
+    }
+
+    fn handle(request: &[u8], registers: &mut Mock) -> Option<std::vec::Vec<u8>> {
+        let mut response = [0u8; MAX_ADU];
+        Slave::new(1).handle(request, registers, &mut response).map(|r| r.to_vec())
+    }
+
+    #[test]
+    fn reads_input_registers() {
+        let response = handle(&[0x01, 0x04, 0x00, 0x00, 0x00, 0x03, 0xB0, 0x0B], &mut mock());
+        assert_eq!(response.unwrap(), [0x01, 0x04, 0x06, 0x00, 0xEB, 0x01, 0xC2, 0x00, 0x00, 0xE4, 0x84]);
+    }
+
+    #[test]
+    fn reads_holding_registers() {
+        let response = handle(&[0x01, 0x03, 0x00, 0x01, 0x00, 0x02, 0x95, 0xCB], &mut mock());
+        assert_eq!(response.unwrap(), [0x01, 0x03, 0x04, 0x00, 0xFA, 0x01, 0x2C, 0xDA, 0x4F]);
+    }
+
+    #[test]
+    fn writes_single_register() {
+        let mut registers = mock();
+        let request = [0x01, 0x06, 0x00, 0x03, 0x00, 0x32, 0xF8, 0x1F];
+        assert_eq!(handle(&request, &mut registers).unwrap(), request);
+        assert_eq!(registers.holding[3], 50);
+    }
+
+    #[test]
+    fn writes_multiple_registers_atomically() {
+        let mut registers = mock();
+        let request = [0x01, 0x10, 0x00, 0x00, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x00, 0xC8, 0xD2, 0x3B];
+        assert_eq!(handle(&request, &mut registers).unwrap(), [0x01, 0x10, 0x00, 0x00, 0x00, 0x02, 0x41, 0xC8]);
+        assert_eq!(registers.holding, [10, 200, 300, 35]);
+
+        // Rejestr 3 odrzuca 500, rejestr 2 również nie może się zmienić
+        let request = [0x01, 0x10, 0x00, 0x02, 0x00, 0x02, 0x04, 0x00, 0x01, 0x01, 0xF4, 0x23, 0xA1];
+        assert_eq!(handle(&request, &mut registers).unwrap(), [0x01, 0x90, 0x03, 0x0C, 0x01]);
+        assert_eq!(registers.holding, [10, 200, 300, 35]);
+    }
+
+    #[test]
+    fn answers_with_exceptions() {
+        // Nieznana funkcja 0x05
+        assert_eq!(handle(&[0x01, 0x05, 0x00, 0x00, 0xFF, 0x00, 0x8C, 0x3A], &mut mock()).unwrap(), [0x01, 0x85, 0x01, 0x83, 0x50]);
+        // Rejestry 2..4 wykraczają poza mapę
+        assert_eq!(handle(&[0x01, 0x04, 0x00, 0x02, 0x00, 0x02, 0xD0, 0x0B], &mut mock()).unwrap(), [0x01, 0x84, 0x02, 0xC2, 0xC1]);
+        // Liczba rejestrów 0
+        assert_eq!(handle(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x00, 0x45, 0xCA], &mut mock()).unwrap(), [0x01, 0x83, 0x03, 0x01, 0x31]);
+    }
+
+    #[test]
+    fn ignores_other_slaves_and_bad_crc() {
+        assert!(handle(&[0x02, 0x04, 0x00, 0x00, 0x00, 0x03, 0xB0, 0x38], &mut mock()).is_none());
+        assert!(handle(&[0x01, 0x04, 0x00, 0x00, 0x00, 0x03, 0xB0, 0x0C], &mut mock()).is_none());
+        assert!(handle(&[0x01, 0x04], &mut mock()).is_none());
+    }
+
+    #[test]
+    fn executes_broadcast_writes_silently() {
+        let mut registers = mock();
+        assert!(handle(&[0x00, 0x06, 0x00, 0x00, 0x00, 0x07, 0xC9, 0xD9], &mut registers).is_none());
+        assert_eq!(registers.holding[0], 7);
+        assert!(handle(&[0x00, 0x04, 0x00, 0x00, 0x00, 0x01, 0x30, 0x1B], &mut registers).is_none());
+    }
+
+    #[test]
+    fn scales_tenths() {
+        assert_eq!(to_tenths(23.4), 234);
+        assert_eq!(to_tenths(-5.25), (-53i16) as u16);
+        assert_eq!(from_tenths(to_tenths(-5.2)), -5.2);
+        assert_eq!(from_tenths(0xFF38), -20.0);
+    }
+}
@@ -0,0 +1,177 @@
+use heapless::spsc::Queue;
+use heapless::Vec;
+
+use crate::MAX_ADU;
+
+// Liczba kompletnych ramek oczekujących na obsługę (pojemność kolejki to N - 1)
+const PENDING_FRAMES: usize = 3;
+
+/// Ramka RTU: adres, PDU i CRC
+pub type Adu = Vec<u8, MAX_ADU>;
+
+/// Czas ciszy 3,5 znaku w µs rozdzielający ramki. Znak RTU ma 11 bitów;
+/// powyżej 19200 Bd specyfikacja zaleca stałe 1750 µs.
+pub const fn silence_us(baud: u32) -> u32 {
+    if baud > 19_200 {
+        1_750
+    } else {
+        // 3,5 znaku * 11 bitów, zaokrąglone w górę
+        38_500_000_u32.div_ceil(baud)
+    }
+}
+
+/// Komponent dzielący odebrane bajty na ramki RTU według przerw między znakami.
+/// Czas podawany jest w dowolnych jednostkach (np. cyklach procesora), w tych samych co `silence`.
+/// Bajty dodawane są w przerwaniu, ramki obsługiwane w pętli głównej.
+/// # Examples
+/// ```
+/// use modbus::RtuReceiver;
+///
+/// // Czas w µs, na urządzeniu np. w cyklach DWT (1750 µs przy 48 MHz to 1_750 * 48)
+/// let mut rtu = RtuReceiver::new(1_750);
+/// // W przerwaniu
+/// for (i, byte) in [1u8, 0x04, 0, 0, 0, 1, 0x31, 0xCA].into_iter().enumerate() {
+///     rtu.push(byte, i as u32 * 600);
+/// }
+/// // W pętli głównej
+/// rtu.poll(10_000);
+/// assert_eq!(rtu.next_frame().unwrap().len(), 8);
+/// assert!(rtu.next_frame().is_none());
+/// ```
+pub struct RtuReceiver {
+    silence: u32,
+    current: Adu,
+    last: u32,
+    too_long: bool,
+    pending: Queue<Adu, PENDING_FRAMES>,
+    dropped: u32,
+}
+
+impl RtuReceiver {
+    pub const fn new(silence: u32) -> Self {
+        RtuReceiver {
+            silence,
+            current: Vec::new(),
+            last: 0,
+            too_long: false,
+            pending: Queue::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Zmiana czasu ciszy, np. po zmianie prędkości portu
+    pub fn set_silence(&mut self, silence: u32) {
+        self.silence = silence;
+    }
+
+    /// Funkcja dodająca odebrany bajt. Przerwa dłuższa niż 3,5 znaku kończy poprzednią ramkę.
+    pub fn push(&mut self, byte: u8, now: u32) {
+        self.poll(now);
+        if !self.too_long && self.current.push(byte).is_err() {
+            self.too_long = true;
+        }
+        self.last = now;
+    }
+
+    /// Funkcja kończąca ramkę, gdy od ostatniego bajtu minęło 3,5 znaku
+    pub fn poll(&mut self, now: u32) {
+        let started = !self.current.is_empty() || self.too_long;
+        if started && now.wrapping_sub(self.last) >= self.silence {
+            let frame = core::mem::take(&mut self.current);
+            if self.too_long {
+                self.too_long = false;
+                self.dropped = self.dropped.wrapping_add(1);
+            } else if self.pending.enqueue(frame).is_err() {
+                self.dropped = self.dropped.wrapping_add(1);
+            }
+        }
+    }
+
+    /// Funkcja zwracająca kolejną kompletną ramkę
+    pub fn next_frame(&mut self) -> Option<Adu> {
+        self.pending.dequeue()
+    }
+
+    /// Funkcja odrzucająca niedokończoną ramkę, np. przy zmianie protokołu
+    pub fn reset(&mut self) {
+        self.current.clear();
+        self.too_long = false;
+    }
+
+    /// Liczba ramek odrzuconych z powodu długości lub pełnej kolejki
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn silence_follows_the_baud_rate() {
+        assert_eq!(silence_us(9_600), 4_011);
+        assert_eq!(silence_us(19_200), 2_006);
+        assert_eq!(silence_us(115_200), 1_750);
+    }
+
+    #[test]
+    fn splits_frames_on_silence() {
+        let mut rtu = RtuReceiver::new(100);
+        for (i, byte) in [1u8, 2, 3].into_iter().enumerate() {
+            rtu.push(byte, 10 * i as u32);
+        }
+        rtu.poll(119);
+        assert!(rtu.next_frame().is_none());
+        rtu.poll(120);
+        assert_eq!(rtu.next_frame().unwrap(), [1, 2, 3]);
+        assert!(rtu.next_frame().is_none());
+    }
+
+    #[test]
+    fn byte_after_silence_starts_a_new_frame_without_poll() {
+        let mut rtu = RtuReceiver::new(100);
+        rtu.push(1, 0);
+        rtu.push(2, 50);
+        rtu.push(3, 200);
+        assert_eq!(rtu.next_frame().unwrap(), [1, 2]);
+        rtu.poll(300);
+        assert_eq!(rtu.next_frame().unwrap(), [3]);
+    }
+
+    #[test]
+    fn handles_timer_wrap_around() {
+        let mut rtu = RtuReceiver::new(100);
+        rtu.push(1, u32::MAX - 10);
+        rtu.push(2, 5);
+        rtu.poll(50);
+        assert!(rtu.next_frame().is_none());
+        rtu.poll(105);
+        assert_eq!(rtu.next_frame().unwrap(), [1, 2]);
+    }
+
+    #[test]
+    fn drops_oversized_frames() {
+        let mut rtu = RtuReceiver::new(100);
+        for i in 0..MAX_ADU as u32 + 10 {
+            rtu.push(0xAA, i);
+        }
+        rtu.poll(MAX_ADU as u32 + 200);
+        assert!(rtu.next_frame().is_none());
+        assert_eq!(rtu.dropped(), 1);
+    }
+
+    #[test]
+    fn reset_discards_partial_frame() {
+        let mut rtu = RtuReceiver::new(100);
+        rtu.push(1, 0);
+        rtu.push(2, 10);
+        rtu.reset();
+        rtu.poll(500);
+        assert!(rtu.next_frame().is_none());
+        rtu.push(3, 600);
+        rtu.poll(700);
+        assert_eq!(rtu.next_frame().unwrap(), [3]);
+        assert_eq!(rtu.dropped(), 0);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::FrameError;
+
+/// Funkcja kodująca COBS (bez bajtu kończącego), zwraca liczbę bajtów zapisanych do `out`
+pub fn cobs_encode(data: &[u8], out: &mut [u8]) -> Result<usize, FrameError> {
+    let mut code_index = 0;
+    let mut write = 1;
+    let mut code: u8 = 1;
+    for byte in data {
+        if *byte == 0 {
+            *out.get_mut(code_index).ok_or(FrameError::BufferTooSmall)? = code;
+            code_index = write;
+            write += 1;
+            code = 1;
+        } else {
+            *out.get_mut(write).ok_or(FrameError::BufferTooSmall)? = *byte;
+            write += 1;
+            code += 1;
+            // blok 254 bajtów bez zera kończy się kodem 0xFF
+            if code == 0xFF {
+                *out.get_mut(code_index).ok_or(FrameError::BufferTooSmall)? = code;
+                code_index = write;
+                write += 1;
+                code = 1;
+            }
+        }
+    }
+    *out.get_mut(code_index).ok_or(FrameError::BufferTooSmall)? = code;
+    Ok(write)
+}
+
+/// Funkcja dekodująca COBS w miejscu (dane bez bajtu kończącego), zwraca długość zdekodowanych danych
+pub fn cobs_decode(data: &mut [u8]) -> Result<usize, FrameError> {
+    let mut read = 0;
+    let mut write = 0;
+    while read < data.len() {
+        let code = data[read] as usize;
+        if code == 0 || read + code > data.len() {
+            return Err(FrameError::Cobs);
+        }
+        read += 1;
+        for _ in 1..code {
+            if data[read] == 0 {
+                return Err(FrameError::Cobs);
+            }
+            data[write] = data[read];
+            write += 1;
+            read += 1;
+        }
+        // kod 0xFF oznacza blok bez zera, ostatni blok nie kończy się zerem
+        if code != 0xFF && read < data.len() {
+            data[write] = 0;
+            write += 1;
+        }
+    }
+    Ok(write)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(data: &[u8]) -> ([u8; 300], usize) {
+        let mut out = [0u8; 300];
+        let len = cobs_encode(data, &mut out).unwrap();
+        (out, len)
+    }
+
+    #[test]
+    fn encodes_reference_vectors() {
+        let (out, len) = encode(&[0x00]);
+        assert_eq!(&out[..len], &[0x01, 0x01]);
+        let (out, len) = encode(&[0x00, 0x00]);
+        assert_eq!(&out[..len], &[0x01, 0x01, 0x01]);
+        let (out, len) = encode(&[0x11, 0x22, 0x00, 0x33]);
+        assert_eq!(&out[..len], &[0x03, 0x11, 0x22, 0x02, 0x33]);
+        let (out, len) = encode(&[0x11, 0x00, 0x00, 0x00]);
+        assert_eq!(&out[..len], &[0x02, 0x11, 0x01, 0x01, 0x01]);
+    }
+
+    #[test]
+    fn round_trips_long_block_without_zeros() {
+        let mut data = [0u8; 260];
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i % 255) as u8 + 1;
+        }
+        let (mut out, len) = encode(&data);
+        assert_eq!(out[0], 0xFF);
+        assert!(!out[..len].contains(&0));
+        let decoded = cobs_decode(&mut out[..len]).unwrap();
+        assert_eq!(&out[..decoded], &data[..]);
+    }
+
+    #[test]
+    fn round_trips_data_with_zeros() {
+        let data = [0x00, 0x05, 0x00, 0x00, 0xFF, 0x01, 0x00];
+        let (mut out, len) = encode(&data);
+        let decoded = cobs_decode(&mut out[..len]).unwrap();
+        assert_eq!(&out[..decoded], &data[..]);
+    }
+
+    #[test]
+    fn rejects_invalid_encoding() {
+        assert_eq!(cobs_decode(&mut [0x05, 0x11]), Err(FrameError::Cobs));
+        assert_eq!(cobs_decode(&mut [0x02, 0x00]), Err(FrameError::Cobs));
+        assert_eq!(cobs_decode(&mut [0x00]), Err(FrameError::Cobs));
+    }
+
+    #[test]
+    fn reports_small_output_buffer() {
+        let mut out = [0u8; 2];
+        assert_eq!(cobs_encode(&[1, 2, 3], &mut out), Err(FrameError::BufferTooSmall));
+    }
+}
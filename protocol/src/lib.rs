@@ -0,0 +1,409 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Binarny protokół termometru dla programów po stronie PC.
+//!
+//! Ramka przed kodowaniem: `[typ wiadomości][dane postcard][CRC-16 LE]`, następnie
+//! kodowana COBS i zakończona bajtem 0x00. CRC-16/CCITT-FALSE liczone jest z typu i danych.
+//! Crate nie zależy od sprzętu i jest wspólny dla oprogramowania termometru i narzędzi PC.
+
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+mod cobs;
+mod crc;
+mod reset;
+
+pub use crate::cobs::{cobs_decode, cobs_encode};
+pub use crate::crc::crc16;
+pub use crate::reset::ResetCause;
+
+/// Bajt kończący ramkę
+pub const DELIMITER: u8 = 0x00;
+/// Największy rozmiar typu, danych i CRC przed kodowaniem COBS
+pub const MAX_PAYLOAD: usize = 64;
+/// Największy rozmiar zakodowanej ramki razem z bajtem 0x00
+pub const MAX_FRAME: usize = MAX_PAYLOAD + MAX_PAYLOAD / 254 + 2;
+
+/// Pomiar z czujnika
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Reading {
+    /// Temperatura w °C
+    pub temperature: f32,
+    /// Wilgotność w %
+    pub humidity: f32,
+}
+
+/// Rekord telemetrii wysyłany bez zapytania według zasady raportowania (`set report`)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// Numer kolejny rekordu od uruchomienia, przerwa w numeracji oznacza zgubiony rekord
+    pub seq: u32,
+    /// Czas od uruchomienia w ms
+    pub uptime_ms: u32,
+    /// None gdy nie ma wartości do wysłania (patrz `quality`)
+    pub reading: Option<Reading>,
+    /// Jakość pomiaru (`telemetry::Quality::id`): 0 ok, 1 timeout, 2 checksum, 3 stale, 4 filtered
+    pub quality: u8,
+}
+
+/// Granice temperatur w °C
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Bounds {
+    pub cold: f32,
+    pub optimal: f32,
+    pub hot: f32,
+    pub critical: f32,
+}
+
+/// Stan urządzenia
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Status {
+    /// Maska aktywnych błędów, bit n oznacza kod błędu n (zob. crate `fault`)
+    pub faults: u8,
+    /// Czas od uruchomienia w ms
+    pub uptime_ms: u32,
+}
+
+/// Informacje o oprogramowaniu i ostatnim restarcie
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Info {
+    /// Wersja oprogramowania: major, minor, patch
+    pub version: [u8; 3],
+    /// Pierwsze 8 cyfr szesnastkowych skrótu commita git, 0 gdy nieznany
+    pub git_hash: u32,
+    /// Program zbudowany z niezatwierdzonymi zmianami
+    pub dirty: bool,
+    /// Profil budowania: false debug, true release
+    pub release: bool,
+    /// Czas od uruchomienia w ms
+    pub uptime_ms: u32,
+    /// Przyczyna ostatniego restartu (`ResetCause::id`)
+    pub reset_cause: u8,
+}
+
+/// Potwierdzenie wykonania żądania
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ack {
+    /// Typ potwierdzanej wiadomości
+    pub request: u8,
+    /// 0 gdy żądanie wykonano, w przeciwnym razie kod błędu z protokołu tekstowego
+    pub error: u8,
+}
+
+/// Identyfikatory typów wiadomości (pierwszy bajt ramki)
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageType {
+    GetReading = 0x01,
+    GetBounds = 0x02,
+    SetBounds = 0x03,
+    GetStatus = 0x04,
+    TextMode = 0x05,
+    GetInfo = 0x06,
+    Reading = 0x81,
+    Bounds = 0x82,
+    Status = 0x84,
+    Info = 0x86,
+    Record = 0x87,
+    Ack = 0xFF,
+}
+
+impl MessageType {
+    pub fn from_u8(id: u8) -> Option<Self> {
+        Some(match id {
+            0x01 => MessageType::GetReading,
+            0x02 => MessageType::GetBounds,
+            0x03 => MessageType::SetBounds,
+            0x04 => MessageType::GetStatus,
+            0x05 => MessageType::TextMode,
+            0x06 => MessageType::GetInfo,
+            0x81 => MessageType::Reading,
+            0x82 => MessageType::Bounds,
+            0x84 => MessageType::Status,
+            0x86 => MessageType::Info,
+            0x87 => MessageType::Record,
+            0xFF => MessageType::Ack,
+            _ => return None,
+        })
+    }
+}
+
+/// Wiadomość protokołu binarnego. Żądania wysyła PC, odpowiedzi (typy 0x80 i wyżej) termometr.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Message {
+    /// Żądanie ostatniego pomiaru
+    GetReading,
+    /// Żądanie granic temperatur
+    GetBounds,
+    /// Zmiana granic temperatur, odpowiedź: Ack
+    SetBounds(Bounds),
+    /// Żądanie stanu urządzenia
+    GetStatus,
+    /// Powrót do protokołu tekstowego, odpowiedź: Ack
+    TextMode,
+    /// Żądanie informacji o oprogramowaniu
+    GetInfo,
+    Reading(Reading),
+    Bounds(Bounds),
+    Status(Status),
+    Info(Info),
+    /// Rekord telemetrii wysyłany bez zapytania, odpowiedzią na `GetReading` pozostaje `Reading`
+    Record(Record),
+    Ack(Ack),
+}
+
+impl Message {
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            Message::GetReading => MessageType::GetReading,
+            Message::GetBounds => MessageType::GetBounds,
+            Message::SetBounds(_) => MessageType::SetBounds,
+            Message::GetStatus => MessageType::GetStatus,
+            Message::TextMode => MessageType::TextMode,
+            Message::GetInfo => MessageType::GetInfo,
+            Message::Reading(_) => MessageType::Reading,
+            Message::Bounds(_) => MessageType::Bounds,
+            Message::Status(_) => MessageType::Status,
+            Message::Info(_) => MessageType::Info,
+            Message::Record(_) => MessageType::Record,
+            Message::Ack(_) => MessageType::Ack,
+        }
+    }
+}
+
+/// Błędy kodowania i dekodowania ramek
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameError {
+    /// Bufor wyjściowy jest za mały
+    BufferTooSmall,
+    /// Niepoprawne kodowanie COBS
+    Cobs,
+    /// Ramka krótsza niż typ i CRC
+    TooShort,
+    /// Niezgodna suma kontrolna
+    Crc,
+    /// Nieznany typ wiadomości
+    UnknownType(u8),
+    /// Błąd serializacji postcard danych wiadomości
+    Payload,
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::BufferTooSmall => f.write_str("buffer too small"),
+            FrameError::Cobs => f.write_str("invalid COBS encoding"),
+            FrameError::TooShort => f.write_str("frame too short"),
+            FrameError::Crc => f.write_str("CRC mismatch"),
+            FrameError::UnknownType(id) => write!(f, "unknown message type 0x{:02x}", id),
+            FrameError::Payload => f.write_str("invalid payload"),
+        }
+    }
+}
+
+fn serialize<T: Serialize>(value: &T, buf: &mut [u8]) -> Result<usize, FrameError> {
+    postcard::to_slice(value, buf)
+        .map(|used| used.len())
+        .map_err(|_| FrameError::BufferTooSmall)
+}
+
+fn deserialize<'a, T: Deserialize<'a>>(data: &'a [u8]) -> Result<T, FrameError> {
+    // dane muszą zostać zużyte w całości
+    match postcard::take_from_bytes(data) {
+        Ok((value, [])) => Ok(value),
+        _ => Err(FrameError::Payload),
+    }
+}
+
+/// Funkcja kodująca wiadomość do ramki zakończonej bajtem 0x00
+/// # Examples
+/// ```
+/// use protocol::{FrameError, Message};
+/// # fn main() -> Result<(), FrameError> {
+///
+/// let mut buf = [0u8; protocol::MAX_FRAME];
+/// let frame = protocol::encode_frame(&Message::GetStatus, &mut buf)?;
+/// assert_eq!(frame.last(), Some(&protocol::DELIMITER));
+/// # Ok(())
+/// # }
+/// ```
+pub fn encode_frame<'a>(message: &Message, buf: &'a mut [u8]) -> Result<&'a [u8], FrameError> {
+    let mut raw = [0u8; MAX_PAYLOAD];
+    raw[0] = message.message_type() as u8;
+    let body = &mut raw[1..MAX_PAYLOAD - 2];
+    let len = 1 + match message {
+        Message::GetReading
+        | Message::GetBounds
+        | Message::GetStatus
+        | Message::TextMode
+        | Message::GetInfo => 0,
+        Message::SetBounds(bounds) | Message::Bounds(bounds) => serialize(bounds, body)?,
+        Message::Reading(reading) => serialize(reading, body)?,
+        Message::Status(status) => serialize(status, body)?,
+        Message::Info(info) => serialize(info, body)?,
+        Message::Record(record) => serialize(record, body)?,
+        Message::Ack(ack) => serialize(ack, body)?,
+    };
+    let crc = crc16(&raw[..len]);
+    raw[len..len + 2].copy_from_slice(&crc.to_le_bytes());
+    let encoded = cobs_encode(&raw[..len + 2], buf)?;
+    *buf.get_mut(encoded).ok_or(FrameError::BufferTooSmall)? = DELIMITER;
+    Ok(&buf[..encoded + 1])
+}
+
+/// Funkcja dekodująca ramkę (bez bajtu 0x00), dekodowanie COBS odbywa się w miejscu
+/// # Examples
+/// ```
+/// use protocol::{Message, Reading};
+///
+/// let reading = Reading { temperature: 23.5, humidity: 40.0 };
+/// let mut buf = [0u8; protocol::MAX_FRAME];
+/// let len = protocol::encode_frame(&Message::Reading(reading), &mut buf).unwrap().len();
+/// // Ramka bez kończącego bajtu 0x00
+/// let frame = &mut buf[..len - 1];
+/// match protocol::decode_frame(frame) {
+///     Ok(Message::Reading(reading)) => println!("{} °C", reading.temperature),
+///     Ok(_) => {}
+///     Err(e) => eprintln!("bad frame: {}", e),
+/// }
+/// ```
+pub fn decode_frame(frame: &mut [u8]) -> Result<Message, FrameError> {
+    let len = cobs_decode(frame)?;
+    if len < 3 {
+        return Err(FrameError::TooShort);
+    }
+    let (data, crc) = frame[..len].split_at(len - 2);
+    if crc16(data) != u16::from_le_bytes([crc[0], crc[1]]) {
+        return Err(FrameError::Crc);
+    }
+    let id = data[0];
+    let body = &data[1..];
+    let message_type = MessageType::from_u8(id).ok_or(FrameError::UnknownType(id))?;
+    let empty = |message: Message| {
+        if body.is_empty() {
+            Ok(message)
+        } else {
+            Err(FrameError::Payload)
+        }
+    };
+    match message_type {
+        MessageType::GetReading => empty(Message::GetReading),
+        MessageType::GetBounds => empty(Message::GetBounds),
+        MessageType::GetStatus => empty(Message::GetStatus),
+        MessageType::TextMode => empty(Message::TextMode),
+        MessageType::GetInfo => empty(Message::GetInfo),
+        MessageType::SetBounds => deserialize(body).map(Message::SetBounds),
+        MessageType::Reading => deserialize(body).map(Message::Reading),
+        MessageType::Bounds => deserialize(body).map(Message::Bounds),
+        MessageType::Status => deserialize(body).map(Message::Status),
+        MessageType::Info => deserialize(body).map(Message::Info),
+        MessageType::Record => deserialize(body).map(Message::Record),
+        MessageType::Ack => deserialize(body).map(Message::Ack),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(message: Message) {
+        let mut buf = [0u8; MAX_FRAME];
+        let frame = encode_frame(&message, &mut buf).unwrap();
+        assert_eq!(frame.last(), Some(&DELIMITER));
+        assert!(!frame[..frame.len() - 1].contains(&DELIMITER));
+        let mut frame_copy = [0u8; MAX_FRAME];
+        let len = frame.len() - 1;
+        frame_copy[..len].copy_from_slice(&frame[..len]);
+        assert_eq!(decode_frame(&mut frame_copy[..len]), Ok(message));
+    }
+
+    #[test]
+    fn round_trips_every_message() {
+        let bounds = Bounds { cold: 0.0, optimal: 25.0, hot: 30.0, critical: 35.0 };
+        round_trip(Message::GetReading);
+        round_trip(Message::GetBounds);
+        round_trip(Message::SetBounds(bounds));
+        round_trip(Message::GetStatus);
+        round_trip(Message::TextMode);
+        round_trip(Message::GetInfo);
+        round_trip(Message::Reading(Reading { temperature: 23.4, humidity: 45.0 }));
+        round_trip(Message::Bounds(bounds));
+        round_trip(Message::Status(Status { faults: 0b100, uptime_ms: 123_456 }));
+        round_trip(Message::Info(Info {
+            version: [0, 1, 0],
+            git_hash: 0x1a2b_3c4d,
+            dirty: false,
+            release: true,
+            uptime_ms: 86_400_000,
+            reset_cause: ResetCause::Watchdog.id(),
+        }));
+        let reading = Some(Reading { temperature: 23.4, humidity: 45.0 });
+        round_trip(Message::Record(Record { seq: 7, uptime_ms: 12_000, reading, quality: 0 }));
+        round_trip(Message::Record(Record { seq: u32::MAX, uptime_ms: 13_000, reading: None, quality: 1 }));
+        round_trip(Message::Ack(Ack { request: MessageType::SetBounds as u8, error: 6 }));
+    }
+
+    #[test]
+    fn encodes_request_without_payload() {
+        let mut buf = [0u8; MAX_FRAME];
+        // typ 0x04, CRC-16 z [0x04] zapisane little endian, COBS, 0x00
+        let crc = crc16(&[0x04]).to_le_bytes();
+        let frame = encode_frame(&Message::GetStatus, &mut buf).unwrap();
+        assert_eq!(frame, &[0x04, 0x04, crc[0], crc[1], 0x00]);
+    }
+
+    #[test]
+    fn rejects_corrupted_frames() {
+        // GetBounds z błędną sumą kontrolną
+        let mut raw = [0x02, 0, 0];
+        let crc = (crc16(&raw[..1]) ^ 0x0100).to_le_bytes();
+        raw[1..].copy_from_slice(&crc);
+        let mut frame = [0u8; 8];
+        let len = cobs_encode(&raw, &mut frame).unwrap();
+        assert_eq!(decode_frame(&mut frame[..len]), Err(FrameError::Crc));
+
+        // przekłamanie dowolnego bajtu ramki musi zostać wykryte
+        let message = Message::Reading(Reading { temperature: 21.5, humidity: 40.0 });
+        let mut buf = [0u8; MAX_FRAME];
+        let len = encode_frame(&message, &mut buf).unwrap().len() - 1;
+        for i in 0..len {
+            let mut corrupted = buf;
+            corrupted[i] ^= 0x10;
+            assert!(decode_frame(&mut corrupted[..len]).is_err(), "byte {}", i);
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_type_and_short_frames() {
+        let mut raw = [0x42, 0, 0];
+        let crc = crc16(&raw[..1]).to_le_bytes();
+        raw[1..].copy_from_slice(&crc);
+        let mut frame = [0u8; 8];
+        let len = cobs_encode(&raw, &mut frame).unwrap();
+        assert_eq!(decode_frame(&mut frame[..len]), Err(FrameError::UnknownType(0x42)));
+
+        let mut frame = [0x02, 0x01];
+        assert_eq!(decode_frame(&mut frame), Err(FrameError::TooShort));
+    }
+
+    #[test]
+    fn rejects_payload_of_wrong_length() {
+        // GetStatus nie ma danych, dodatkowy bajt jest błędem
+        let mut raw = [0x04, 0x07, 0, 0];
+        let crc = crc16(&raw[..2]).to_le_bytes();
+        raw[2..].copy_from_slice(&crc);
+        let mut frame = [0u8; 8];
+        let len = cobs_encode(&raw, &mut frame).unwrap();
+        assert_eq!(decode_frame(&mut frame[..len]), Err(FrameError::Payload));
+    }
+
+    #[test]
+    fn reports_small_output_buffer() {
+        let mut buf = [0u8; 4];
+        assert_eq!(
+            encode_frame(&Message::Reading(Reading { temperature: 1.0, humidity: 2.0 }), &mut buf),
+            Err(FrameError::BufferTooSmall)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use core::fmt;
+
+/// Przyczyna ostatniego restartu, odczytywana z flag rejestru RCC_CSR (STM32F3)
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetCause {
+    /// Brak rozpoznanej flagi
+    Unknown = 0,
+    /// Włączenie zasilania lub jego spadek poniżej progu (POR/PDR, STM32F3 nie rozróżnia brownout)
+    PowerOn = 1,
+    /// Niski stan na wyprowadzeniu NRST
+    Pin = 2,
+    /// Niezależny watchdog (IWDG)
+    Watchdog = 3,
+    /// Watchdog okienkowy (WWDG)
+    WindowWatchdog = 4,
+    /// Restart programowy (`reset`, SCB::sys_reset)
+    Software = 5,
+    /// Wejście w tryb niskiego poboru mocy
+    LowPower = 6,
+    /// Przeładowanie bajtów opcji
+    OptionBytes = 7,
+}
+
+// Flagi RCC_CSR
+const LPWRRSTF: u32 = 1 << 31;
+const WWDGRSTF: u32 = 1 << 30;
+const IWDGRSTF: u32 = 1 << 29;
+const SFTRSTF: u32 = 1 << 28;
+const PORRSTF: u32 = 1 << 27;
+const PINRSTF: u32 = 1 << 26;
+const OBLRSTF: u32 = 1 << 25;
+
+impl ResetCause {
+    /// Funkcja rozpoznająca przyczynę z wartości RCC_CSR. Każdy restart ustawia także PINRSTF
+    /// (impuls wychodzi na NRST), więc flaga wyprowadzenia ma najniższy priorytet.
+    pub fn from_rcc_csr(csr: u32) -> Self {
+        if csr & LPWRRSTF != 0 {
+            ResetCause::LowPower
+        } else if csr & WWDGRSTF != 0 {
+            ResetCause::WindowWatchdog
+        } else if csr & IWDGRSTF != 0 {
+            ResetCause::Watchdog
+        } else if csr & SFTRSTF != 0 {
+            ResetCause::Software
+        } else if csr & PORRSTF != 0 {
+            ResetCause::PowerOn
+        } else if csr & OBLRSTF != 0 {
+            ResetCause::OptionBytes
+        } else if csr & PINRSTF != 0 {
+            ResetCause::Pin
+        } else {
+            ResetCause::Unknown
+        }
+    }
+
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Some(match id {
+            0 => ResetCause::Unknown,
+            1 => ResetCause::PowerOn,
+            2 => ResetCause::Pin,
+            3 => ResetCause::Watchdog,
+            4 => ResetCause::WindowWatchdog,
+            5 => ResetCause::Software,
+            6 => ResetCause::LowPower,
+            7 => ResetCause::OptionBytes,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ResetCause::Unknown => "unknown",
+            ResetCause::PowerOn => "power-on",
+            ResetCause::Pin => "pin",
+            ResetCause::Watchdog => "watchdog",
+            ResetCause::WindowWatchdog => "window-watchdog",
+            ResetCause::Software => "software",
+            ResetCause::LowPower => "low-power",
+            ResetCause::OptionBytes => "option-bytes",
+        }
+    }
+}
+
+impl fmt::Display for ResetCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_rcc_csr_flags() {
+        // Po włączeniu zasilania ustawione są PORRSTF i PINRSTF
+        assert_eq!(ResetCause::from_rcc_csr(PORRSTF | PINRSTF), ResetCause::PowerOn);
+        assert_eq!(ResetCause::from_rcc_csr(SFTRSTF | PINRSTF), ResetCause::Software);
+        assert_eq!(ResetCause::from_rcc_csr(IWDGRSTF | PINRSTF), ResetCause::Watchdog);
+        assert_eq!(ResetCause::from_rcc_csr(PINRSTF), ResetCause::Pin);
+        assert_eq!(ResetCause::from_rcc_csr(0), ResetCause::Unknown);
+    }
+
+    #[test]
+    fn ids_round_trip() {
+        for id in 0..8 {
+            assert_eq!(ResetCause::from_id(id).map(ResetCause::id), Some(id));
+        }
+        assert_eq!(ResetCause::from_id(8), None);
+    }
+}
@@ -0,0 +1,243 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Ustawienia wysyłania pomiarów: format rekordów, zasada raportowania i subskrybowane strumienie.
+//! Wspólne dla parsera poleceń, zapisu konfiguracji i telemetrii, dzięki czemu parser
+//! nie zależy od kodu formatowania rekordów.
+
+use core::fmt;
+
+/// Format pomiarów wybierany poleceniem `set format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// `Temp: 23.4, Hum: 45, Seq: 1, Uptime: 1000 ms, Quality: ok`
+    #[default]
+    Human,
+    /// `seq,uptime_ms,temperature,humidity,quality` z nagłówkiem
+    Csv,
+    /// `{"seq":1,"uptime_ms":1000,"temperature":23.4,"humidity":45,"quality":"ok"}`
+    Json,
+    /// `thermometer,quality=ok temperature=23.4,humidity=45,seq=1i,uptime_ms=1000i`
+    Influx,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Human, Format::Csv, Format::Json, Format::Influx];
+
+    /// Nazwa używana w poleceniach
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Human => "human",
+            Format::Csv => "csv",
+            Format::Json => "json",
+            Format::Influx => "influx",
+        }
+    }
+
+    /// Format o podanej nazwie, wielkość liter nie ma znaczenia
+    pub fn from_name(name: &str) -> Option<Format> {
+        Format::ALL.into_iter().find(|f| f.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Identyfikator zapisywany w konfiguracji
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Format> {
+        Format::ALL.into_iter().find(|f| f.id() == id)
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Najdłuższy okres raportowania i podtrzymania
+pub const MAX_REPORT_MS: u32 = 3_600_000;
+/// Najkrótszy okres podtrzymania przy raportowaniu zmian
+pub const MIN_KEEPALIVE_MS: u32 = 1_000;
+/// Największy próg zmiany temperatury (zakres czujnika)
+pub const MAX_TEMPERATURE_DEADBAND: f32 = 50.0;
+/// Największy próg zmiany wilgotności
+pub const MAX_HUMIDITY_DEADBAND: f32 = 100.0;
+
+/// Zasada wysyłania pomiarów ustawiana poleceniem `set report`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Policy {
+    /// Pomiar nie częściej niż co `interval_ms`, 0 oznacza każdy pomiar
+    Interval { interval_ms: u32 },
+    /// Pomiar po zmianie temperatury (°C) lub wilgotności (%) co najmniej o próg
+    /// oraz po zmianie jakości pomiaru (np. błąd czujnika), a bez zmian co `keepalive_ms`
+    Change { temperature: f32, humidity: f32, keepalive_ms: u32 },
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy::Interval { interval_ms: 0 }
+    }
+}
+
+impl Policy {
+    /// Czy parametry mieszczą się w dopuszczalnych zakresach, progi muszą być dodatnie
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Policy::Interval { interval_ms } => interval_ms <= MAX_REPORT_MS,
+            Policy::Change { temperature, humidity, keepalive_ms } => {
+                temperature > 0.0
+                    && temperature <= MAX_TEMPERATURE_DEADBAND
+                    && humidity > 0.0
+                    && humidity <= MAX_HUMIDITY_DEADBAND
+                    && (MIN_KEEPALIVE_MS..=MAX_REPORT_MS).contains(&keepalive_ms)
+            }
+        }
+    }
+}
+
+/// Format zgodny z poleceniem: "interval 10000" lub "change 0.5 2 60000"
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Interval { interval_ms } => write!(f, "interval {}", interval_ms),
+            Policy::Change { temperature, humidity, keepalive_ms } => {
+                write!(f, "change {} {} {}", temperature, humidity, keepalive_ms)
+            }
+        }
+    }
+}
+
+/// Strumień danych wysyłanych bez zapytania, wybierany poleceniami `subscribe` i `unsubscribe`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// Pomiary według zasady raportowania
+    Readings,
+    /// Przejścia temperatury do innej strefy
+    Zones,
+    /// Zgłoszenie i usunięcie błędów
+    Errors,
+}
+
+impl Stream {
+    pub const ALL: [Stream; 3] = [Stream::Readings, Stream::Zones, Stream::Errors];
+
+    /// Nazwa używana w poleceniach
+    pub fn name(self) -> &'static str {
+        match self {
+            Stream::Readings => "readings",
+            Stream::Zones => "zones",
+            Stream::Errors => "errors",
+        }
+    }
+
+    /// Strumień o podanej nazwie, wielkość liter nie ma znaczenia
+    pub fn from_name(name: &str) -> Option<Stream> {
+        Stream::ALL.into_iter().find(|s| s.name().eq_ignore_ascii_case(name))
+    }
+
+    fn mask(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+/// Zbiór subskrybowanych strumieni (maska bitowa), domyślnie tylko pomiary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Streams(u8);
+
+impl Default for Streams {
+    fn default() -> Self {
+        Streams(Stream::Readings.mask())
+    }
+}
+
+impl Streams {
+    pub const NONE: Streams = Streams(0);
+
+    /// Maska bitowa zapisywana w konfiguracji
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Zbiór z maski, None gdy zawiera nieznane strumienie
+    pub fn from_bits(bits: u8) -> Option<Streams> {
+        let all = Stream::ALL.iter().fold(0, |mask, s| mask | s.mask());
+        (bits & !all == 0).then_some(Streams(bits))
+    }
+
+    pub fn contains(self, stream: Stream) -> bool {
+        self.0 & stream.mask() != 0
+    }
+
+    pub fn insert(&mut self, stream: Stream) {
+        self.0 |= stream.mask();
+    }
+
+    pub fn remove(&mut self, stream: Stream) {
+        self.0 &= !stream.mask();
+    }
+}
+
+/// Format "readings,zones" lub "none"
+impl fmt::Display for Streams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for stream in Stream::ALL.into_iter().filter(|s| self.contains(*s)) {
+            if !first {
+                f.write_str(",")?;
+            }
+            f.write_str(stream.name())?;
+            first = false;
+        }
+        if first {
+            f.write_str("none")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::string::ToString;
+
+    const CHANGE: Policy = Policy::Change { temperature: 0.5, humidity: 2.0, keepalive_ms: 60_000 };
+
+    #[test]
+    fn names_and_ids_round_trip() {
+        for format in Format::ALL {
+            assert_eq!(Format::from_name(format.name()), Some(format));
+            assert_eq!(Format::from_id(format.id()), Some(format));
+        }
+        assert_eq!(Format::from_name("JSON"), Some(Format::Json));
+        assert_eq!(Format::from_name("xml"), None);
+        assert_eq!(Format::from_id(4), None);
+    }
+
+    #[test]
+    fn validates_policies() {
+        assert!(Policy::default().is_valid());
+        assert!(CHANGE.is_valid());
+        assert!(!Policy::Interval { interval_ms: MAX_REPORT_MS + 1 }.is_valid());
+        assert!(!Policy::Change { temperature: 0.0, humidity: 2.0, keepalive_ms: 60_000 }.is_valid());
+        assert!(!Policy::Change { temperature: 0.5, humidity: 101.0, keepalive_ms: 60_000 }.is_valid());
+        assert!(!Policy::Change { temperature: 0.5, humidity: 2.0, keepalive_ms: 0 }.is_valid());
+        assert_eq!(CHANGE.to_string(), "change 0.5 2 60000");
+    }
+
+    #[test]
+    fn streams_set() {
+        let mut streams = Streams::default();
+        assert_eq!(streams.to_string(), "readings");
+        streams.insert(Stream::Errors);
+        assert_eq!(streams.to_string(), "readings,errors");
+        streams.remove(Stream::Readings);
+        streams.remove(Stream::Errors);
+        assert_eq!(streams, Streams::NONE);
+        assert_eq!(streams.to_string(), "none");
+        assert_eq!(Streams::from_bits(0b111), Some(Streams(0b111)));
+        assert_eq!(Streams::from_bits(0b1000), None);
+        assert_eq!(Stream::from_name("Zones"), Some(Stream::Zones));
+    }
+}
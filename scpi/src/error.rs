@@ -0,0 +1,114 @@
+use core::fmt;
+use heapless::Deque;
+
+/// Błędy SCPI z kodami według IEEE 488.2 / SCPI-99, odczytywane przez `SYST:ERR?`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Niepoprawna składnia, np. nieznany znak
+    SyntaxError,
+    /// Nieoczekiwany parametr
+    ParameterNotAllowed,
+    /// Brak wymaganego parametru
+    MissingParameter,
+    /// Parametr nie jest liczbą
+    DataTypeError,
+    /// Nieznane polecenie
+    UndefinedHeader,
+    /// Polecenie zapisu przy blokadzie PIN-em
+    CommandProtected,
+    /// Ustawienie sprzeczne z innymi (np. zła kolejność granic)
+    SettingsConflict,
+    /// Wartość spoza zakresu
+    DataOutOfRange,
+    /// Brak aktualnego pomiaru
+    DataStale,
+    /// Błąd urządzenia, np. zapisu konfiguracji
+    DeviceError,
+    /// Kolejka błędów była pełna, kolejne błędy zostały utracone
+    QueueOverflow,
+}
+
+impl Error {
+    pub fn code(self) -> i16 {
+        match self {
+            Error::SyntaxError => -102,
+            Error::ParameterNotAllowed => -108,
+            Error::MissingParameter => -109,
+            Error::DataTypeError => -104,
+            Error::UndefinedHeader => -113,
+            Error::CommandProtected => -203,
+            Error::SettingsConflict => -221,
+            Error::DataOutOfRange => -222,
+            Error::DataStale => -230,
+            Error::DeviceError => -300,
+            Error::QueueOverflow => -350,
+        }
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            Error::SyntaxError => "Syntax error",
+            Error::ParameterNotAllowed => "Parameter not allowed",
+            Error::MissingParameter => "Missing parameter",
+            Error::DataTypeError => "Data type error",
+            Error::UndefinedHeader => "Undefined header",
+            Error::CommandProtected => "Command protected",
+            Error::SettingsConflict => "Settings conflict",
+            Error::DataOutOfRange => "Data out of range",
+            Error::DataStale => "Data stale",
+            Error::DeviceError => "Device-specific error",
+            Error::QueueOverflow => "Queue overflow",
+        }
+    }
+}
+
+/// Format odpowiedzi `SYST:ERR?`: `-113,"Undefined header"`
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},\"{}\"", self.code(), self.message())
+    }
+}
+
+/// Odpowiedź `SYST:ERR?` przy pustej kolejce
+pub const NO_ERROR: &str = "0,\"No error\"";
+
+/// Kolejka błędów FIFO. Przy przepełnieniu ostatni wpis zastępowany jest przez
+/// `QueueOverflow`, tak jak wymaga SCPI, a najstarsze błędy zostają zachowane.
+pub struct ErrorQueue<const N: usize> {
+    errors: Deque<Error, N>,
+}
+
+impl<const N: usize> Default for ErrorQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> ErrorQueue<N> {
+    pub const fn new() -> Self {
+        ErrorQueue { errors: Deque::new() }
+    }
+
+    pub fn push(&mut self, error: Error) {
+        if self.errors.is_full() {
+            if let Some(last) = self.errors.back_mut() {
+                *last = Error::QueueOverflow;
+            }
+        } else {
+            self.errors.push_back(error).ok();
+        }
+    }
+
+    /// Najstarszy błąd, None gdy kolejka jest pusta
+    pub fn pop(&mut self) -> Option<Error> {
+        self.errors.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.errors.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
@@ -0,0 +1,282 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Polecenia SCPI termometru dla narzędzi laboratoryjnych (np. PyVISA).
+//!
+//! Nagłówki można podawać w formie krótkiej (`MEAS:TEMP?`) lub długiej (`MEASure:TEMPerature?`),
+//! bez rozróżniania wielkości liter. Polecenia bez `?` nie mają odpowiedzi, a błędy trafiają
+//! do kolejki odczytywanej przez `SYST:ERR?`. Crate nie zależy od sprzętu.
+
+mod error;
+
+pub use error::*;
+
+/// Wartość zwracana zamiast pomiaru, gdy pomiar jest niedostępny (SCPI "not a number")
+pub const NOT_A_NUMBER: &str = "9.91E+37";
+
+/// Granica temperatury ustawiana przez `CONF:LIM:<granica>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Cold,
+    Optimal,
+    Hot,
+    Critical,
+}
+
+impl Limit {
+    /// Indeks w tablicy granic cold, optimal, hot, critical
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Rozpoznane polecenie SCPI
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Request {
+    /// `*IDN?` - producent, model, numer seryjny, wersja
+    Identify,
+    /// `*RST` - przywrócenie domyślnych granic i okresu próbkowania
+    Reset,
+    /// `*CLS` - wyczyszczenie kolejki błędów
+    ClearStatus,
+    /// `*OPC?` - polecenia wykonywane są od razu, odpowiedź to zawsze 1
+    OperationComplete,
+    /// `MEAS:TEMP?` - temperatura w °C
+    MeasureTemperature,
+    /// `MEAS:HUM?` - wilgotność w %
+    MeasureHumidity,
+    /// `CONF:LIM:COLD <v>` ... `CONF:LIM:CRIT <v>`
+    SetLimit(Limit, f32),
+    /// `CONF:LIM:COLD?` ... `CONF:LIM:CRIT?`
+    GetLimit(Limit),
+    /// `SYST:ERR?` - najstarszy błąd z kolejki
+    NextError,
+}
+
+impl Request {
+    /// Polecenia zmieniające konfigurację, wymagające odblokowania PIN-em
+    pub fn is_write(&self) -> bool {
+        matches!(self, Request::Reset | Request::SetLimit(..))
+    }
+}
+
+/// Funkcja odróżniająca polecenie SCPI od poleceń tekstowych termometru: polecenia
+/// wspólne zaczynają się od `*`, a nagłówki zawierają `:` lub kończą się `?`
+pub fn is_scpi(line: &str) -> bool {
+    let header = line.trim().split_ascii_whitespace().next().unwrap_or("");
+    header.starts_with('*') || header.contains(':') || header.ends_with('?')
+}
+
+/// Funkcja rozpoznająca jedno polecenie SCPI (bez separatora `;`)
+/// # Examples
+/// ```
+/// use scpi::{Error, Request};
+///
+/// assert_eq!(scpi::parse("MEAS:TEMP?"), Ok(Request::MeasureTemperature));
+/// assert_eq!(scpi::parse("*idn?"), Ok(Request::Identify));
+/// assert_eq!(scpi::parse("*RST 1"), Err(Error::ParameterNotAllowed));
+/// ```
+pub fn parse(line: &str) -> Result<Request, Error> {
+    let line = line.trim();
+    let (header, parameter) = match line.find(|c: char| c.is_ascii_whitespace()) {
+        Some(i) => (&line[..i], line[i..].trim()),
+        None => (line, ""),
+    };
+    let (header, query) = match header.strip_suffix('?') {
+        Some(header) => (header, true),
+        None => (header, false),
+    };
+
+    if let Some(common) = header.strip_prefix('*') {
+        let request = match query {
+            true if common.eq_ignore_ascii_case("IDN") => Request::Identify,
+            false if common.eq_ignore_ascii_case("RST") => Request::Reset,
+            false if common.eq_ignore_ascii_case("CLS") => Request::ClearStatus,
+            true if common.eq_ignore_ascii_case("OPC") => Request::OperationComplete,
+            _ => return Err(Error::UndefinedHeader),
+        };
+        return no_parameter(parameter, request);
+    }
+
+    let header = header.strip_prefix(':').unwrap_or(header);
+    let mut path: [&str; 3] = [""; 3];
+    let mut depth = 0;
+    for node in header.split(':') {
+        if node.is_empty() || !node.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(Error::SyntaxError);
+        }
+        if depth == path.len() {
+            return Err(Error::UndefinedHeader);
+        }
+        path[depth] = node;
+        depth += 1;
+    }
+
+    match &path[..depth] {
+        [meas, what] if mnemonic(meas, "MEASure") && query => {
+            let request = if mnemonic(what, "TEMPerature") {
+                Request::MeasureTemperature
+            } else if mnemonic(what, "HUMidity") {
+                Request::MeasureHumidity
+            } else {
+                return Err(Error::UndefinedHeader);
+            };
+            no_parameter(parameter, request)
+        }
+        [conf, lim, which] if mnemonic(conf, "CONFigure") && mnemonic(lim, "LIMit") => {
+            let limit = if mnemonic(which, "COLD") {
+                Limit::Cold
+            } else if mnemonic(which, "OPTimal") {
+                Limit::Optimal
+            } else if mnemonic(which, "HOT") {
+                Limit::Hot
+            } else if mnemonic(which, "CRITical") {
+                Limit::Critical
+            } else {
+                return Err(Error::UndefinedHeader);
+            };
+            if query {
+                no_parameter(parameter, Request::GetLimit(limit))
+            } else {
+                Ok(Request::SetLimit(limit, number(parameter)?))
+            }
+        }
+        [syst, err] | [syst, err, _] if mnemonic(syst, "SYSTem") && mnemonic(err, "ERRor") && query => {
+            // Dopuszczalna jest także pełna forma `SYST:ERR:NEXT?`
+            if depth == 3 && !mnemonic(path[2], "NEXT") {
+                return Err(Error::UndefinedHeader);
+            }
+            no_parameter(parameter, Request::NextError)
+        }
+        _ => Err(Error::UndefinedHeader),
+    }
+}
+
+// Porównanie z formą krótką (wielkie litery wzorca) lub długą, bez rozróżniania wielkości liter
+fn mnemonic(word: &str, pattern: &str) -> bool {
+    let short = pattern.bytes().take_while(|b| b.is_ascii_uppercase()).count();
+    word.eq_ignore_ascii_case(&pattern[..short]) || word.eq_ignore_ascii_case(pattern)
+}
+
+fn no_parameter(parameter: &str, request: Request) -> Result<Request, Error> {
+    if parameter.is_empty() {
+        Ok(request)
+    } else {
+        Err(Error::ParameterNotAllowed)
+    }
+}
+
+fn number(parameter: &str) -> Result<f32, Error> {
+    if parameter.is_empty() {
+        return Err(Error::MissingParameter);
+    }
+    if parameter.contains(|c: char| c == ',' || c.is_ascii_whitespace()) {
+        return Err(Error::ParameterNotAllowed);
+    }
+    let value: f32 = parameter.parse().map_err(|_| Error::DataTypeError)?;
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(Error::DataTypeError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::string::ToString;
+
+    #[test]
+    fn parses_common_commands() {
+        assert_eq!(parse("*IDN?"), Ok(Request::Identify));
+        assert_eq!(parse("*idn?"), Ok(Request::Identify));
+        assert_eq!(parse("*RST"), Ok(Request::Reset));
+        assert_eq!(parse("*CLS"), Ok(Request::ClearStatus));
+        assert_eq!(parse("*OPC?"), Ok(Request::OperationComplete));
+        assert_eq!(parse("*IDN"), Err(Error::UndefinedHeader));
+        assert_eq!(parse("*RST 1"), Err(Error::ParameterNotAllowed));
+    }
+
+    #[test]
+    fn accepts_short_and_long_forms() {
+        assert_eq!(parse("MEAS:TEMP?"), Ok(Request::MeasureTemperature));
+        assert_eq!(parse("measure:temperature?"), Ok(Request::MeasureTemperature));
+        assert_eq!(parse(":MEAS:HUM?"), Ok(Request::MeasureHumidity));
+        assert_eq!(parse("SYST:ERR?"), Ok(Request::NextError));
+        assert_eq!(parse("SYSTem:ERRor:NEXT?"), Ok(Request::NextError));
+        // Formy pośrednie nie są dozwolone
+        assert_eq!(parse("MEASU:TEMP?"), Err(Error::UndefinedHeader));
+    }
+
+    #[test]
+    fn parses_limits() {
+        assert_eq!(parse("CONF:LIM:COLD 5"), Ok(Request::SetLimit(Limit::Cold, 5.0)));
+        assert_eq!(parse("conf:lim:opt 2.25E1"), Ok(Request::SetLimit(Limit::Optimal, 22.5)));
+        assert_eq!(parse("CONFigure:LIMit:HOT -1.5"), Ok(Request::SetLimit(Limit::Hot, -1.5)));
+        assert_eq!(parse("CONF:LIM:CRIT?"), Ok(Request::GetLimit(Limit::Critical)));
+        assert_eq!(Limit::Critical.index(), 3);
+    }
+
+    #[test]
+    fn reports_parameter_errors() {
+        assert_eq!(parse("CONF:LIM:COLD"), Err(Error::MissingParameter));
+        assert_eq!(parse("CONF:LIM:COLD abc"), Err(Error::DataTypeError));
+        assert_eq!(parse("CONF:LIM:COLD inf"), Err(Error::DataTypeError));
+        assert_eq!(parse("CONF:LIM:COLD 1,2"), Err(Error::ParameterNotAllowed));
+        assert_eq!(parse("CONF:LIM:COLD? 1"), Err(Error::ParameterNotAllowed));
+        assert_eq!(parse("MEAS:TEMP? 1"), Err(Error::ParameterNotAllowed));
+    }
+
+    #[test]
+    fn reports_header_errors() {
+        assert_eq!(parse("MEAS:PRES?"), Err(Error::UndefinedHeader));
+        assert_eq!(parse("MEAS:TEMP"), Err(Error::UndefinedHeader));
+        assert_eq!(parse("CONF:LIM:WARM 3"), Err(Error::UndefinedHeader));
+        assert_eq!(parse("MEAS::TEMP?"), Err(Error::SyntaxError));
+        assert_eq!(parse("MEAS:TEMP$?"), Err(Error::SyntaxError));
+        assert_eq!(parse("A:B:C:D?"), Err(Error::UndefinedHeader));
+    }
+
+    #[test]
+    fn tells_scpi_from_text_commands() {
+        assert!(is_scpi("*IDN?"));
+        assert!(is_scpi("MEAS:TEMP?"));
+        assert!(is_scpi("syst:err?"));
+        assert!(!is_scpi("get bounds"));
+        assert!(!is_scpi("s0,25,30,35"));
+        assert!(!is_scpi("set bounds 0,25,30,35"));
+    }
+
+    #[test]
+    fn only_changes_need_unlocking() {
+        assert!(Request::Reset.is_write());
+        assert!(Request::SetLimit(Limit::Hot, 30.0).is_write());
+        assert!(!Request::GetLimit(Limit::Hot).is_write());
+        assert!(!Request::MeasureTemperature.is_write());
+    }
+
+    #[test]
+    fn error_queue_is_fifo_and_marks_overflow() {
+        let mut queue = ErrorQueue::<3>::new();
+        assert_eq!(queue.pop(), None);
+        queue.push(Error::UndefinedHeader);
+        queue.push(Error::DataTypeError);
+        queue.push(Error::MissingParameter);
+        queue.push(Error::SyntaxError);
+        assert_eq!(queue.pop(), Some(Error::UndefinedHeader));
+        assert_eq!(queue.pop(), Some(Error::DataTypeError));
+        assert_eq!(queue.pop(), Some(Error::QueueOverflow));
+        assert!(queue.is_empty());
+        queue.push(Error::SyntaxError);
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn formats_errors_for_syst_err() {
+        assert_eq!(Error::UndefinedHeader.to_string(), "-113,\"Undefined header\"");
+        assert_eq!(Error::CommandProtected.to_string(), "-203,\"Command protected\"");
+    }
+}
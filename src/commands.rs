@@ -0,0 +1,287 @@
+// Obsługa poleceń odebranych przez port szeregowy
+use command::{Command, ErrorCode, PinHash, ProtocolMode, BROADCAST, HELP};
+use cortex_m::peripheral::DWT;
+use core::fmt::{self, Write};
+use leds::BoundaryError;
+use usart_1::{uprintln, Protocol, Reply};
+
+#[cfg(feature = "debug-shell")]
+use crate::debug;
+use crate::{info, instrument};
+use crate::{apply_serial, confirm_serial, set_protocol, set_rs485, set_shell, settings, wait_tx_idle, with_serial, write_long, PendingSerial, Port, State};
+
+/// Funkcja wykonująca polecenie z jednej linii i wysyłająca odpowiedź `OK ...` lub `ERR <kod> <opis>`.
+/// Polecenie `@<adres> <polecenie>` wykonywane jest tylko przez urządzenie o tym adresie, a odpowiedź
+/// poprzedzona jest adresem. Polecenie rozgłoszeniowe `@0` wykonują wszystkie urządzenia bez odpowiedzi.
+pub fn handle_line(line: &str, state: &mut State) {
+    match command::split_address(line) {
+        Ok(Some((address, command))) => {
+            let reply = if address == state.address {
+                Reply::Addressed(address)
+            } else if address == BROADCAST {
+                Reply::Silent
+            } else {
+                return;
+            };
+            with_serial(|serial| serial.set_reply(reply));
+            handle_command(command, state);
+            with_serial(|serial| serial.set_reply(Reply::Plain));
+        }
+        // Na magistrali RS-485 odpowiadają tylko zaadresowane urządzenia, inaczej nadawałyby jednocześnie
+        Ok(None) if state.rs485 => {}
+        Ok(None) => handle_command(line, state),
+        Err(_) if state.rs485 => {}
+        Err(code) => {
+            with_serial(|serial| command::err(serial, code, format_args!("{}", line.trim())).ok());
+        }
+    }
+}
+
+fn handle_command(line: &str, state: &mut State) {
+    // Polecenia SCPI rozpoznawane są przed poleceniami tekstowymi, np. "syst:err?" zaczyna się od 's'
+    if scpi::is_scpi(line) {
+        confirm_serial(state);
+        instrument::handle(line, state);
+        return;
+    }
+    let command = match command::parse(line) {
+        Ok(command) => command,
+        Err(code) => {
+            with_serial(|serial| command::err(serial, code, format_args!("{}", line.trim())).ok());
+            return;
+        }
+    };
+    confirm_serial(state);
+    let now = clock::now_ms();
+    if command.is_write() && state.access.is_locked(now) {
+        with_serial(|serial| command::err(serial, ErrorCode::Locked, format_args!("unlock first")).ok());
+        return;
+    }
+    state.access.touch(now);
+    // Skrót PIN-u liczony jest poza sekcją krytyczną, aby nie blokować przerwań odbioru
+    match command {
+        Command::Unlock(pin) => {
+            let result = state.access.unlock(pin, now);
+            with_serial(|serial| match result {
+                Ok(()) => command::ok(serial, format_args!("unlocked")).ok(),
+                Err(e) => command::err(serial, ErrorCode::Locked, format_args!("{}", e)).ok(),
+            });
+            return;
+        }
+        Command::SetPin(pin) => {
+            state.access.set_pin(pin.map(|pin| PinHash::new(pin, salt())), now);
+            let response = if pin.is_some() { "pin set" } else { "pin off" };
+            with_serial(|serial| saved(serial, state, format_args!("{}", response)).ok());
+            return;
+        }
+        _ => {}
+    }
+    // Lista poleceń nie mieści się w buforach TX, jest wysyłana częściami
+    if command == Command::Help {
+        with_serial(|serial| serial.write_str("OK commands: ").ok());
+        write_long(HELP);
+        with_serial(|serial| serial.write_str("\n").ok());
+        return;
+    }
+    if command == Command::Reset {
+        // Odpowiedź musi zostać wysłana przed restartem
+        with_serial(|serial| command::ok(serial, format_args!("reset")).ok());
+        wait_tx_idle();
+        cortex_m::peripheral::SCB::sys_reset();
+    }
+    with_serial(|serial| execute(command, state, serial).ok());
+    // Parametry portu zmieniane są dopiero po wysłaniu odpowiedzi, na którą czeka się poza sekcją krytyczną
+    match command {
+        Command::SetSerial(settings) => {
+            wait_tx_idle();
+            with_serial(|serial| apply_serial(serial, settings));
+        }
+        // Odrzucone `set rs485 on` nie zmienia `state.rs485`
+        Command::SetRs485(enabled) if state.rs485 == enabled => {
+            wait_tx_idle();
+            with_serial(|serial| set_rs485(serial, enabled));
+        }
+        _ => {}
+    }
+}
+
+fn execute(command: Command, state: &mut State, serial: &mut Port) -> fmt::Result {
+    match command {
+        Command::Version => command::ok(serial, format_args!("version {}", env!("CARGO_PKG_VERSION"))),
+        Command::Info => command::ok(serial, format_args!("info {}", info::Text(state.reset_cause))),
+        Command::Caps => command::ok(serial, format_args!("{}", caps::CAPS)),
+        Command::GetBounds => command::ok(serial, format_args!("bounds {}", state.tb)),
+        // Przy błędzie granice pozostają bez zmian
+        Command::SetBounds(bounds) => match state.tb.set_from_string(bounds) {
+            Ok(()) => saved(serial, state, format_args!("bounds {}", state.tb)),
+            Err(e) => command::err(serial, boundary_error_code(e), format_args!("{}", e)),
+        },
+        Command::GetReading => match state.reading {
+            Some(reading) => command::ok(serial, format_args!("reading temp={} hum={}", reading.temperature, reading.humidity)),
+            None => command::err(serial, ErrorCode::Unavailable, format_args!("no valid measurement")),
+        },
+        Command::GetInterval => command::ok(serial, format_args!("interval {}", state.interval_ms)),
+        Command::SetInterval(ms) => {
+            state.interval_ms = ms;
+            saved(serial, state, format_args!("interval {}", ms))
+        }
+        Command::GetFormat => command::ok(serial, format_args!("format {}", state.format)),
+        // Nagłówek CSV wysyłany jest zaraz po potwierdzeniu
+        Command::SetFormat(format) => {
+            state.format = format;
+            saved(serial, state, format_args!("format {}", format))?;
+            telemetry::write_header(serial, format)
+        }
+        Command::GetProtocol => command::ok(serial, format_args!("protocol {}", protocol_name(serial.protocol()))),
+        // Odpowiedź wysyłana jest jeszcze tekstem, kolejne bajty są już traktowane jako ramki
+        // Ramki binarne nie mają adresu, na magistrali RS-485 odpowiadałyby wszystkie urządzenia
+        Command::SetProtocol(ProtocolMode::Binary) if state.rs485 => {
+            command::err(serial, ErrorCode::Rejected, format_args!("binary protocol not allowed with rs485"))
+        }
+        Command::SetProtocol(mode) => {
+            state.protocol = match mode {
+                ProtocolMode::Text => Protocol::Text,
+                ProtocolMode::Binary => Protocol::Binary,
+                ProtocolMode::Modbus => Protocol::Modbus,
+            };
+            saved(serial, state, format_args!("protocol {}", protocol_name(state.protocol)))?;
+            set_protocol(serial, state.protocol);
+            Ok(())
+        }
+        Command::GetAddress => command::ok(serial, format_args!("address {}", state.address)),
+        Command::SetAddress(address) => {
+            state.address = address;
+            saved(serial, state, format_args!("address {}", address))
+        }
+        Command::GetSerial => command::ok(serial, format_args!("serial {}", state.serial)),
+        // Odpowiedź wysyłana jest z dotychczasowymi parametrami, nowe zapisywane są dopiero
+        // po poleceniu odebranym z ich użyciem, bez niego wracają poprzednie (`serve_commands`)
+        Command::SetSerial(settings) => {
+            command::ok(serial, format_args!("serial {}", settings))?;
+            state.serial_pending = Some(PendingSerial { previous: state.serial, since_ms: clock::now_ms() });
+            state.serial = settings;
+            Ok(())
+        }
+        Command::GetUart => command::ok(
+            serial,
+            format_args!("uart {} rx_dropped={} tx_dropped={}", serial.rx_errors(), serial.rx_dropped(), serial.tx_dropped()),
+        ),
+        Command::ClearUart => {
+            serial.clear_errors();
+            command::ok(serial, format_args!("uart cleared"))
+        }
+        Command::GetShell => command::ok(serial, format_args!("shell {}", if state.shell { "on" } else { "off" })),
+        // Zmiana obowiązuje od kolejnej linii, zachęta po odpowiedzi pojawia się już tylko przy włączonej powłoce
+        Command::SetShell(enabled) => {
+            state.shell = enabled;
+            saved(serial, state, format_args!("shell {}", if enabled { "on" } else { "off" }))?;
+            set_shell(enabled && !state.rs485);
+            Ok(())
+        }
+        Command::GetReport => command::ok(serial, format_args!("report {}", state.report)),
+        Command::SetReport(policy) => {
+            state.report = policy;
+            saved(serial, state, format_args!("report {}", policy))
+        }
+        Command::GetSubscriptions => command::ok(serial, format_args!("subscriptions {}", state.streams)),
+        Command::Subscribe(stream) => {
+            state.streams.insert(stream);
+            saved(serial, state, format_args!("subscriptions {}", state.streams))
+        }
+        Command::Unsubscribe(stream) => {
+            state.streams.remove(stream);
+            saved(serial, state, format_args!("subscriptions {}", state.streams))
+        }
+        Command::GetRs485 => command::ok(serial, format_args!("rs485 {}", if state.rs485 { "on" } else { "off" })),
+        Command::SetRs485(true) if state.protocol == Protocol::Binary => {
+            command::err(serial, ErrorCode::Rejected, format_args!("rs485 not allowed with binary protocol"))
+        }
+        // Potwierdzenie wysyłane jest jeszcze w poprzednim trybie. Echo powłoki zakłócałoby
+        // magistralę, dlatego w trybie RS-485 powłoka jest wyłączona.
+        Command::SetRs485(enabled) => {
+            state.rs485 = enabled;
+            saved(serial, state, format_args!("rs485 {}", if enabled { "on" } else { "off" }))?;
+            set_shell(state.shell && !enabled);
+            Ok(())
+        }
+        Command::Lock => {
+            state.access.lock();
+            command::ok(serial, format_args!("locked"))
+        }
+        Command::GetLock => {
+            let status = match (state.access.pin(), state.access.is_locked(clock::now_ms())) {
+                (None, _) => "off",
+                (Some(_), true) => "locked",
+                (Some(_), false) => "unlocked",
+            };
+            command::ok(serial, format_args!("lock {}", status))
+        }
+        // Obsługiwane w `handle_line`
+        Command::Unlock(_) | Command::SetPin(_) => Ok(()),
+        // Dawne polecenia 'g' i 's' zachowują swój format odpowiedzi
+        Command::LegacyGet => {
+            uprintln!(serial, "Cold: {}, Optimal: {},  High: {}, Critical: {}", state.tb.cold(), state.tb.optimal(), state.tb.hot(), state.tb.critical());
+            Ok(())
+        }
+        Command::LegacySet(val) => {
+            uprintln!(serial, "Got s: {}", val);
+            match state.tb.set_from_string(val) {
+                Ok(()) => {
+                    // Dawny format odpowiedzi nie przewiduje błędu zapisu, jest on pomijany
+                    settings::save(state).ok();
+                    uprintln!(serial, "Temp values changed: Low: {}, Optimal: {},  High: {}, Critical: {}", state.tb.cold(), state.tb.optimal(), state.tb.hot(), state.tb.critical());
+                }
+                Err(e) => {
+                    uprintln!(serial, "Temp values not changed: {}", e);
+                }
+            }
+            Ok(())
+        }
+        // Obsługiwane w `handle_command`
+        Command::Reset | Command::Help => Ok(()),
+        #[cfg(feature = "debug-shell")]
+        Command::Peek(address) => debug::peek(serial, address),
+        #[cfg(feature = "debug-shell")]
+        Command::Poke(address, value) => debug::poke(serial, address, value),
+        #[cfg(feature = "debug-shell")]
+        Command::Dump(name) => debug::dump(serial, name),
+        #[cfg(feature = "debug-shell")]
+        Command::Stack => debug::stack(serial),
+    }
+}
+
+// Sól PIN-u z licznika cykli i zegara w chwili polecenia, zależnych od czasu pisania na klawiaturze
+fn salt() -> [u8; command::SALT_LEN] {
+    let mut salt = [0; command::SALT_LEN];
+    salt[..4].copy_from_slice(&DWT::cycle_count().to_le_bytes());
+    salt[4..].copy_from_slice(&clock::now_ms().to_le_bytes());
+    salt
+}
+
+// Odpowiedź na zmianę ustawienia zapisywanego w pamięci flash. Zmiana obowiązuje także,
+// gdy zapis się nie powiódł, ale wtedy zostanie utracona po restarcie.
+// Kasowanie strony wstrzymuje procesor, więc sekcja krytyczna nie wydłuża blokady przerwań.
+fn saved(serial: &mut Port, state: &State, response: fmt::Arguments) -> fmt::Result {
+    match settings::save(state) {
+        Ok(()) => command::ok(serial, response),
+        Err(e) => command::err(serial, ErrorCode::Unavailable, format_args!("applied but not saved: {}", e)),
+    }
+}
+
+fn protocol_name(protocol: Protocol) -> &'static str {
+    match protocol {
+        Protocol::Text => "text",
+        Protocol::Binary => "binary",
+        Protocol::Modbus => "modbus",
+    }
+}
+
+// Błędy formatu to zły argument, błędy wartości to odrzucenie przez urządzenie
+pub(crate) fn boundary_error_code(e: BoundaryError) -> ErrorCode {
+    match e {
+        BoundaryError::MissingField(_) => ErrorCode::MissingArgument,
+        BoundaryError::TooManyFields => ErrorCode::TooManyArguments,
+        BoundaryError::Parse(_) => ErrorCode::InvalidArgument,
+        BoundaryError::Order(_, _) | BoundaryError::OutOfRange(_) => ErrorCode::Rejected,
+    }
+}
@@ -0,0 +1,173 @@
+// Polecenia diagnostyczne funkcji `debug-shell`: odczyt i zapis pamięci, rejestry układów peryferyjnych i stos
+use core::fmt;
+use core::ptr;
+use command::ErrorCode;
+use stm32f3xx_hal::pac;
+
+use crate::Port;
+
+// Wzór wpisywany do wolnej części stosu, nadpisane słowa oznaczają użycie stosu
+const STACK_PAINT: u32 = 0xDEAD_BEEF;
+// Zapas poniżej wskaźnika stosu pozostawiany przy malowaniu (ramka funkcji malującej)
+const PAINT_MARGIN: u32 = 64;
+
+extern "C" {
+    // Symbole skryptu linkera cortex-m-rt: koniec danych statycznych (dno stosu) i szczyt stosu
+    static __sheap: u32;
+    static _stack_start: u32;
+}
+
+/// Obszar pamięci STM32F303VC, do którego dostęp nie kończy się błędem szyny
+struct Region {
+    start: u32,
+    end: u32,
+    writable: bool,
+}
+
+const REGIONS: [Region; 10] = [
+    // Flash 256 KB
+    Region { start: 0x0800_0000, end: 0x0804_0000, writable: false },
+    // CCM SRAM 8 KB
+    Region { start: 0x1000_0000, end: 0x1000_2000, writable: true },
+    // Pamięć systemowa (bootloader) i bajty opcji
+    Region { start: 0x1FFF_D800, end: 0x1FFF_F810, writable: false },
+    // SRAM 40 KB
+    Region { start: 0x2000_0000, end: 0x2000_A000, writable: true },
+    // Układy peryferyjne APB1, APB2, AHB1, AHB2 (GPIO) i AHB3 (ADC)
+    Region { start: 0x4000_0000, end: 0x4000_7800, writable: true },
+    Region { start: 0x4001_0000, end: 0x4001_6C00, writable: true },
+    Region { start: 0x4002_0000, end: 0x4002_4400, writable: true },
+    Region { start: 0x4800_0000, end: 0x4800_1800, writable: true },
+    Region { start: 0x5000_0000, end: 0x5000_0800, writable: true },
+    // Układy peryferyjne rdzenia Cortex-M4 (SysTick, NVIC, SCB, DWT)
+    Region { start: 0xE000_0000, end: 0xE010_0000, writable: true },
+];
+
+fn region(address: u32) -> Option<&'static Region> {
+    REGIONS.iter().find(|r| (r.start..r.end).contains(&address))
+}
+
+/// Nazwa rejestru i jego przesunięcie względem początku bloku
+type Register = (&'static str, u32);
+
+const RCC: [Register; 13] = [
+    ("cr", 0x00), ("cfgr", 0x04), ("cir", 0x08), ("apb2rstr", 0x0C), ("apb1rstr", 0x10),
+    ("ahbenr", 0x14), ("apb2enr", 0x18), ("apb1enr", 0x1C), ("bdcr", 0x20), ("csr", 0x24),
+    ("ahbrstr", 0x28), ("cfgr2", 0x2C), ("cfgr3", 0x30),
+];
+// Pomijane są rejestry tylko do zapisu (BSRR, BRR)
+const GPIO: [Register; 9] = [
+    ("moder", 0x00), ("otyper", 0x04), ("ospeedr", 0x08), ("pupdr", 0x0C), ("idr", 0x10),
+    ("odr", 0x14), ("lckr", 0x1C), ("afrl", 0x20), ("afrh", 0x24),
+];
+// Pomijany jest RDR, którego odczyt zabrałby odebrany bajt, oraz rejestry tylko do zapisu (RQR, ICR)
+const USART: [Register; 8] = [
+    ("cr1", 0x00), ("cr2", 0x04), ("cr3", 0x08), ("brr", 0x0C), ("gtpr", 0x10),
+    ("rtor", 0x14), ("isr", 0x1C), ("tdr", 0x28),
+];
+// Pomijany jest RXDR (odczyt kasuje RXNE) i ICR (tylko do zapisu)
+const I2C: [Register; 9] = [
+    ("cr1", 0x00), ("cr2", 0x04), ("oar1", 0x08), ("oar2", 0x0C), ("timingr", 0x10),
+    ("timeoutr", 0x14), ("isr", 0x18), ("pecr", 0x20), ("txdr", 0x28),
+];
+
+// Nazwa, adres bloku rejestrów i lista jego rejestrów dla nazwy z polecenia `dump`
+fn block(name: &str) -> Option<(&'static str, u32, &'static [Register])> {
+    let blocks: [(&str, u32, &'static [Register]); 9] = [
+        ("rcc", pac::RCC::ptr() as u32, &RCC),
+        ("gpioa", pac::GPIOA::ptr() as u32, &GPIO),
+        ("gpiob", pac::GPIOB::ptr() as u32, &GPIO),
+        ("gpioc", pac::GPIOC::ptr() as u32, &GPIO),
+        ("gpiod", pac::GPIOD::ptr() as u32, &GPIO),
+        ("gpioe", pac::GPIOE::ptr() as u32, &GPIO),
+        ("gpiof", pac::GPIOF::ptr() as u32, &GPIO),
+        ("usart1", pac::USART1::ptr() as u32, &USART),
+        ("i2c1", pac::I2C1::ptr() as u32, &I2C),
+    ];
+    blocks.into_iter().find(|(block, _, _)| block.eq_ignore_ascii_case(name))
+}
+
+// Odczyt słowa spod adresu sprawdzonego w `REGIONS` lub z rejestrów bloku peryferyjnego
+fn read(address: u32) -> u32 {
+    unsafe { ptr::read_volatile(address as *const u32) }
+}
+
+/// `peek <adres>` - odpowiedź `OK peek 0x40021000=0x00000083`
+pub fn peek(serial: &mut Port, address: u32) -> fmt::Result {
+    match region(address) {
+        Some(_) => command::ok(serial, format_args!("peek {:#010x}={:#010x}", address, read(address))),
+        None => command::err(serial, ErrorCode::Rejected, format_args!("unmapped address {:#010x}", address)),
+    }
+}
+
+/// `poke <adres> <wartość>` - zapis słowa do RAM lub rejestru, odpowiedź powtarza zapisaną wartość
+/// (odczyt zwrotny mógłby mieć skutki uboczne, np. kasowanie flag)
+pub fn poke(serial: &mut Port, address: u32, value: u32) -> fmt::Result {
+    match region(address) {
+        Some(r) if r.writable => {
+            unsafe { ptr::write_volatile(address as *mut u32, value) };
+            command::ok(serial, format_args!("poke {:#010x}={:#010x}", address, value))
+        }
+        Some(_) => command::err(serial, ErrorCode::Rejected, format_args!("read-only address {:#010x}", address)),
+        None => command::err(serial, ErrorCode::Rejected, format_args!("unmapped address {:#010x}", address)),
+    }
+}
+
+/// `dump <blok>` - wszystkie odczytywalne rejestry bloku w jednej linii:
+/// `OK dump gpioe moder=0x55550000 otyper=0x00000000 ...`
+pub fn dump(serial: &mut Port, name: &str) -> fmt::Result {
+    match block(name) {
+        Some((name, base, registers)) => {
+            command::ok(serial, format_args!("dump {} {}", name, Registers { base, registers }))
+        }
+        None => command::err(serial, ErrorCode::InvalidArgument, format_args!("unknown block {}", name)),
+    }
+}
+
+struct Registers {
+    base: u32,
+    registers: &'static [Register],
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, offset)) in self.registers.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            write!(f, "{}={:#010x}", name, read(self.base + offset))?;
+        }
+        Ok(())
+    }
+}
+
+fn stack_bottom() -> u32 {
+    ptr::addr_of!(__sheap) as u32
+}
+
+fn stack_top() -> u32 {
+    ptr::addr_of!(_stack_start) as u32
+}
+
+/// Funkcja wypełniająca wolną część stosu wzorem `STACK_PAINT`, wywoływana na początku `main`,
+/// zanim włączone zostaną przerwania. Pozwala `stack` podać największe zużycie stosu.
+#[inline(never)]
+pub fn paint_stack() {
+    let sp = cortex_m::register::msp::read() - PAINT_MARGIN;
+    for address in (stack_bottom()..sp).step_by(4) {
+        unsafe { ptr::write_volatile(address as *mut u32, STACK_PAINT) };
+    }
+}
+
+/// `stack` - wskaźnik stosu, bieżące i największe zużycie oraz rozmiar stosu w bajtach:
+/// `OK stack sp=0x20009f58 used=168 max=1312 size=38912`
+pub fn stack(serial: &mut Port) -> fmt::Result {
+    let sp = cortex_m::register::msp::read();
+    let (bottom, top) = (stack_bottom(), stack_top());
+    // Pierwsze od dna słowo różne od wzoru wyznacza najgłębsze miejsce, do którego sięgnął stos
+    let deepest = (bottom..top).step_by(4).find(|&address| read(address) != STACK_PAINT).unwrap_or(top);
+    command::ok(
+        serial,
+        format_args!("stack sp={:#010x} used={} max={} size={}", sp, top - sp, top - deepest, top - bottom),
+    )
+}
@@ -0,0 +1,46 @@
+// Informacje o oprogramowaniu dla polecenia `info` i wiadomości GetInfo
+use core::fmt;
+use protocol::{Info, ResetCause};
+
+/// Skrót commita z build.rs, z przyrostkiem "-dirty" przy niezatwierdzonych zmianach
+pub const GIT_HASH: &str = env!("GIT_HASH");
+/// Profil budowania: "debug" lub "release"
+pub const BUILD_PROFILE: &str = env!("BUILD_PROFILE");
+
+/// Funkcja zbierająca informacje o oprogramowaniu, czas pracy liczony jest zegarem TIM2
+pub fn info(reset_cause: ResetCause) -> Info {
+    Info {
+        version: [
+            version_part(env!("CARGO_PKG_VERSION_MAJOR")),
+            version_part(env!("CARGO_PKG_VERSION_MINOR")),
+            version_part(env!("CARGO_PKG_VERSION_PATCH")),
+        ],
+        git_hash: GIT_HASH.get(..8).and_then(|hash| u32::from_str_radix(hash, 16).ok()).unwrap_or(0),
+        dirty: GIT_HASH.ends_with("-dirty"),
+        release: BUILD_PROFILE == "release",
+        uptime_ms: clock::now_ms(),
+        reset_cause: reset_cause.id(),
+    }
+}
+
+/// Odpowiedź tekstowa w postaci par klucz=wartość:
+/// `version=0.1.0 git=1a2b3c4d profile=release uptime_ms=5000 reset=power-on`
+pub struct Text(pub ResetCause);
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "version={} git={} profile={} uptime_ms={} reset={}",
+            env!("CARGO_PKG_VERSION"),
+            GIT_HASH,
+            BUILD_PROFILE,
+            clock::now_ms(),
+            self.0
+        )
+    }
+}
+
+fn version_part(part: &str) -> u8 {
+    part.parse().unwrap_or(0)
+}
@@ -0,0 +1,106 @@
+// Obsługa poleceń SCPI (crate `scpi`) dla narzędzi laboratoryjnych
+use core::fmt::Write;
+use leds::{BoundaryError, TemperatureBoundaries};
+use scpi::{Error, Limit, Request, NOT_A_NUMBER, NO_ERROR};
+use stm32f3xx_hal::signature::Uid;
+use usart_1::uprintln;
+
+use crate::info::GIT_HASH;
+use crate::{settings, with_serial, Port, State};
+
+/// Funkcja wykonująca polecenie SCPI. Zapytania dostają odpowiedź z samą wartością,
+/// polecenia nie mają odpowiedzi, a błędy trafiają do kolejki odczytywanej przez `SYST:ERR?`.
+pub fn handle(line: &str, state: &mut State) {
+    let request = match scpi::parse(line) {
+        Ok(request) => request,
+        Err(e) => {
+            state.scpi_errors.push(e);
+            return;
+        }
+    };
+    let now = clock::now_ms();
+    if request.is_write() && state.access.is_locked(now) {
+        state.scpi_errors.push(Error::CommandProtected);
+        return;
+    }
+    state.access.touch(now);
+    if let Err(e) = with_serial(|serial| execute(request, state, serial)).unwrap_or(Ok(())) {
+        state.scpi_errors.push(e);
+    }
+}
+
+fn execute(request: Request, state: &mut State, serial: &mut Port) -> Result<(), Error> {
+    match request {
+        Request::Identify => {
+            let uid = Uid::get();
+            uprintln!(
+                serial,
+                "Mixxy3k,STM32 Thermometer,{}-{}-{:04X}{:04X},{}-{}",
+                uid.lot_number(),
+                uid.wafer_number(),
+                uid.x_bcd(),
+                uid.y_bcd(),
+                env!("CARGO_PKG_VERSION"),
+                GIT_HASH
+            );
+        }
+        // Przywrócenie domyślnych granic i okresu próbkowania, pozostałe ustawienia portu
+        // nie są zmieniane, aby nie zerwać połączenia
+        Request::Reset => {
+            state.tb = TemperatureBoundaries::default();
+            state.interval_ms = config::Config::default().interval_ms;
+            settings::save(state).map_err(|_| Error::DeviceError)?;
+        }
+        Request::ClearStatus => state.scpi_errors.clear(),
+        Request::OperationComplete => {
+            uprintln!(serial, "1");
+        }
+        Request::MeasureTemperature => measurement(serial, state.reading.map(|r| r.temperature))?,
+        Request::MeasureHumidity => measurement(serial, state.reading.map(|r| r.humidity))?,
+        // Przy błędzie granice pozostają bez zmian
+        Request::SetLimit(limit, value) => {
+            let result = match limit {
+                Limit::Cold => state.tb.set_cold(value),
+                Limit::Optimal => state.tb.set_optimal(value),
+                Limit::Hot => state.tb.set_hot(value),
+                Limit::Critical => state.tb.set_critical(value),
+            };
+            result.map_err(boundary_error)?;
+            settings::save(state).map_err(|_| Error::DeviceError)?;
+        }
+        Request::GetLimit(limit) => {
+            uprintln!(serial, "{}", state.tb.values()[limit.index()]);
+        }
+        Request::NextError => {
+            match state.scpi_errors.pop() {
+                Some(e) => uprintln!(serial, "{}", e),
+                None => uprintln!(serial, "{}", NO_ERROR),
+            };
+        }
+    }
+    Ok(())
+}
+
+// Brak pomiaru zwracany jest jako "not a number", aby skrypt odczytujący liczbę nie czekał na odpowiedź
+fn measurement(serial: &mut Port, value: Option<f32>) -> Result<(), Error> {
+    match value {
+        Some(value) => {
+            uprintln!(serial, "{}", value);
+            Ok(())
+        }
+        None => {
+            uprintln!(serial, "{}", NOT_A_NUMBER);
+            Err(Error::DataStale)
+        }
+    }
+}
+
+fn boundary_error(e: BoundaryError) -> Error {
+    match e {
+        BoundaryError::Order(_, _) => Error::SettingsConflict,
+        BoundaryError::OutOfRange(_) => Error::DataOutOfRange,
+        // Pozostałe błędy dotyczą tekstu granic, a nie pojedynczej wartości
+        BoundaryError::MissingField(_) | BoundaryError::TooManyFields | BoundaryError::Parse(_) => Error::DataTypeError,
+    }
+}
+
@@ -4,123 +4,461 @@
 // Importy
 use core::f32;
 use cortex_m::{interrupt::Mutex};
-use core::{cell::RefCell, fmt::Write};
+use core::cell::{Cell, RefCell};
 use embedded_hal::blocking::{delay::*};
 use stm32f3xx_hal::{interrupt};
 use panic_semihosting as _;
 use cortex_m_rt::entry;
-use dht11::{Dht11, Measurement};
+use dht11::Dht11;
+use cortex_m::peripheral::DWT;
+use fault::{FaultCode, Health};
+use modbus::{RtuReceiver, Slave, MAX_ADU};
+use protocol::ResetCause;
+use scpi::ErrorQueue;
+use command::{Access, SerialSettings};
+use stm32f3xx_hal::{prelude::*, rcc::Clocks, serial::config::{Config as SerialConfig, Parity, StopBits}};
 use init::*;
 use leds::*;
 use lcd::*;
+use telemetry::{Format, Policy, Quality, Sample, Streams, Validator};
 use usart_1::*;
 
+mod commands;
+#[cfg(feature = "debug-shell")]
+mod debug;
+mod info;
+mod instrument;
+mod messages;
+mod registers;
+mod reports;
+mod settings;
+
 // Zmienne dostępne w całym programie
-static SERIAL: Mutex<RefCell<Option<SerialPort>>> = Mutex::new(RefCell::new(None));
-static TB: Mutex<RefCell<Option<TemperatureBoundaries>>> = Mutex::new(RefCell::new(None));
+static SERIAL: Mutex<RefCell<Option<Port>>> = Mutex::new(RefCell::new(None));
+// Zegary ustalone w `init`, od nich liczony jest dzielnik prędkości USART1
+static CLOCKS: Mutex<Cell<Option<Clocks>>> = Mutex::new(Cell::new(None));
+// Linie poleceń składane w przerwaniu i obsługiwane w pętli głównej
+static LINES: Mutex<RefCell<LineReader>> = Mutex::new(RefCell::new(LineReader::new()));
+// Powłoka interaktywna (echo, edycja, historia) przed składaniem linii, włączana poleceniem `set shell on`
+static SHELL: Mutex<RefCell<Shell>> = Mutex::new(RefCell::new(Shell::new()));
+// Ramki protokołu binarnego, używane po poleceniu `set protocol binary`
+static FRAMES: Mutex<RefCell<FrameReader>> = Mutex::new(RefCell::new(FrameReader::new()));
+// Ramki Modbus RTU, czas liczony w cyklach procesora (DWT)
+static MODBUS: Mutex<RefCell<RtuReceiver>> = Mutex::new(RefCell::new(RtuReceiver::new(
+    modbus::silence_us(BAUD_RATE) * (SYSCLK_HZ / 1_000_000),
+)));
+
+/// Port szeregowy termometru (USART1)
+pub(crate) type Port = SerialPort<Usart1, TxChannel<Usart1TxDma>>;
+
+/// Pomiar z czujnika: temperatura w °C, wilgotność w %
+#[derive(Clone, Copy)]
+pub(crate) struct Reading {
+    pub temperature: f32,
+    pub humidity: f32,
+}
+
+/// Stan urządzenia odczytywany i zmieniany przez polecenia
+pub(crate) struct State {
+    pub tb: TemperatureBoundaries,
+    pub interval_ms: u32,
+    // Format pomiarów w protokole tekstowym
+    pub format: Format,
+    // Protokół portu szeregowego, zgodny z `SerialPort::protocol`
+    pub protocol: Protocol,
+    // Adres urządzenia (Modbus)
+    pub address: u8,
+    // Bieżące parametry portu szeregowego
+    pub serial: SerialSettings,
+    // Zmiana parametrów portu czekająca na potwierdzenie
+    pub serial_pending: Option<PendingSerial>,
+    // Interaktywna powłoka, zgodna z `Shell::is_enabled`
+    pub shell: bool,
+    // Półdupleksowa magistrala RS-485 z poleceniami adresowanymi `@<adres> <polecenie>`
+    pub rs485: bool,
+    // Zasada wysyłania pomiarów i subskrybowane strumienie
+    pub report: Policy,
+    pub streams: Streams,
+    // Blokada poleceń zapisu PIN-em
+    pub access: Access,
+    // Ostatni poprawny pomiar, None gdy czujnik nie odpowiada
+    pub reading: Option<Reading>,
+    // Przyczyna ostatniego restartu
+    pub reset_cause: ResetCause,
+    // Kolejka błędów SCPI odczytywana przez `SYST:ERR?`
+    pub scpi_errors: ErrorQueue<SCPI_ERRORS>,
+}
+
+/// Parametry portu obowiązujące przed `set serial` i czas ich zmiany
+#[derive(Clone, Copy)]
+pub(crate) struct PendingSerial {
+    pub previous: SerialSettings,
+    pub since_ms: u32,
+}
+
+// Pojemność kolejki błędów SCPI
+const SCPI_ERRORS: usize = 8;
+
+// Czas na polecenie wysłane z nowymi parametrami portu, po nim przywracane są poprzednie
+const SERIAL_CONFIRM_MS: u32 = 10_000;
+
+// Liczba diod paska WS2812
+#[cfg(feature = "rgb-strip")]
+const RGB_STRIP_LEN: usize = 16;
 
 #[entry]
 fn main() -> ! {
+    // Wzór na wolnej części stosu, z którego polecenie `stack` odczytuje największe zużycie
+    #[cfg(feature = "debug-shell")]
+    debug::paint_stack();
+    // Przyczyna restartu odczytywana przed konfiguracją, flagi są potem kasowane
+    let reset_cause = ResetCause::from_rcc_csr(take_reset_flags());
     // Wywołanie funkcji konfigurującej mikrokontroler
-    let (usart, 
-        led_array,
+    let Board {
+        mut usart,
+        usart_dma,
+        leds: led_array,
         mut i2c,
         mut delay,
-        dht_pin) = init();
+        dht_pin,
+        #[cfg(feature = "rgb-strip")]
+        rgb_spi,
+        clocks,
+    } = init();
 	
     // Utworzenie komponentów
 	let mut dht = Dht11::new(dht_pin);
-    let mut serial = SerialPort::new(usart);
-	let mut leds = LedArray::new(led_array);
+    // Nadawanie przez DMA z podwójnego bufora, procesor nie obsługuje każdego wysyłanego znaku
+    let dma_buffers = cortex_m::singleton!(: DmaBuffers = [[0; DMA_BUFFER]; 2]).unwrap();
+    let usart_dma = TxChannel::new(&mut usart, usart_dma);
+    let mut serial = SerialPort::new(usart).with_dma(usart_dma, dma_buffers);
+	// LED 7 (PE8) zarezerwowana dla heartbeat, usunięcie `heartbeat_led` zwalnia ją dla stref
+	let mut leds = LedArray::new(led_array).heartbeat_led::<7>();
+    // Pasek WS2812 przejmuje pokazywanie temperatury, diody na płytce sygnalizują tylko błędy
+    #[cfg(feature = "rgb-strip")]
+    let mut strip = RgbStrip::<_, RGB_STRIP_LEN>::new(ws2812::Ws2812::new(rgb_spi)).brightness(64);
+    // Brak wyświetlacza nie zatrzymuje programu, błąd sygnalizują diody LED
     let mut lcd = Lcd::new(&mut i2c)
         .address(0x3f)
         .cursor_on(false) 
         .rows(2)
-        .init(&mut delay).unwrap();
+        .init(&mut delay)
+        .map_err(|_| fault::report(FaultCode::LcdNotFound))
+        .ok();
     
+    // Stan zmieniany jest tylko przez polecenia obsługiwane w pętli głównej, ustawienia odczytywane są z flash
+    let mut state = settings::load();
+    state.reset_cause = reset_cause;
+
+    cortex_m::interrupt::free(|cs| CLOCKS.borrow(cs).set(Some(clocks)));
+    apply_serial(&mut serial, state.serial);
+    set_rs485(&mut serial, state.rs485);
 	serial.enable_interrupt();
+    serial.set_protocol(state.protocol);
+    if state.protocol == Protocol::Text && !state.rs485 {
+        telemetry::write_header(&mut serial, state.format).ok();
+    }
     cortex_m::interrupt::free(|cs| {
+        SHELL.borrow(cs).borrow_mut().set_enabled(state.shell && !state.rs485);
         SERIAL.borrow(cs).replace(Some(serial));
-        TB.borrow(cs).replace(Some(TemperatureBoundaries::new(0.0, 25.0, 30.0, 35.0)));
     }); 
+    prompt();
 	
-	lcd.clear(&mut delay).ok();
+	if let Some(lcd) = lcd.as_mut() {
+        lcd.clear(&mut delay).ok();
+    }
+    // Kod błędu pokazany w poprzedniej iteracji, przy kilku błędach pokazywane są kolejno
+    let mut shown_fault: Option<FaultCode> = None;
+    // Ocena jakości pomiarów i telemetria wysyłana według zasady raportowania i subskrypcji
+    let mut validator = Validator::new();
+    let mut reports = reports::Reports::new();
     loop {
-        // Wykonanie pomiaru
-		let measurement = dht.perform_measurement(&mut delay)
-        .unwrap_or_else(|_| {
-            Measurement { temperature: 2555, humidity: 2555 }
-        });
+        let loop_start = clock::now_ms();
+        // Wykonanie pomiaru i zgłoszenie ewentualnego błędu czujnika
+		let sample = match dht.perform_measurement(&mut delay) {
+            Ok(measurement) => {
+                fault::clear(FaultCode::SensorTimeout);
+                fault::clear(FaultCode::Checksum);
+                // Konwersja wartości z czujnika na dane
+                Sample::Measured(telemetry::Reading {
+                    temperature: (measurement.temperature as f32) / 10.0,
+                    humidity: (measurement.humidity as f32) / 10.0,
+                })
+            }
+            Err(dht11::Error::CrcMismatch) => {
+                fault::report(FaultCode::Checksum);
+                Sample::Checksum
+            }
+            Err(_) => {
+                fault::report(FaultCode::SensorTimeout);
+                Sample::Timeout
+            }
+        };
 
-        // Konwersja wartości z czujnika na dane
-		let temp_f32 = (measurement.temperature as f32) / 10.0;
-		let hum_f32 = (measurement.humidity as f32) / 10.0;
+        // Wartości spoza zakresu czujnika są odrzucane, stan zawiera tylko pomiar z bieżącego okresu
+        let (value, quality) = validator.check(sample);
+        state.reading = match (value, quality) {
+            (Some(r), Quality::Ok) => Some(Reading { temperature: r.temperature, humidity: r.humidity }),
+            _ => None,
+        };
+        // Diody pokazują także ostatni poprawny pomiar (`Quality::Stale`), tak jak rekord telemetrii
+        let shown_temperature = value.map(|r| r.temperature);
+        let tb = state.tb;
 
         // Jeśli pomiar jest prawidłowy na LCD pojawiają się wyniki z pomiaru
         // Jeśli pomiar jest nieprawidłowy na LCD pojawiają się informacje o błędzie
-        if temp_f32 != 255.5 {
-            lcd.send_temp(&mut delay, temp_f32, hum_f32);
+        if let Some(lcd) = lcd.as_mut() {
+            if let Some(reading) = state.reading {
+                lcd.send_temp(&mut delay, reading.temperature, reading.humidity);
+            } else {
+                lcd.clear(&mut delay).ok();
+                lcd.write_str(&mut delay, "Connect DHT11!").ok();
+            }
+        }
+
+        // Wysłanie pomiaru i zdarzeń do komputera (zapis do bufora TX) w wybranym formacie
+        reports.publish(&state, value, quality);
+
+        // Zapalenie diod LED poza sekcją krytyczną, animacje nie blokują przerwań
+        // Gdy są aktywne błędy dioda miga kodem błędu zamiast pokazywać strefę temperatury
+        let faults = fault::active();
+        shown_fault = faults.next_after(shown_fault);
+        if let Some(code) = shown_fault {
+            #[cfg(feature = "rgb-strip")]
+            strip.set_all(Rgb::OFF).ok();
+            leds.blink_code(&mut delay, code.blinks()).ok();
+        } else if let Some(temperature) = shown_temperature {
+            #[cfg(feature = "rgb-strip")]
+            strip.set_gauge(&tb, temperature).ok();
+            #[cfg(not(feature = "rgb-strip"))]
+            leds.set_from_tb(&mut delay, &tb, temperature).ok();
         } else {
-            lcd.clear(&mut delay).ok();
-            lcd.write_str(&mut delay, "Connect DHT11!").ok();
+            // Odrzucona wartość (`Quality::Filtered`) nie zgłasza błędu, strefa nie jest wtedy pokazywana
+            #[cfg(feature = "rgb-strip")]
+            strip.set_all(Rgb::OFF).ok();
+            #[cfg(not(feature = "rgb-strip"))]
+            leds.set_all(false).ok();
         }
 
-        // Wysłanie wartości z pomiaru do komputera oraz zapalenie diod LED
-		cortex_m::interrupt::free(|cs| {
-			if let Some(ref mut serial) = SERIAL.borrow(cs).borrow_mut().as_mut() {
-                serial.enable_interrupt();
-				uprintln!(serial, "Temp: {}, Hum: {}", temp_f32, hum_f32);
-			}
-            if let Some(ref mut tb) = TB.borrow(cs).borrow_mut().as_mut() {
-                leds.set_from_tb(&mut delay, tb, temp_f32).ok();
-            }
-		}); 
+        // Heartbeat: jeden błysk gdy wszystko działa, dwa przy pracy ograniczonej, trzy przy błędzie pomiaru
+        let pulses = match faults.health() {
+            Health::Ok => 1,
+            Health::Degraded => 2,
+            Health::Error => 3,
+        };
+        leds.heartbeat(&mut delay, pulses).ok();
 
         // https://www.mouser.com/datasheet/2/758/DHT11-Technical-Data-Sheet-Translated-Version-1143054.pdf
         // Strona 8: Note: Sampling period at intervals should be no less than 1 second.
-        // Ponieważ polecenia powyżej trwają ~250ms mikrokontroler zostaje uspany na 800ms,
-		delay.delay_ms(800u16);
+        // Do końca okresu próbkowania (co najmniej 1 s) obsługiwane są polecenia
+        while clock::elapsed_ms(loop_start) < state.interval_ms {
+            serve_commands(&mut state);
+            delay.delay_ms(COMMAND_POLL_MS);
+        }
     }
 }
 
+// Odstęp między sprawdzeniami, czy przyszły nowe polecenia
+const COMMAND_POLL_MS: u16 = 50;
+
+/// Funkcja obsługująca wszystkie kompletne linie poleceń i ramki odebrane przez przerwanie
+fn serve_commands(state: &mut State) {
+    // Brak polecenia z nowymi parametrami portu oznacza, że komputer ich nie używa
+    if let Some(pending) = state.serial_pending {
+        if clock::elapsed_ms(pending.since_ms) >= SERIAL_CONFIRM_MS {
+            state.serial = pending.previous;
+            state.serial_pending = None;
+            wait_tx_idle();
+            with_serial(|serial| apply_serial(serial, pending.previous));
+        }
+    }
+    loop {
+        let adu = cortex_m::interrupt::free(|cs| {
+            let mut rtu = MODBUS.borrow(cs).borrow_mut();
+            rtu.poll(DWT::cycle_count());
+            rtu.next_frame()
+        });
+        match adu {
+            Some(adu) => serve_modbus(&adu, state),
+            None => break,
+        }
+    }
+    // Ramka jest kopiowana tak jak linia poniżej
+    while let Some(mut frame) = cortex_m::interrupt::free(|cs| FRAMES.borrow(cs).borrow_mut().next_frame()) {
+        messages::handle_frame(&mut frame, state);
+    }
+    loop {
+        // Linia jest kopiowana, aby obsługa polecenia odbywała się poza sekcją krytyczną
+        let line = cortex_m::interrupt::free(|cs| {
+            let mut lines = LINES.borrow(cs).borrow_mut();
+            lines.poll(clock::now_ms());
+            lines.next_line()
+        });
+        match line {
+            Some(Ok(line)) => commands::handle_line(&line, state),
+            // Na magistrali RS-485 błędne linie pomijane są bez odpowiedzi
+            Some(Err(_)) if state.rs485 => {}
+            Some(Err(e)) => {
+                with_serial(|serial| {
+                    command::err(serial, command::ErrorCode::BadLine, format_args!("{}", e)).ok();
+                });
+            }
+            None => break,
+        }
+        prompt();
+    }
+}
+
+// Odpowiedź na ramkę Modbus, zmiana protokołu rejestrem następuje po wysłaniu odpowiedzi
+fn serve_modbus(adu: &[u8], state: &mut State) {
+    let slave = Slave::new(state.address);
+    let mut registers = registers::DeviceRegisters { state, protocol: None };
+    let mut response = [0u8; MAX_ADU];
+    let reply = slave.handle(adu, &mut registers, &mut response);
+    let protocol = registers.protocol;
+    if reply.is_some() {
+        confirm_serial(state);
+    }
+    with_serial(|serial| {
+        if let Some(reply) = reply {
+            serial.write_bytes(reply).ok();
+        }
+        if let Some(protocol) = protocol {
+            set_protocol(serial, protocol);
+        }
+    });
+}
+
+/// Funkcja zmieniająca parametry portu po wysłaniu zawartości bufora TX,
+/// razem z nimi zmienia się czas ciszy rozdzielający ramki Modbus.
+/// Wywoływana po `wait_tx_idle`, `flush` czeka wtedy najwyżej na echo powłoki.
+/// Linie odebrane z poprzednimi parametrami są odrzucane, aby nie potwierdziły nowych (`confirm_serial`).
+pub(crate) fn apply_serial(serial: &mut Port, settings: SerialSettings) {
+    serial.flush();
+    let config = SerialConfig::default()
+        .baudrate(settings.baud.Bd())
+        .parity(match settings.parity {
+            command::Parity::None => Parity::None,
+            command::Parity::Even => Parity::Even,
+            command::Parity::Odd => Parity::Odd,
+        })
+        .stopbits(match settings.stop_bits {
+            command::StopBits::One => StopBits::Stop1,
+            command::StopBits::Two => StopBits::Stop2,
+        });
+    // Zegary zapisywane są w `main` przed pierwszym wywołaniem
+    if let Some(clocks) = cortex_m::interrupt::free(|cs| CLOCKS.borrow(cs).get()) {
+        configure_usart1(serial.interface(), &clocks, config);
+    }
+    cortex_m::interrupt::free(|cs| {
+        MODBUS.borrow(cs).borrow_mut().set_silence(modbus::silence_us(settings.baud) * (SYSCLK_HZ / 1_000_000));
+        LINES.borrow(cs).borrow_mut().clear();
+    });
+}
+
+/// Funkcja zapisująca parametry portu ustawione poleceniem `set serial`, wywoływana
+/// po odebraniu poprawnego polecenia, czyli gdy komputer używa już nowych parametrów
+pub(crate) fn confirm_serial(state: &mut State) {
+    if state.serial_pending.take().is_some() {
+        settings::save(state).ok();
+    }
+}
+
+/// Funkcja zmieniająca protokół portu. Niedokończone linie i ramki są odrzucane, aby bajty
+/// odebrane wcześniej nie zostały doklejone do pierwszej linii lub ramki po powrocie do protokołu.
+pub(crate) fn set_protocol(serial: &mut Port, protocol: Protocol) {
+    serial.set_protocol(protocol);
+    cortex_m::interrupt::free(|cs| {
+        LINES.borrow(cs).borrow_mut().reset();
+        FRAMES.borrow(cs).borrow_mut().reset();
+        MODBUS.borrow(cs).borrow_mut().reset();
+    });
+}
+
+/// Funkcja włączająca lub wyłączająca sprzętowe sterowanie nadajnikiem RS-485 razem z pinem DE
+pub(crate) fn set_rs485(serial: &mut Port, enabled: bool) {
+    configure_de_pin(enabled);
+    serial.interface().set_driver_enable(enabled);
+}
+
+/// Funkcja czekająca z włączonymi przerwaniami, aż port wyśle zawartość buforów TX.
+/// `flush` w sekcji krytycznej blokowałby przerwania przez cały czas nadawania
+/// (przy 1200 Bd do kilku sekund), więc przed zmianą parametrów portu czeka się tutaj.
+pub(crate) fn wait_tx_idle() {
+    while with_serial(|serial| serial.tx_idle()) == Some(false) {}
+}
+
+/// Funkcja wysyłająca tekst dłuższy niż bufory TX. Kolejne części zapisywane są w osobnych
+/// sekcjach krytycznych, a między nimi przerwania DMA i TXE opróżniają bufory.
+pub(crate) fn write_long(text: &str) {
+    let mut rest = text.as_bytes();
+    while let Some(n) = with_serial(|serial| serial.write_some(rest)) {
+        rest = &rest[n..];
+        if rest.is_empty() {
+            break;
+        }
+    }
+}
+
+/// Funkcja wysyłająca znak zachęty, gdy powłoka jest włączona, a port używa protokołu tekstowego
+pub(crate) fn prompt() {
+    with_serial(|serial| {
+        if serial.protocol() == Protocol::Text {
+            cortex_m::interrupt::free(|cs| SHELL.borrow(cs).borrow().prompt(serial).ok());
+        }
+    });
+}
+
+/// Funkcja włączająca lub wyłączająca powłokę interaktywną
+pub(crate) fn set_shell(enabled: bool) {
+    cortex_m::interrupt::free(|cs| SHELL.borrow(cs).borrow_mut().set_enabled(enabled));
+}
+
+/// Funkcja wykonująca `f` na porcie szeregowym w sekcji krytycznej
+/// # Examples
+/// ```
+/// with_serial(|serial| {
+///     uprintln!(serial, "2+2 = {}", 2+2);
+/// });
+/// ```
+pub(crate) fn with_serial<R>(f: impl FnOnce(&mut Port) -> R) -> Option<R> {
+    cortex_m::interrupt::free(|cs| SERIAL.borrow(cs).borrow_mut().as_mut().map(f))
+}
+
+#[interrupt]
+fn DMA1_CH4() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(ref mut serial) = SERIAL.borrow(cs).borrow_mut().as_mut() {
+            // Wysłanie bufora wypełnionego w trakcie poprzedniego przesyłania
+            serial.on_dma_interrupt();
+        }
+    });
+}
+
 #[interrupt]
 fn USART1_EXTI25() {
     cortex_m::interrupt::free(|cs| {
         // Jesli istnieje komponent SerialPort nalezy go odblokować
         if let Some(ref mut serial) = SERIAL.borrow(cs).borrow_mut().as_mut() {
-            // Jeśli są dane do odczytu
-            if serial.ReciveDataRegisterNotEmpty() {
-                // Reset flagi informującej o odczycie
-                serial.resetReciveDataRegisterNotEmpty();
-                // Odczytanie kodu polecenia
-                let command = serial.read(); 
-
-                // Jeśli polecenie 's' to program czeka na wpisanie nowych wartości granic temperatury
-                if command == 's' {
-                    let val = serial.block_readln();
-                    uprintln!(serial, "Got {}: {}", command, val);
-                    let val: &str = &val[..];
-                    if let Some(ref mut tb) = TB.borrow(cs).borrow_mut().as_mut() {
-                        tb.set_from_string(val).ok();
-                        uprintln!(serial, "Temp values changed: Low: {}, Optimal: {},  High: {}, Critical: {}", tb.cold, tb.optimal, tb.hot, tb.critical);
-                    }
-                }
-
-                // Jeśli polecenie 'g' to program wysyła wartości granic temperatury
-                else if command == 'g' {
-                    if let Some(ref mut tb) = TB.borrow(cs).borrow_mut().as_mut() {
-                        uprintln!(serial, "Cold: {}, Optimal: {},  High: {}, Critical: {}", tb.cold, tb.optimal, tb.hot, tb.critical);
-                    }
-                }
-                // Transmisja zakończona
-                serial.setTransmitionComplete();
+            // Przeniesienie odebranego znaku do bufora RX i wysłanie kolejnego znaku z bufora TX
+            serial.on_interrupt();
+            // Przekazanie odebranych znaków do składania linii lub ramek, polecenia obsługuje pętla główna
+            let mut shell = SHELL.borrow(cs).borrow_mut();
+            let mut lines = LINES.borrow(cs).borrow_mut();
+            let mut frames = FRAMES.borrow(cs).borrow_mut();
+            let mut rtu = MODBUS.borrow(cs).borrow_mut();
+            // Linia z brakującym lub przekłamanym znakiem jest odrzucana w całości. Ramki binarne
+            // i Modbus chroni CRC, a w powłoce użytkownik widzi echo i może poprawić linię.
+            if serial.take_rx_error() && serial.protocol() == Protocol::Text && !shell.is_enabled() {
+                lines.discard();
             }
-            // Jeśli transmisja jest zakończona to flagi odpowiedzialne za przerwanie są czyszczone
-            if serial.TransmitionComplete() {
-                serial.clear_interrupt();
+            while let Some(byte) = serial.read_byte() {
+                match serial.protocol() {
+                    Protocol::Text => shell.push(byte, clock::now_ms(), &mut lines, serial),
+                    Protocol::Binary => frames.push(byte),
+                    Protocol::Modbus => rtu.push(byte, DWT::cycle_count()),
+                }
             }
-            return;
         }
     });
-    return;
 }
\ No newline at end of file
@@ -0,0 +1,88 @@
+// Obsługa wiadomości protokołu binarnego odebranych przez port szeregowy
+use command::ErrorCode;
+use leds::TemperatureBoundaries;
+use protocol::{Ack, Bounds, FrameError, Message, Status};
+use usart_1::{Frame, Protocol};
+
+use crate::commands::boundary_error_code;
+use crate::info;
+use crate::{confirm_serial, set_protocol, settings, with_serial, Port, State};
+
+/// Funkcja dekodująca ramkę i wysyłająca odpowiedź. Uszkodzona ramka potwierdzana jest
+/// wiadomością Ack z typem 0 i kodem BadLine, ramka z nieznanym typem - kodem UnknownCommand.
+pub fn handle_frame(frame: &mut Frame, state: &mut State) {
+    let message = match protocol::decode_frame(frame) {
+        Ok(message) => message,
+        Err(FrameError::UnknownType(id)) => {
+            with_serial(|serial| ack(serial, id, Some(ErrorCode::UnknownCommand)).ok());
+            return;
+        }
+        Err(_) => {
+            with_serial(|serial| ack(serial, 0, Some(ErrorCode::BadLine)).ok());
+            return;
+        }
+    };
+    confirm_serial(state);
+    with_serial(|serial| execute(message, state, serial).ok());
+}
+
+fn execute(message: Message, state: &mut State, serial: &mut Port) -> core::fmt::Result {
+    let request = message.message_type() as u8;
+    match message {
+        Message::GetReading => match state.reading {
+            Some(reading) => serial.send_message(&Message::Reading(protocol::Reading {
+                temperature: reading.temperature,
+                humidity: reading.humidity,
+            })),
+            None => ack(serial, request, Some(ErrorCode::Unavailable)),
+        },
+        Message::GetBounds => serial.send_message(&Message::Bounds(bounds(&state.tb))),
+        // Przy błędzie granice pozostają bez zmian
+        Message::SetBounds(_) if state.access.is_locked(clock::now_ms()) => ack(serial, request, Some(ErrorCode::Locked)),
+        Message::SetBounds(b) => match TemperatureBoundaries::new(b.cold, b.optimal, b.hot, b.critical) {
+            Ok(tb) => {
+                state.tb = tb;
+                let error = settings::save(state).err().map(|_| ErrorCode::Unavailable);
+                ack(serial, request, error)
+            }
+            Err(e) => ack(serial, request, Some(boundary_error_code(e))),
+        },
+        Message::GetStatus => serial.send_message(&Message::Status(Status {
+            faults: fault::active().bits(),
+            uptime_ms: clock::now_ms(),
+        })),
+        Message::GetInfo => serial.send_message(&Message::Info(info::info(state.reset_cause))),
+        // Potwierdzenie wysyłane jest jeszcze jako ramka
+        Message::TextMode => {
+            state.protocol = Protocol::Text;
+            let error = settings::save(state).err().map(|_| ErrorCode::Unavailable);
+            ack(serial, request, error)?;
+            set_protocol(serial, Protocol::Text);
+            Ok(())
+        }
+        // Odpowiedzi termometru nie są poprawnymi żądaniami
+        Message::Reading(_)
+        | Message::Bounds(_)
+        | Message::Status(_)
+        | Message::Info(_)
+        | Message::Record(_)
+        | Message::Ack(_) => {
+            ack(serial, request, Some(ErrorCode::UnknownCommand))
+        }
+    }
+}
+
+// Granice temperatur w postaci wiadomości protokołu
+fn bounds(tb: &TemperatureBoundaries) -> Bounds {
+    Bounds {
+        cold: tb.cold(),
+        optimal: tb.optimal(),
+        hot: tb.hot(),
+        critical: tb.critical(),
+    }
+}
+
+fn ack(serial: &mut Port, request: u8, error: Option<ErrorCode>) -> core::fmt::Result {
+    let error = error.map_or(0, |code| code.code());
+    serial.send_message(&Message::Ack(Ack { request, error }))
+}
@@ -0,0 +1,83 @@
+// Rejestry Modbus RTU termometru
+use command::{MAX_INTERVAL_MS, MIN_INTERVAL_MS};
+use leds::TemperatureBoundaries;
+use modbus::{from_tenths, to_tenths, Exception, Registers, NO_VALUE};
+use usart_1::Protocol;
+
+use crate::{settings, State};
+
+// Rejestry wejściowe
+const TEMPERATURE: u16 = 0;
+const HUMIDITY: u16 = 1;
+const STATUS: u16 = 2;
+// Rejestry zapisu: granice 0..=3, okres próbkowania w s, protokół
+const BOUNDS: u16 = 0;
+const INTERVAL: u16 = 4;
+const PROTOCOL: u16 = 5;
+
+/// Mapa rejestrów na stanie urządzenia. Zmiana protokołu jest zapamiętywana w `protocol`
+/// i stosowana dopiero po wysłaniu odpowiedzi.
+pub struct DeviceRegisters<'a> {
+    pub state: &'a mut State,
+    pub protocol: Option<Protocol>,
+}
+
+impl Registers for DeviceRegisters<'_> {
+    fn read_input(&mut self, address: u16) -> Result<u16, Exception> {
+        let reading = self.state.reading;
+        match address {
+            TEMPERATURE => Ok(reading.map_or(NO_VALUE, |r| to_tenths(r.temperature))),
+            HUMIDITY => Ok(reading.map_or(NO_VALUE, |r| to_tenths(r.humidity))),
+            STATUS => Ok(fault::active().bits() as u16),
+            _ => Err(Exception::IllegalDataAddress),
+        }
+    }
+
+    fn read_holding(&mut self, address: u16) -> Result<u16, Exception> {
+        match address {
+            BOUNDS..=3 => Ok(to_tenths(self.state.tb.values()[(address - BOUNDS) as usize])),
+            INTERVAL => Ok((self.state.interval_ms / 1000) as u16),
+            PROTOCOL => Ok(settings::protocol_id(self.state.protocol) as u16),
+            _ => Err(Exception::IllegalDataAddress),
+        }
+    }
+
+    fn write_holding(&mut self, start: u16, values: &[u16]) -> Result<(), Exception> {
+        // Przy blokadzie PIN-em dozwolony jest tylko powrót do protokołu tekstowego, w którym działa `unlock`
+        let to_text = start == PROTOCOL && values == [settings::protocol_id(Protocol::Text) as u16];
+        if !to_text && self.state.access.is_locked(clock::now_ms()) {
+            return Err(Exception::IllegalFunction);
+        }
+        // Nowe wartości sprawdzane są razem i stosowane w całości albo wcale
+        let mut bounds = self.state.tb.values();
+        let mut interval_ms = self.state.interval_ms;
+        let mut protocol = self.state.protocol;
+        for (address, &value) in (start..).zip(values) {
+            match address {
+                BOUNDS..=3 => bounds[(address - BOUNDS) as usize] = from_tenths(value),
+                INTERVAL => {
+                    interval_ms = value as u32 * 1000;
+                    if !(MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&interval_ms) {
+                        return Err(Exception::IllegalDataValue);
+                    }
+                }
+                PROTOCOL => protocol = settings::protocol_from_id(value as u8).ok_or(Exception::IllegalDataValue)?,
+                _ => return Err(Exception::IllegalDataAddress),
+            }
+        }
+        // Ramki binarne nie mają adresu, na magistrali RS-485 odpowiadałyby wszystkie urządzenia
+        if protocol == Protocol::Binary && self.state.rs485 {
+            return Err(Exception::IllegalDataValue);
+        }
+        let [cold, optimal, hot, critical] = bounds;
+        let tb = TemperatureBoundaries::new(cold, optimal, hot, critical).map_err(|_| Exception::IllegalDataValue)?;
+
+        self.state.tb = tb;
+        self.state.interval_ms = interval_ms;
+        if protocol != self.state.protocol {
+            self.state.protocol = protocol;
+            self.protocol = Some(protocol);
+        }
+        settings::save(self.state).map_err(|_| Exception::SlaveDeviceFailure)
+    }
+}
@@ -0,0 +1,97 @@
+// Telemetria wysyłana bez zapytania: pomiary według zasady raportowania (`set report`)
+// oraz zdarzenia zmiany strefy i błędów dla subskrybentów (`subscribe`)
+use fault::{FaultCode, Faults};
+use leds::Zone;
+use protocol::Message;
+use telemetry::{Event, EventRecord, Quality, Record, Reporter, Stream};
+use usart_1::Protocol;
+
+use crate::{with_serial, Port, State, SHELL};
+
+/// Stan telemetrii w pętli głównej: ostatnio wysłany pomiar, strefa i błędy
+pub struct Reports {
+    reporter: Reporter,
+    // Numer kolejny wysłanego rekordu lub zdarzenia, przerwa w numeracji oznacza zgubiony rekord
+    seq: u32,
+    zone: Option<Zone>,
+    faults: Faults,
+}
+
+impl Reports {
+    pub fn new() -> Self {
+        Reports { reporter: Reporter::new(), seq: 0, zone: None, faults: Faults::default() }
+    }
+
+    /// Funkcja wysyłająca pomiar z bieżącego okresu próbkowania (wartość i jakość z `Validator`),
+    /// jeśli wymaga tego zasada raportowania, oraz zdarzenia od poprzedniego wywołania. W trybie binarnym
+    /// wysyłane są tylko pomiary, a w trybie Modbus i na magistrali RS-485 urządzenie odpowiada tylko na zapytania.
+    pub fn publish(&mut self, state: &State, reading: Option<telemetry::Reading>, quality: Quality) {
+        let now = clock::now_ms();
+        // Przy błędzie czujnika strefa pozostaje bez zmian
+        let zone = state.reading.map(|r| state.tb.zone(r.temperature));
+        let zone_changed = zone.is_some() && zone != self.zone;
+        if zone_changed {
+            self.zone = zone;
+        }
+        let previous = self.faults;
+        self.faults = fault::active();
+        let faults = self.faults;
+
+        let report =
+            state.streams.contains(Stream::Readings) && self.reporter.check(&state.report, reading, quality, now);
+        let zone_event = self.zone.filter(|_| zone_changed && state.streams.contains(Stream::Zones));
+        let fault_events = faults != previous && state.streams.contains(Stream::Errors);
+
+        // Numery otrzymują tylko rzeczywiście wysłane rekordy i zdarzenia
+        let seq = &mut self.seq;
+        with_serial(|serial| match serial.protocol() {
+            _ if state.rs485 => {}
+            // Rekordy zastępują wiersz z zachętą, który jest potem przywracany razem z wpisywanym poleceniem
+            Protocol::Text if report || zone_event.is_some() || fault_events => cortex_m::interrupt::free(|cs| {
+                let shell = SHELL.borrow(cs).borrow();
+                shell.clear_line(serial).ok();
+                if let Some(zone) = zone_event {
+                    let event = EventRecord { seq: next(seq), uptime_ms: now, event: Event::Zone(zone.name()) };
+                    telemetry::write_event(serial, state.format, &event).ok();
+                }
+                if fault_events {
+                    write_fault_events(serial, state, seq, now, previous, faults);
+                }
+                if report {
+                    let record = Record { seq: next(seq), uptime_ms: now, reading, quality };
+                    telemetry::write_record(serial, state.format, &record).ok();
+                }
+                shell.prompt(serial).ok();
+            }),
+            // Ramka Record z tą samą wartością i jakością co rekord tekstowy
+            Protocol::Binary if report => {
+                let record = protocol::Record {
+                    seq: next(seq),
+                    uptime_ms: now,
+                    reading: reading.map(|r| protocol::Reading { temperature: r.temperature, humidity: r.humidity }),
+                    quality: quality.id(),
+                };
+                serial.send_message(&Message::Record(record)).ok();
+            }
+            _ => {}
+        });
+    }
+}
+
+// Kolejny numer rekordu
+fn next(seq: &mut u32) -> u32 {
+    let current = *seq;
+    *seq = seq.wrapping_add(1);
+    current
+}
+
+// Zdarzenia dla błędów zgłoszonych lub usuniętych od poprzedniego pomiaru
+fn write_fault_events(serial: &mut Port, state: &State, seq: &mut u32, now: u32, previous: Faults, faults: Faults) {
+    for code in FaultCode::ALL {
+        let active = faults.contains(code);
+        if active != previous.contains(code) {
+            let event = Event::Fault { code: code.blinks(), name: code.name(), active };
+            telemetry::write_event(serial, state.format, &EventRecord { seq: next(seq), uptime_ms: now, event }).ok();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// Ustawienia zachowywane w pamięci flash (crate `config`)
+use command::{Parity, SerialSettings, StopBits, BAUD_RATES, MAX_ADDRESS, MAX_INTERVAL_MS, MIN_ADDRESS, MIN_INTERVAL_MS};
+use command::{Access, PinHash};
+use config::{Config, ConfigError, FlashError, PinRecord};
+use fault::FaultCode;
+use leds::TemperatureBoundaries;
+use protocol::ResetCause;
+use scpi::ErrorQueue;
+use usart_1::Protocol;
+
+use crate::State;
+
+/// Funkcja tworząca stan z zapisanej konfiguracji.
+/// Uszkodzona konfiguracja lub niepoprawne wartości zastępowane są domyślnymi i zgłaszane jako błąd 5.
+pub fn load() -> State {
+    let config = match config::load() {
+        Ok(config) => config,
+        Err(ConfigError::Empty) => Config::default(),
+        Err(_) => {
+            fault::report(FaultCode::ConfigCorrupted);
+            Config::default()
+        }
+    };
+    let [cold, optimal, hot, critical] = config.bounds;
+    let tb = TemperatureBoundaries::new(cold, optimal, hot, critical).unwrap_or_else(|_| {
+        fault::report(FaultCode::ConfigCorrupted);
+        TemperatureBoundaries::default()
+    });
+    let interval_ms = if (MIN_INTERVAL_MS..=MAX_INTERVAL_MS).contains(&config.interval_ms) {
+        config.interval_ms
+    } else {
+        fault::report(FaultCode::ConfigCorrupted);
+        Config::default().interval_ms
+    };
+    let protocol = match protocol_from_id(config.protocol) {
+        // Protokół binarny nie jest dozwolony na magistrali RS-485
+        Some(Protocol::Binary) if config.rs485 => None,
+        protocol => protocol,
+    }
+    .unwrap_or_else(|| {
+        fault::report(FaultCode::ConfigCorrupted);
+        Protocol::Text
+    });
+    let address = if (MIN_ADDRESS..=MAX_ADDRESS).contains(&config.address) {
+        config.address
+    } else {
+        fault::report(FaultCode::ConfigCorrupted);
+        Config::default().address
+    };
+    let report = if config.report.is_valid() {
+        config.report
+    } else {
+        fault::report(FaultCode::ConfigCorrupted);
+        Config::default().report
+    };
+    let serial = serial_from_config(&config).unwrap_or_else(|| {
+        fault::report(FaultCode::ConfigCorrupted);
+        SerialSettings::default()
+    });
+    State {
+        tb,
+        interval_ms,
+        format: config.format,
+        protocol,
+        address,
+        serial,
+        serial_pending: None,
+        shell: config.shell,
+        rs485: config.rs485,
+        report,
+        streams: config.streams,
+        access: Access::new(config.pin.map(|pin| PinHash { salt: pin.salt, hash: pin.hash })),
+        reading: None,
+        reset_cause: ResetCause::Unknown,
+        scpi_errors: ErrorQueue::new(),
+    }
+}
+
+/// Funkcja zapisująca ustawienia ze stanu, poprawny zapis usuwa błąd uszkodzonej konfiguracji.
+/// Niepotwierdzone parametry portu nie są zapisywane, zachowywane są poprzednie.
+pub fn save(state: &State) -> Result<(), FlashError> {
+    let serial = state.serial_pending.map_or(state.serial, |pending| pending.previous);
+    config::store(&Config {
+        bounds: state.tb.values(),
+        interval_ms: state.interval_ms,
+        format: state.format,
+        protocol: protocol_id(state.protocol),
+        address: state.address,
+        baud: serial.baud,
+        parity: match serial.parity {
+            Parity::None => 0,
+            Parity::Even => 1,
+            Parity::Odd => 2,
+        },
+        stop_bits: match serial.stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        },
+        shell: state.shell,
+        rs485: state.rs485,
+        report: state.report,
+        streams: state.streams,
+        pin: state.access.pin().map(|pin| PinRecord { salt: pin.salt, hash: pin.hash }),
+    })?;
+    fault::clear(FaultCode::ConfigCorrupted);
+    Ok(())
+}
+
+/// Identyfikator protokołu zapisywany w konfiguracji i rejestrze Modbus
+pub fn protocol_id(protocol: Protocol) -> u8 {
+    match protocol {
+        Protocol::Text => 0,
+        Protocol::Binary => 1,
+        Protocol::Modbus => 2,
+    }
+}
+
+pub fn protocol_from_id(id: u8) -> Option<Protocol> {
+    match id {
+        0 => Some(Protocol::Text),
+        1 => Some(Protocol::Binary),
+        2 => Some(Protocol::Modbus),
+        _ => None,
+    }
+}
+
+// Parametry portu z konfiguracji, None przy nieobsługiwanej wartości
+fn serial_from_config(config: &Config) -> Option<SerialSettings> {
+    if !BAUD_RATES.contains(&config.baud) {
+        return None;
+    }
+    let parity = match config.parity {
+        0 => Parity::None,
+        1 => Parity::Even,
+        2 => Parity::Odd,
+        _ => return None,
+    };
+    let stop_bits = match config.stop_bits {
+        1 => StopBits::One,
+        2 => StopBits::Two,
+        _ => return None,
+    };
+    Some(SerialSettings { baud: config.baud, parity, stop_bits })
+}
@@ -0,0 +1,264 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Formaty pomiarów wysyłanych przez termometr co okres próbkowania.
+//! Rekordy zapisywane są bezpośrednio do `core::fmt::Write` (np. `SerialPort`), bez alokacji.
+
+use core::fmt::{self, Write};
+
+mod policy;
+pub use policy::*;
+mod quality;
+pub use quality::*;
+pub use reporting::*;
+
+/// Nazwa pomiaru w protokole InfluxDB
+pub const INFLUX_MEASUREMENT: &str = "thermometer";
+/// Nazwa pomiaru zdarzeń (strefy, błędy) w protokole InfluxDB
+pub const INFLUX_EVENTS: &str = "thermometer_events";
+
+/// Pomiar z czujnika: temperatura w °C, wilgotność w %
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub temperature: f32,
+    pub humidity: f32,
+}
+
+/// Jeden rekord telemetrii
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Record {
+    /// Numer kolejny rekordu od uruchomienia
+    pub seq: u32,
+    /// Czas od uruchomienia w ms
+    pub uptime_ms: u32,
+    /// None gdy nie ma wartości do wysłania (patrz `quality`)
+    pub reading: Option<Reading>,
+    /// Jakość pomiaru
+    pub quality: Quality,
+}
+
+/// Zdarzenie wysyłane subskrybentom strumieni `zones` i `errors`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// Temperatura przeszła do strefy o podanej nazwie
+    Zone(&'static str),
+    /// Błąd o kodzie `code` został zgłoszony (`active`) lub usunięty
+    Fault { code: u8, name: &'static str, active: bool },
+}
+
+/// Zdarzenie z numerem kolejnym i czasem, numeracja jest wspólna z rekordami pomiarów
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventRecord {
+    /// Numer kolejny od uruchomienia
+    pub seq: u32,
+    /// Czas od uruchomienia w ms
+    pub uptime_ms: u32,
+    pub event: Event,
+}
+
+/// Funkcja wysyłająca nagłówek formatu (tylko CSV), wywoływana po wybraniu formatu i po starcie
+pub fn write_header<W: Write>(w: &mut W, format: Format) -> fmt::Result {
+    match format {
+        Format::Csv => w.write_str("seq,uptime_ms,temperature,humidity,quality\n"),
+        Format::Human | Format::Json | Format::Influx => Ok(()),
+    }
+}
+
+/// Funkcja wysyłająca rekord zakończony znakiem nowej linii. Każdy rekord zawiera numer kolejny,
+/// czas od uruchomienia i jakość pomiaru. Brak wartości zapisywany jest jako `-` (human),
+/// puste pola (CSV), `null` (JSON), a w protokole InfluxDB pola pomiaru są pomijane.
+/// # Examples
+/// ```
+/// use telemetry::{Format, Quality, Reading, Record};
+///
+/// let reading = Some(Reading { temperature: 23.4, humidity: 45.0 });
+/// let record = Record { seq: 1, uptime_ms: 1000, reading, quality: Quality::Ok };
+/// let mut out = String::new();
+/// telemetry::write_record(&mut out, Format::Csv, &record).unwrap();
+/// assert_eq!(out, "1,1000,23.4,45,ok\n");
+/// ```
+pub fn write_record<W: Write>(w: &mut W, format: Format, record: &Record) -> fmt::Result {
+    let Record { seq, uptime_ms, quality, .. } = *record;
+    match (format, record.reading) {
+        (Format::Human, Some(r)) => writeln!(
+            w,
+            "Temp: {}, Hum: {}, Seq: {}, Uptime: {} ms, Quality: {}",
+            r.temperature, r.humidity, seq, uptime_ms, quality
+        ),
+        (Format::Human, None) => writeln!(w, "Temp: -, Hum: -, Seq: {}, Uptime: {} ms, Quality: {}", seq, uptime_ms, quality),
+        (Format::Csv, Some(r)) => writeln!(w, "{},{},{},{},{}", seq, uptime_ms, r.temperature, r.humidity, quality),
+        (Format::Csv, None) => writeln!(w, "{},{},,,{}", seq, uptime_ms, quality),
+        (Format::Json, Some(r)) => writeln!(
+            w,
+            "{{\"seq\":{},\"uptime_ms\":{},\"temperature\":{},\"humidity\":{},\"quality\":\"{}\"}}",
+            seq, uptime_ms, r.temperature, r.humidity, quality
+        ),
+        (Format::Json, None) => writeln!(
+            w,
+            "{{\"seq\":{},\"uptime_ms\":{},\"temperature\":null,\"humidity\":null,\"quality\":\"{}\"}}",
+            seq, uptime_ms, quality
+        ),
+        (Format::Influx, Some(r)) => writeln!(
+            w,
+            "{},quality={} temperature={},humidity={},seq={}i,uptime_ms={}i",
+            INFLUX_MEASUREMENT, quality, r.temperature, r.humidity, seq, uptime_ms
+        ),
+        (Format::Influx, None) => writeln!(
+            w,
+            "{},quality={} seq={}i,uptime_ms={}i",
+            INFLUX_MEASUREMENT, quality, seq, uptime_ms
+        ),
+    }
+}
+
+/// Funkcja wysyłająca zdarzenie zakończone znakiem nowej linii, z numerem kolejnym i czasem jak rekord.
+/// W CSV zdarzenie jest komentarzem `#`, aby nie psuć kolumn pomiarów.
+/// # Examples
+/// ```
+/// use telemetry::{Event, EventRecord, Format};
+///
+/// let event = EventRecord { seq: 9, uptime_ms: 14000, event: Event::Zone("hot") };
+/// let mut out = String::new();
+/// telemetry::write_event(&mut out, Format::Json, &event).unwrap();
+/// assert_eq!(out, "{\"event\":\"zone\",\"seq\":9,\"uptime_ms\":14000,\"zone\":\"hot\"}\n");
+/// ```
+pub fn write_event<W: Write>(w: &mut W, format: Format, record: &EventRecord) -> fmt::Result {
+    let EventRecord { seq, uptime_ms, event } = *record;
+    match (format, event) {
+        (Format::Human, Event::Zone(zone)) => writeln!(w, "Zone: {}, Seq: {}, Uptime: {} ms", zone, seq, uptime_ms),
+        (Format::Human, Event::Fault { code, name, active }) => writeln!(
+            w,
+            "Fault {} {}: {}, Seq: {}, Uptime: {} ms",
+            code, change(active), name, seq, uptime_ms
+        ),
+        (Format::Csv, Event::Zone(zone)) => writeln!(w, "# seq={} uptime_ms={} zone {}", seq, uptime_ms, zone),
+        (Format::Csv, Event::Fault { code, name, active }) => writeln!(
+            w,
+            "# seq={} uptime_ms={} fault {} {}: {}",
+            seq, uptime_ms, code, change(active), name
+        ),
+        (Format::Json, Event::Zone(zone)) => writeln!(
+            w,
+            "{{\"event\":\"zone\",\"seq\":{},\"uptime_ms\":{},\"zone\":\"{}\"}}",
+            seq, uptime_ms, zone
+        ),
+        (Format::Json, Event::Fault { code, name, active }) => writeln!(
+            w,
+            "{{\"event\":\"fault\",\"seq\":{},\"uptime_ms\":{},\"code\":{},\"name\":\"{}\",\"active\":{}}}",
+            seq, uptime_ms, code, name, active
+        ),
+        (Format::Influx, Event::Zone(zone)) => {
+            writeln!(w, "{} zone=\"{}\",seq={}i,uptime_ms={}i", INFLUX_EVENTS, zone, seq, uptime_ms)
+        }
+        (Format::Influx, Event::Fault { code, active, .. }) => writeln!(
+            w,
+            "{} fault={}i,active={},seq={}i,uptime_ms={}i",
+            INFLUX_EVENTS, code, active, seq, uptime_ms
+        ),
+    }
+}
+
+fn change(active: bool) -> &'static str {
+    if active {
+        "raised"
+    } else {
+        "cleared"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use std::string::String;
+
+    const OK: Record = Record {
+        seq: 7,
+        uptime_ms: 12_000,
+        reading: Some(Reading { temperature: 23.4, humidity: 45.0 }),
+        quality: Quality::Ok,
+    };
+    const SENSOR_FAILED: Record = Record { seq: 8, uptime_ms: 13_000, reading: None, quality: Quality::Timeout };
+
+    fn render(format: Format, record: &Record) -> String {
+        let mut out = String::new();
+        write_record(&mut out, format, record).unwrap();
+        out
+    }
+
+    #[test]
+    fn human_records_carry_seq_uptime_and_quality() {
+        assert_eq!(render(Format::Human, &OK), "Temp: 23.4, Hum: 45, Seq: 7, Uptime: 12000 ms, Quality: ok\n");
+        assert_eq!(
+            render(Format::Human, &SENSOR_FAILED),
+            "Temp: -, Hum: -, Seq: 8, Uptime: 13000 ms, Quality: timeout\n"
+        );
+    }
+
+    #[test]
+    fn csv_rows_match_the_header() {
+        let mut out = String::new();
+        write_header(&mut out, Format::Csv).unwrap();
+        assert_eq!(out, "seq,uptime_ms,temperature,humidity,quality\n");
+        assert_eq!(render(Format::Csv, &OK), "7,12000,23.4,45,ok\n");
+        assert_eq!(render(Format::Csv, &SENSOR_FAILED), "8,13000,,,timeout\n");
+    }
+
+    #[test]
+    fn json_lines() {
+        assert_eq!(
+            render(Format::Json, &OK),
+            "{\"seq\":7,\"uptime_ms\":12000,\"temperature\":23.4,\"humidity\":45,\"quality\":\"ok\"}\n"
+        );
+        assert_eq!(
+            render(Format::Json, &SENSOR_FAILED),
+            "{\"seq\":8,\"uptime_ms\":13000,\"temperature\":null,\"humidity\":null,\"quality\":\"timeout\"}\n"
+        );
+    }
+
+    #[test]
+    fn influx_line_protocol() {
+        assert_eq!(
+            render(Format::Influx, &OK),
+            "thermometer,quality=ok temperature=23.4,humidity=45,seq=7i,uptime_ms=12000i\n"
+        );
+        assert_eq!(render(Format::Influx, &SENSOR_FAILED), "thermometer,quality=timeout seq=8i,uptime_ms=13000i\n");
+    }
+
+    #[test]
+    fn events_in_every_format_carry_seq_and_uptime() {
+        let zone = Event::Zone("hot");
+        let fault = Event::Fault { code: 2, name: "sensor timeout", active: true };
+        let render = |format, event| {
+            let mut out = String::new();
+            write_event(&mut out, format, &EventRecord { seq: 9, uptime_ms: 14_000, event }).unwrap();
+            out
+        };
+        assert_eq!(render(Format::Human, zone), "Zone: hot, Seq: 9, Uptime: 14000 ms\n");
+        assert_eq!(render(Format::Human, fault), "Fault 2 raised: sensor timeout, Seq: 9, Uptime: 14000 ms\n");
+        assert_eq!(render(Format::Csv, zone), "# seq=9 uptime_ms=14000 zone hot\n");
+        assert_eq!(
+            render(Format::Csv, Event::Fault { code: 2, name: "sensor timeout", active: false }),
+            "# seq=9 uptime_ms=14000 fault 2 cleared: sensor timeout\n"
+        );
+        assert_eq!(render(Format::Json, zone), "{\"event\":\"zone\",\"seq\":9,\"uptime_ms\":14000,\"zone\":\"hot\"}\n");
+        assert_eq!(
+            render(Format::Json, fault),
+            "{\"event\":\"fault\",\"seq\":9,\"uptime_ms\":14000,\"code\":2,\"name\":\"sensor timeout\",\"active\":true}\n"
+        );
+        assert_eq!(render(Format::Influx, zone), "thermometer_events zone=\"hot\",seq=9i,uptime_ms=14000i\n");
+        assert_eq!(
+            render(Format::Influx, fault),
+            "thermometer_events fault=2i,active=true,seq=9i,uptime_ms=14000i\n"
+        );
+    }
+
+    #[test]
+    fn only_csv_has_a_header() {
+        for format in [Format::Human, Format::Json, Format::Influx] {
+            let mut out = String::new();
+            write_header(&mut out, format).unwrap();
+            assert!(out.is_empty());
+        }
+    }
+}
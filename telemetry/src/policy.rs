@@ -0,0 +1,121 @@
+use crate::{Policy, Quality, Reading};
+
+#[derive(Debug, Clone, Copy)]
+struct Sent {
+    reading: Option<Reading>,
+    quality: Quality,
+    at_ms: u32,
+}
+
+/// Decyzja o wysłaniu pomiaru według zasady raportowania.
+/// Pamięta ostatni wysłany pomiar, z którym porównywane są kolejne.
+/// # Examples
+/// ```
+/// use telemetry::{Policy, Quality, Reading, Reporter};
+///
+/// let policy = Policy::Interval { interval_ms: 5000 };
+/// let reading = Some(Reading { temperature: 23.4, humidity: 45.0 });
+/// let mut reporter = Reporter::new();
+/// assert!(reporter.check(&policy, reading, Quality::Ok, 0));
+/// assert!(!reporter.check(&policy, reading, Quality::Ok, 2000));
+/// assert!(reporter.check(&policy, reading, Quality::Ok, 5000));
+/// ```
+#[derive(Debug, Default)]
+pub struct Reporter {
+    last: Option<Sent>,
+}
+
+impl Reporter {
+    pub const fn new() -> Self {
+        Reporter { last: None }
+    }
+
+    /// Czy pomiar z chwili `now_ms` należy wysłać, pozytywna decyzja jest zapamiętywana.
+    /// Pierwszy pomiar jest zawsze wysyłany.
+    pub fn check(&mut self, policy: &Policy, reading: Option<Reading>, quality: Quality, now_ms: u32) -> bool {
+        let due = match (self.last, *policy) {
+            (None, _) => true,
+            (Some(last), Policy::Interval { interval_ms }) => now_ms.wrapping_sub(last.at_ms) >= interval_ms,
+            (Some(last), Policy::Change { temperature, humidity, keepalive_ms }) => {
+                now_ms.wrapping_sub(last.at_ms) >= keepalive_ms
+                    || last.quality != quality
+                    || match (last.reading, reading) {
+                        (Some(sent), Some(new)) => {
+                            exceeds(new.temperature - sent.temperature, temperature)
+                                || exceeds(new.humidity - sent.humidity, humidity)
+                        }
+                        (None, None) => false,
+                        _ => true,
+                    }
+            }
+        };
+        if due {
+            self.last = Some(Sent { reading, quality, at_ms: now_ms });
+        }
+        due
+    }
+}
+
+// Czy zmiana o dowolnym znaku osiągnęła próg (bez f32::abs, niedostępnego w no_std)
+fn exceeds(change: f32, deadband: f32) -> bool {
+    change >= deadband || -change >= deadband
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+
+    const CHANGE: Policy = Policy::Change { temperature: 0.5, humidity: 2.0, keepalive_ms: 60_000 };
+
+    // Poprawny pomiar lub brak odpowiedzi czujnika
+    fn check(reporter: &mut Reporter, policy: &Policy, reading: Option<(f32, f32)>, now_ms: u32) -> bool {
+        match reading {
+            Some((temperature, humidity)) => {
+                reporter.check(policy, Some(Reading { temperature, humidity }), Quality::Ok, now_ms)
+            }
+            None => reporter.check(policy, None, Quality::Timeout, now_ms),
+        }
+    }
+
+    #[test]
+    fn interval_policy_limits_the_rate() {
+        let policy = Policy::Interval { interval_ms: 10_000 };
+        let mut reporter = Reporter::new();
+        assert!(check(&mut reporter, &policy, Some((20.0, 40.0)), 1_000));
+        assert!(!check(&mut reporter, &policy, Some((25.0, 40.0)), 2_000));
+        assert!(!check(&mut reporter, &policy, Some((25.0, 40.0)), 10_999));
+        assert!(check(&mut reporter, &policy, Some((25.0, 40.0)), 11_000));
+
+        let mut every = Reporter::new();
+        for now in [0, 1_000, 2_000] {
+            assert!(check(&mut every, &Policy::default(), None, now));
+        }
+    }
+
+    #[test]
+    fn change_policy_reports_outside_the_deadband_and_keeps_alive() {
+        let mut reporter = Reporter::new();
+        assert!(check(&mut reporter, &CHANGE, Some((20.0, 40.0)), 0));
+        assert!(!check(&mut reporter, &CHANGE, Some((20.4, 41.0)), 1_000));
+        // Porównanie z ostatnim wysłanym pomiarem, a nie z poprzednim
+        assert!(check(&mut reporter, &CHANGE, Some((20.5, 41.0)), 2_000));
+        assert!(!check(&mut reporter, &CHANGE, Some((20.1, 42.9)), 3_000));
+        assert!(check(&mut reporter, &CHANGE, Some((20.1, 38.5)), 4_000));
+        assert!(!check(&mut reporter, &CHANGE, Some((20.1, 38.5)), 63_999));
+        assert!(check(&mut reporter, &CHANGE, Some((20.1, 38.5)), 64_000));
+    }
+
+    #[test]
+    fn change_policy_reports_quality_changes() {
+        let mut reporter = Reporter::new();
+        assert!(check(&mut reporter, &CHANGE, Some((20.0, 40.0)), 0));
+        assert!(check(&mut reporter, &CHANGE, None, 1_000));
+        assert!(!check(&mut reporter, &CHANGE, None, 2_000));
+        assert!(check(&mut reporter, &CHANGE, Some((20.0, 40.0)), 3_000));
+        // Ostatni poprawny pomiar powtórzony po błędzie odczytu
+        let reading = Some(Reading { temperature: 20.0, humidity: 40.0 });
+        assert!(reporter.check(&CHANGE, reading, Quality::Stale, 4_000));
+        assert!(!reporter.check(&CHANGE, reading, Quality::Stale, 5_000));
+    }
+}
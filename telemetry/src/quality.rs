@@ -0,0 +1,150 @@
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::Reading;
+
+/// Zakres temperatur, które czujnik może zmierzyć, wartości spoza niego są odrzucane
+pub const TEMPERATURE_RANGE: RangeInclusive<f32> = -40.0..=80.0;
+/// Zakres wilgotności, wartości spoza niego są odrzucane
+pub const HUMIDITY_RANGE: RangeInclusive<f32> = 0.0..=100.0;
+/// Liczba kolejnych nieudanych odczytów, przez które wysyłany jest ostatni poprawny pomiar
+pub const STALE_SAMPLES: u8 = 2;
+
+/// Wynik odczytu czujnika
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sample {
+    Measured(Reading),
+    /// Czujnik nie odpowiada
+    Timeout,
+    /// Błędna suma kontrolna odczytu
+    Checksum,
+}
+
+/// Jakość pomiaru w rekordzie telemetrii
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quality {
+    /// Pomiar z bieżącego okresu próbkowania
+    Ok,
+    /// Czujnik nie odpowiada, brak wartości
+    Timeout,
+    /// Błędna suma kontrolna, brak wartości
+    Checksum,
+    /// Odczyt się nie udał, wysyłany jest ostatni poprawny pomiar
+    Stale,
+    /// Wartość spoza zakresu czujnika została odrzucona
+    Filtered,
+}
+
+impl Quality {
+    pub const ALL: [Quality; 5] = [Quality::Ok, Quality::Timeout, Quality::Checksum, Quality::Stale, Quality::Filtered];
+
+    /// Identyfikator wysyłany w ramkach protokołu binarnego
+    pub fn id(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_id(id: u8) -> Option<Quality> {
+        Quality::ALL.into_iter().find(|q| q.id() == id)
+    }
+
+    /// Nazwa wysyłana w rekordach
+    pub fn name(self) -> &'static str {
+        match self {
+            Quality::Ok => "ok",
+            Quality::Timeout => "timeout",
+            Quality::Checksum => "checksum",
+            Quality::Stale => "stale",
+            Quality::Filtered => "filtered",
+        }
+    }
+}
+
+impl fmt::Display for Quality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Ocena odczytów czujnika: odrzuca wartości spoza zakresu, a po krótkiej serii
+/// nieudanych odczytów zastępuje pomiar ostatnim poprawnym
+/// # Examples
+/// ```
+/// use telemetry::{Quality, Reading, Sample, Validator};
+///
+/// let mut validator = Validator::new();
+/// let reading = Reading { temperature: 23.4, humidity: 45.0 };
+/// assert_eq!(validator.check(Sample::Measured(reading)), (Some(reading), Quality::Ok));
+/// // Krótka seria błędów powtarza ostatni poprawny pomiar
+/// assert_eq!(validator.check(Sample::Timeout), (Some(reading), Quality::Stale));
+/// ```
+#[derive(Debug, Default)]
+pub struct Validator {
+    last: Option<Reading>,
+    failures: u8,
+}
+
+impl Validator {
+    pub const fn new() -> Self {
+        Validator { last: None, failures: 0 }
+    }
+
+    /// Funkcja zwracająca wartość wysyłaną w rekordzie i jej jakość
+    pub fn check(&mut self, sample: Sample) -> (Option<Reading>, Quality) {
+        let failure = match sample {
+            Sample::Measured(reading)
+                if TEMPERATURE_RANGE.contains(&reading.temperature) && HUMIDITY_RANGE.contains(&reading.humidity) =>
+            {
+                self.last = Some(reading);
+                self.failures = 0;
+                return (Some(reading), Quality::Ok);
+            }
+            Sample::Measured(_) => Quality::Filtered,
+            Sample::Timeout => Quality::Timeout,
+            Sample::Checksum => Quality::Checksum,
+        };
+        self.failures = self.failures.saturating_add(1);
+        match self.last {
+            Some(last) if failure != Quality::Filtered && self.failures <= STALE_SAMPLES => (Some(last), Quality::Stale),
+            _ => (None, failure),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const READING: Reading = Reading { temperature: 23.4, humidity: 45.0 };
+
+    #[test]
+    fn short_failures_repeat_the_last_reading_as_stale() {
+        let mut validator = Validator::new();
+        assert_eq!(validator.check(Sample::Timeout), (None, Quality::Timeout));
+        assert_eq!(validator.check(Sample::Measured(READING)), (Some(READING), Quality::Ok));
+        assert_eq!(validator.check(Sample::Checksum), (Some(READING), Quality::Stale));
+        assert_eq!(validator.check(Sample::Timeout), (Some(READING), Quality::Stale));
+        assert_eq!(validator.check(Sample::Timeout), (None, Quality::Timeout));
+        assert_eq!(validator.check(Sample::Checksum), (None, Quality::Checksum));
+        assert_eq!(validator.check(Sample::Measured(READING)), (Some(READING), Quality::Ok));
+        assert_eq!(validator.check(Sample::Timeout), (Some(READING), Quality::Stale));
+    }
+
+    #[test]
+    fn ids_round_trip() {
+        for quality in Quality::ALL {
+            assert_eq!(Quality::from_id(quality.id()), Some(quality));
+        }
+        assert_eq!(Quality::Stale.id(), 3);
+        assert_eq!(Quality::from_id(5), None);
+    }
+
+    #[test]
+    fn values_out_of_range_are_filtered() {
+        let mut validator = Validator::new();
+        validator.check(Sample::Measured(READING));
+        let error = Reading { temperature: 255.5, humidity: 255.5 };
+        assert_eq!(validator.check(Sample::Measured(error)), (None, Quality::Filtered));
+        let dry = Reading { temperature: 20.0, humidity: -1.0 };
+        assert_eq!(validator.check(Sample::Measured(dry)), (None, Quality::Filtered));
+    }
+}
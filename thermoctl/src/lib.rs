@@ -0,0 +1,268 @@
+//! Biblioteka programu `thermoctl`: komunikacja z termometrem przez port szeregowy
+//! w protokole tekstowym (linie `uprintln!` oraz polecenia `g`, `s`, `version` i `caps`).
+//!
+//! Urządzenie jest dowolnym strumieniem `Read + Write`, dzięki czemu testy mogą
+//! podłączyć fałszywy termometr przez pseudoterminal zamiast prawdziwego portu.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+mod line;
+pub use caps::{Caps, Version, PROTOCOL_VERSION};
+pub use line::*;
+
+/// Prędkość USART1 termometru
+pub const DEFAULT_BAUD: u32 = 115_200;
+/// Domyślny czas oczekiwania na odpowiedź
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Czas pojedynczego odczytu z portu, po nim sprawdzany jest termin odpowiedzi
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Błędy komunikacji z termometrem
+#[derive(Debug)]
+pub enum Error {
+    /// Nie udało się otworzyć lub skonfigurować portu
+    Serial(serialport::Error),
+    /// Błąd odczytu lub zapisu
+    Io(io::Error),
+    /// Brak oczekiwanej odpowiedzi w zadanym czasie
+    Timeout(&'static str),
+    /// Termometr odrzucił polecenie
+    Rejected(String),
+    /// Odpowiedź w nieoczekiwanym formacie
+    BadAnswer(String),
+    /// Termometr używa protokołu niezgodnego z `PROTOCOL_VERSION`
+    Incompatible(Version),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Serial(e) => write!(f, "serial port: {}", e),
+            Error::Io(e) => write!(f, "i/o: {}", e),
+            Error::Timeout(what) => write!(f, "timed out waiting for {}", what),
+            Error::Rejected(reason) => write!(f, "rejected by the device: {}", reason),
+            Error::BadAnswer(answer) => write!(f, "unexpected answer: {}", answer),
+            Error::Incompatible(version) => write!(
+                f,
+                "device speaks protocol {}, thermoctl supports {}.{} or newer {}.x",
+                version, PROTOCOL_VERSION.major, PROTOCOL_VERSION.minor, PROTOCOL_VERSION.major
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serialport::Error> for Error {
+    fn from(e: serialport::Error) -> Self {
+        Error::Serial(e)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Termometr podłączony przez port szeregowy
+/// # Examples
+/// ```no_run
+/// use thermoctl::{Device, DEFAULT_BAUD};
+/// # fn main() -> thermoctl::Result<()> {
+///
+/// let mut device = Device::open("/dev/ttyACM0", DEFAULT_BAUD)?;
+/// println!("bounds {}", device.get_bounds()?);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Device<P> {
+    port: P,
+    // Odebrane bajty niedokończonej linii
+    pending: Vec<u8>,
+    timeout: Duration,
+}
+
+impl Device<Box<dyn serialport::SerialPort>> {
+    /// Funkcja otwierająca port szeregowy, np. `/dev/ttyACM0` lub `COM3`
+    pub fn open(path: &str, baud: u32) -> Result<Self> {
+        let port = serialport::new(path, baud).timeout(READ_TIMEOUT).open()?;
+        Ok(Device::new(port))
+    }
+}
+
+impl<P: Read + Write> Device<P> {
+    /// Odczyt z `port` powinien kończyć się po krótkim czasie błędem `TimedOut` lub `WouldBlock`
+    pub fn new(port: P) -> Self {
+        Device {
+            port,
+            pending: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Zmiana czasu oczekiwania na odpowiedź
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Funkcja czekająca na kolejną niepustą linię
+    pub fn next_line(&mut self) -> Result<Line> {
+        self.line_before(Instant::now() + self.timeout)
+    }
+
+    fn line_before(&mut self, deadline: Instant) -> Result<Line> {
+        loop {
+            if let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+                let raw: Vec<u8> = self.pending.drain(..=end).collect();
+                let text = String::from_utf8_lossy(&raw);
+                if text.trim().is_empty() {
+                    continue;
+                }
+                return Ok(line::parse(&text));
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout("a line"));
+            }
+            let mut buf = [0u8; 64];
+            match self.port.read(&mut buf) {
+                Ok(0) => std::thread::sleep(READ_TIMEOUT),
+                Ok(n) => self.pending.extend_from_slice(&buf[..n]),
+                Err(e) if matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Funkcja czekająca na kolejny pomiar wysyłany co okres próbkowania.
+    /// Zwraca None, gdy termometr zgłosił błąd czujnika.
+    pub fn next_reading(&mut self) -> Result<Option<Reading>> {
+        self.wait_for("a reading", |line| match line {
+            Line::Reading(reading) => Some(Ok(Some(reading))),
+            Line::SensorError => Some(Ok(None)),
+            _ => None,
+        })
+    }
+
+    /// Odczyt granic temperatur poleceniem `g`
+    pub fn get_bounds(&mut self) -> Result<Bounds> {
+        self.send("g")?;
+        self.wait_for("bounds", |line| match line {
+            Line::Bounds(bounds) => Some(Ok(bounds)),
+            _ => None,
+        })
+    }
+
+    /// Zmiana granic temperatur poleceniem `s`, zwraca granice potwierdzone przez termometr
+    pub fn set_bounds(&mut self, bounds: &Bounds) -> Result<Bounds> {
+        self.send(&format!("s{}", bounds))?;
+        self.wait_for("bounds confirmation", |line| match line {
+            Line::BoundsChanged(bounds) => Some(Ok(bounds)),
+            Line::BoundsRejected(reason) => Some(Err(Error::Rejected(reason))),
+            // Np. `ERR 8 locked: unlock first`, gdy termometr jest zablokowany PIN-em
+            Line::Err(reason) => Some(Err(Error::Rejected(reason))),
+            _ => None,
+        })
+    }
+
+    /// Odblokowanie poleceń zapisu poleceniem `unlock <pin>`
+    pub fn unlock(&mut self, pin: &str) -> Result<()> {
+        self.send(&format!("unlock {}", pin))?;
+        self.wait_for("unlock", |line| match line {
+            Line::Ok(_) => Some(Ok(())),
+            Line::Err(reason) => Some(Err(Error::Rejected(reason))),
+            _ => None,
+        })
+    }
+
+    /// Zablokowanie poleceń zapisu poleceniem `lock`
+    pub fn lock(&mut self) -> Result<()> {
+        self.send("lock")?;
+        self.wait_for("lock", |line| match line {
+            Line::Ok(_) => Some(Ok(())),
+            Line::Err(reason) => Some(Err(Error::Rejected(reason))),
+            _ => None,
+        })
+    }
+
+    /// Wersja oprogramowania z polecenia `version`.
+    /// Zwraca None, gdy termometr nie obsługuje tego polecenia.
+    pub fn version(&mut self) -> Result<Option<String>> {
+        self.send("version")?;
+        let version = self.wait_for("version", |line| match line {
+            Line::Ok(body) => Some(Ok(body.strip_prefix("version ").map(str::to_string))),
+            Line::Err(_) => Some(Ok(None)),
+            _ => None,
+        });
+        // Starsze oprogramowanie nie odpowiada na nieznane polecenia
+        match version {
+            Err(Error::Timeout(_)) => Ok(None),
+            version => version,
+        }
+    }
+
+    /// Treść odpowiedzi na `caps` (bez `OK `), do odczytu przez `parse_caps`.
+    /// Zwraca None, gdy termometr nie obsługuje tego polecenia.
+    pub fn caps(&mut self) -> Result<Option<String>> {
+        self.send("caps")?;
+        let caps = self.wait_for("caps", |line| match line {
+            Line::Ok(body) if body.starts_with("caps ") => Some(Ok(Some(body))),
+            Line::Err(_) => Some(Ok(None)),
+            _ => None,
+        });
+        match caps {
+            Err(Error::Timeout(_)) => Ok(None),
+            caps => caps,
+        }
+    }
+
+    // Wysłanie polecenia zakończonego znakiem nowej linii
+    fn send(&mut self, command: &str) -> Result<()> {
+        self.port.write_all(command.as_bytes())?;
+        self.port.write_all(b"\n")?;
+        self.port.flush()?;
+        Ok(())
+    }
+
+    // Pomijanie linii, dla których `f` zwraca None (np. pomiarów wysyłanych w międzyczasie)
+    fn wait_for<T>(&mut self, what: &'static str, mut f: impl FnMut(Line) -> Option<Result<T>>) -> Result<T> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match self.line_before(deadline) {
+                Ok(line) => {
+                    if let Some(result) = f(line) {
+                        return result;
+                    }
+                }
+                Err(Error::Timeout(_)) => return Err(Error::Timeout(what)),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Funkcja odczytująca odpowiedź `caps` i sprawdzająca, czy termometr obsługuje protokół `PROTOCOL_VERSION`
+/// # Examples
+/// ```
+/// # fn main() -> thermoctl::Result<()> {
+/// // Treść odpowiedzi z `Device::caps`
+/// let body = "caps protocol=1.1 commands=help,version formats=csv,json sensors=dht11";
+/// let caps = thermoctl::parse_caps(body)?;
+/// println!("formats: {}", caps.formats);
+/// assert!(thermoctl::parse_caps("caps protocol=2.0").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn parse_caps(body: &str) -> Result<Caps<'_>> {
+    let caps = Caps::parse(body).map_err(|e| Error::BadAnswer(format!("{}: {}", e, body)))?;
+    if !caps.protocol.supports(PROTOCOL_VERSION) {
+        return Err(Error::Incompatible(caps.protocol));
+    }
+    Ok(caps)
+}
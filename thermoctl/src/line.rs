@@ -0,0 +1,205 @@
+// Rozpoznawanie linii wysyłanych przez termometr w protokole tekstowym
+use std::fmt;
+
+// Wartość wysyłana przez starsze wersje termometru, gdy czujnik nie odpowiada (2555 / 10)
+const SENSOR_ERROR: f32 = 255.5;
+
+/// Pomiar z czujnika: temperatura w °C, wilgotność w %
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub temperature: f32,
+    pub humidity: f32,
+}
+
+/// Granice temperatur w °C
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bounds {
+    pub cold: f32,
+    pub optimal: f32,
+    pub hot: f32,
+    pub critical: f32,
+}
+
+impl Bounds {
+    fn from_values([cold, optimal, hot, critical]: [f32; 4]) -> Self {
+        Bounds { cold, optimal, hot, critical }
+    }
+}
+
+/// Format zgodny z poleceniem `s` termometru: "cold,optimal,hot,critical"
+impl fmt::Display for Bounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.cold, self.optimal, self.hot, self.critical)
+    }
+}
+
+/// Jedna linia odebrana z termometru
+#[derive(Debug, Clone, PartialEq)]
+pub enum Line {
+    /// `Temp: 23.4, Hum: 45, Seq: 7, Uptime: 12000 ms, Quality: ok` (starsze wersje: `Temp: 23.4, Hum: 45`)
+    Reading(Reading),
+    /// `Temp: -, Hum: -, ...` lub `Temp: 255.5, Hum: 255.5` - czujnik nie odpowiada
+    SensorError,
+    /// `Cold: 0, Optimal: 25,  High: 30, Critical: 35` - odpowiedź na `g`
+    Bounds(Bounds),
+    /// `Temp values changed: Low: 0, ...` - odpowiedź na `s`
+    BoundsChanged(Bounds),
+    /// `Temp values not changed: <powód>` - odpowiedź na `s`
+    BoundsRejected(String),
+    /// `OK ...` - odpowiedź na polecenie
+    Ok(String),
+    /// `ERR <kod> <opis>` - odpowiedź na polecenie
+    Err(String),
+    /// Pozostałe linie, np. echo `Got s: ...`
+    Other(String),
+}
+
+/// Funkcja rozpoznająca linię bez znaków końca linii
+pub fn parse(line: &str) -> Line {
+    let line = without_prompt(line.trim());
+    if let Some(record) = record(line) {
+        return record;
+    }
+    if let Some(values) = fields(line, ["Cold", "Optimal", "High", "Critical"]) {
+        return Line::Bounds(Bounds::from_values(values));
+    }
+    if let Some(rest) = line.strip_prefix("Temp values changed:") {
+        if let Some(values) = fields(rest, ["Low", "Optimal", "High", "Critical"]) {
+            return Line::BoundsChanged(Bounds::from_values(values));
+        }
+    }
+    if let Some(reason) = line.strip_prefix("Temp values not changed:") {
+        return Line::BoundsRejected(reason.trim().to_string());
+    }
+    if line == "OK" {
+        return Line::Ok(String::new());
+    }
+    if let Some(body) = line.strip_prefix("OK ") {
+        return Line::Ok(body.to_string());
+    }
+    if let Some(body) = line.strip_prefix("ERR ") {
+        return Line::Err(body.to_string());
+    }
+    Line::Other(line.to_string())
+}
+
+// Zachęta `> ` i czyszczenie wiersza wysyłane przez powłokę termometru (`set shell on`)
+fn without_prompt(mut line: &str) -> &str {
+    loop {
+        if let Some(rest) = line.strip_prefix("\x1b[K").or_else(|| line.strip_prefix(">")) {
+            line = rest.trim_start();
+        } else {
+            return line;
+        }
+    }
+}
+
+// Rekord pomiaru: temperatura i wilgotność, a od wersji z jakością pomiaru także numer, czas i jakość.
+// Brak wartości oznaczany jest `-`, w starszych wersjach wartością 255.5.
+fn record(line: &str) -> Option<Line> {
+    let parts: Vec<&str> = line.split(',').collect();
+    let field = |i: usize, label: &str| {
+        let (name, value) = parts.get(i)?.split_once(':')?;
+        (name.trim() == label).then(|| value.trim())
+    };
+    match parts.len() {
+        2 => {}
+        5 => {
+            field(2, "Seq")?;
+            field(3, "Uptime")?;
+            field(4, "Quality")?;
+        }
+        _ => return None,
+    }
+    let (temperature, humidity) = (field(0, "Temp")?, field(1, "Hum")?);
+    if temperature == "-" && humidity == "-" {
+        return Some(Line::SensorError);
+    }
+    let reading = Reading { temperature: temperature.parse().ok()?, humidity: humidity.parse().ok()? };
+    if reading.temperature == SENSOR_ERROR {
+        return Some(Line::SensorError);
+    }
+    Some(Line::Reading(reading))
+}
+
+// Odczyt pól "Etykieta: wartość" rozdzielonych przecinkami, w podanej kolejności
+fn fields<const N: usize>(line: &str, labels: [&str; N]) -> Option<[f32; N]> {
+    let mut values = [0.0; N];
+    let mut parts = line.split(',');
+    for (value, label) in values.iter_mut().zip(labels) {
+        let (name, text) = parts.next()?.split_once(':')?;
+        if name.trim() != label {
+            return None;
+        }
+        *value = text.trim().parse().ok()?;
+    }
+    match parts.next() {
+        Some(_) => None,
+        None => Some(values),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: Bounds = Bounds { cold: 0.0, optimal: 25.0, hot: 30.0, critical: 35.0 };
+
+    #[test]
+    fn parses_readings() {
+        assert_eq!(
+            parse("Temp: 23.4, Hum: 45\r\n"),
+            Line::Reading(Reading { temperature: 23.4, humidity: 45.0 })
+        );
+        assert_eq!(parse("Temp: 255.5, Hum: 255.5"), Line::SensorError);
+        assert_eq!(
+            parse("Temp: 23.4, Hum: 45, Seq: 7, Uptime: 12000 ms, Quality: stale"),
+            Line::Reading(Reading { temperature: 23.4, humidity: 45.0 })
+        );
+        assert_eq!(parse("Temp: -, Hum: -, Seq: 8, Uptime: 13000 ms, Quality: timeout"), Line::SensorError);
+    }
+
+    #[test]
+    fn parses_legacy_bounds_responses() {
+        assert_eq!(parse("Cold: 0, Optimal: 25,  High: 30, Critical: 35"), Line::Bounds(DEFAULT));
+        assert_eq!(
+            parse("Temp values changed: Low: 0, Optimal: 25,  High: 30, Critical: 35"),
+            Line::BoundsChanged(DEFAULT)
+        );
+        assert_eq!(
+            parse("Temp values not changed: optimal must be below hot"),
+            Line::BoundsRejected("optimal must be below hot".to_string())
+        );
+    }
+
+    #[test]
+    fn parses_command_responses() {
+        assert_eq!(parse("OK version 0.1.0"), Line::Ok("version 0.1.0".to_string()));
+        assert_eq!(parse("ERR 1 unknown command: info"), Line::Err("1 unknown command: info".to_string()));
+        assert_eq!(parse("Got s: 0,25,30,35"), Line::Other("Got s: 0,25,30,35".to_string()));
+    }
+
+    #[test]
+    fn skips_shell_prompt() {
+        assert_eq!(parse("> OK shell on"), Line::Ok("shell on".to_string()));
+        assert_eq!(
+            parse("\r\x1b[KTemp: 23.4, Hum: 45"),
+            Line::Reading(Reading { temperature: 23.4, humidity: 45.0 })
+        );
+        assert_eq!(parse("> "), Line::Other(String::new()));
+    }
+
+    #[test]
+    fn rejects_partial_or_mislabelled_fields() {
+        assert!(matches!(parse("Temp: 23.4"), Line::Other(_)));
+        assert!(matches!(parse("Temp: 23.4, Hum: x"), Line::Other(_)));
+        assert!(matches!(parse("Temp: 1, Hum: 2, Extra: 3"), Line::Other(_)));
+        assert!(matches!(parse("Hum: 1, Temp: 2"), Line::Other(_)));
+        assert!(matches!(parse("Temp: 1, Hum: 2, Seq: 3, Uptime: 4 ms, Extra: 5"), Line::Other(_)));
+    }
+
+    #[test]
+    fn formats_bounds_for_the_s_command() {
+        assert_eq!(DEFAULT.to_string(), "0,25,30,35");
+    }
+}
@@ -0,0 +1,176 @@
+//! `thermoctl` - program PC do obsługi termometru przez port szeregowy
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand};
+use thermoctl::{Bounds, Device, Error, Reading, Result, DEFAULT_BAUD};
+
+/// Host companion tool for the STM32 thermometer
+#[derive(Parser)]
+#[command(version)]
+struct Cli {
+    /// Serial port of the thermometer, e.g. /dev/ttyACM0 or COM3
+    #[arg(short, long, env = "THERMOCTL_PORT")]
+    port: String,
+    /// Baud rate
+    #[arg(short, long, default_value_t = DEFAULT_BAUD)]
+    baud: u32,
+    /// How long to wait for an answer, in milliseconds
+    #[arg(short, long, default_value_t = 3000)]
+    timeout: u64,
+    /// PIN sent with `unlock` before changing settings on a locked thermometer
+    #[arg(long, env = "THERMOCTL_PIN", hide_env_values = true)]
+    pin: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print live readings
+    Watch {
+        /// Stop after this many readings
+        #[arg(short = 'n', long)]
+        count: Option<u64>,
+    },
+    /// Print the temperature boundaries
+    GetBounds,
+    /// Change the temperature boundaries
+    SetBounds {
+        /// Boundaries in °C as cold,optimal,hot,critical, e.g. 0,25,30,35
+        #[arg(value_parser = parse_bounds)]
+        bounds: Bounds,
+    },
+    /// Record readings to a file
+    Log {
+        /// CSV file to write (time_s,temperature,humidity)
+        #[arg(long)]
+        csv: PathBuf,
+        /// Stop after this many readings
+        #[arg(short = 'n', long)]
+        count: Option<u64>,
+    },
+    /// Print firmware version, boundaries and the latest reading
+    Info,
+    /// Print the protocol version, commands, formats and sensors the firmware supports
+    Caps,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("thermoctl: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let mut device = Device::open(&cli.port, cli.baud)?.timeout(Duration::from_millis(cli.timeout));
+    match cli.command {
+        Command::Watch { count } => {
+            let start = Instant::now();
+            each_reading(&mut device, count, |reading| {
+                println!("{:>8.1} s  {}", start.elapsed().as_secs_f32(), describe(reading));
+                Ok(())
+            })
+        }
+        Command::GetBounds => {
+            println!("{}", device.get_bounds()?);
+            Ok(())
+        }
+        Command::SetBounds { bounds } => {
+            if let Some(pin) = &cli.pin {
+                device.unlock(pin)?;
+            }
+            println!("{}", device.set_bounds(&bounds)?);
+            Ok(())
+        }
+        Command::Log { csv, count } => {
+            let mut file = BufWriter::new(File::create(&csv)?);
+            writeln!(file, "time_s,temperature,humidity")?;
+            each_reading(&mut device, count, |reading| {
+                let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+                // Błąd czujnika zapisywany jest jako wiersz bez wartości
+                match reading {
+                    Some(r) => writeln!(file, "{:.3},{},{}", time.as_secs_f64(), r.temperature, r.humidity)?,
+                    None => writeln!(file, "{:.3},,", time.as_secs_f64())?,
+                }
+                // Zapis po każdym wierszu, aby przerwanie Ctrl-C nie gubiło danych
+                file.flush()?;
+                Ok(())
+            })
+        }
+        Command::Info => {
+            println!("port: {} @ {} Bd", cli.port, cli.baud);
+            match device.version()? {
+                Some(version) => println!("firmware: {}", version),
+                None => println!("firmware: unknown (no `version` command)"),
+            }
+            match device.caps()? {
+                Some(body) => println!("protocol: {}", thermoctl::parse_caps(&body)?.protocol),
+                None => println!("protocol: unknown (no `caps` command)"),
+            }
+            println!("bounds: {}", device.get_bounds()?);
+            match device.next_reading() {
+                Ok(reading) => println!("reading: {}", describe(reading)),
+                Err(Error::Timeout(_)) => println!("reading: none within {} ms", cli.timeout),
+                Err(e) => return Err(e),
+            }
+            Ok(())
+        }
+        // Niezgodna wersja protokołu kończy program błędem, aby skrypty nie używały nieznanych poleceń
+        Command::Caps => {
+            let body = device.caps()?.ok_or(Error::Rejected("no `caps` command, firmware too old".to_string()))?;
+            let caps = thermoctl::parse_caps(&body)?;
+            println!("protocol: {}", caps.protocol);
+            println!("commands: {}", caps.commands);
+            println!("formats: {}", caps.formats);
+            println!("sensors: {}", caps.sensors);
+            Ok(())
+        }
+    }
+}
+
+// Odczyt `count` pomiarów lub bez końca, brak pomiaru w czasie oczekiwania nie przerywa odczytu
+fn each_reading<P: std::io::Read + std::io::Write>(
+    device: &mut Device<P>,
+    count: Option<u64>,
+    mut f: impl FnMut(Option<Reading>) -> Result<()>,
+) -> Result<()> {
+    let mut received = 0;
+    while count.is_none_or(|count| received < count) {
+        match device.next_reading() {
+            Ok(reading) => {
+                f(reading)?;
+                received += 1;
+            }
+            Err(Error::Timeout(_)) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn describe(reading: Option<Reading>) -> String {
+    match reading {
+        Some(r) => format!("temp={} °C hum={} %", r.temperature, r.humidity),
+        None => "sensor error".to_string(),
+    }
+}
+
+fn parse_bounds(s: &str) -> std::result::Result<Bounds, String> {
+    let values: Vec<f32> = s
+        .split(',')
+        .map(|v| v.trim().parse::<f32>().map_err(|_| format!("`{}` is not a number", v.trim())))
+        .collect::<std::result::Result<_, _>>()?;
+    match values[..] {
+        [cold, optimal, hot, critical] => Ok(Bounds { cold, optimal, hot, critical }),
+        _ => Err(format!("expected 4 values (cold,optimal,hot,critical), got {}", values.len())),
+    }
+}
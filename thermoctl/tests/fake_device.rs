@@ -0,0 +1,219 @@
+// Testy z fałszywym termometrem podłączonym przez pseudoterminal, bez sprzętu
+use std::io::{Read, Write};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serialport::{SerialPort, TTYPort};
+use thermoctl::{Bounds, Device, Error, Reading, PROTOCOL_VERSION};
+
+const READING_PERIOD: Duration = Duration::from_millis(50);
+
+/// Wątek odpowiadający jak oprogramowanie termometru: wysyła kolejno `readings`
+/// (None = błąd czujnika) i obsługuje polecenia `g`, `s`, `version`, `lock` i `unlock` (PIN 2468)
+struct FakeDevice {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FakeDevice {
+    fn spawn(mut port: TTYPort, readings: Vec<Option<(f32, f32)>>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        port.set_timeout(Duration::from_millis(10)).unwrap();
+        let thread = thread::spawn(move || {
+            let mut bounds = [0.0f32, 25.0, 30.0, 35.0];
+            let mut locked = false;
+            let mut line = Vec::new();
+            let mut next = readings.iter().cycle();
+            let mut last_reading = Instant::now();
+            let started = Instant::now();
+            let mut seq = 0u32;
+            while !stopped.load(Ordering::Relaxed) {
+                if last_reading.elapsed() >= READING_PERIOD {
+                    last_reading = Instant::now();
+                    let uptime_ms = started.elapsed().as_millis();
+                    match next.next().unwrap() {
+                        Some((temp, hum)) => write!(
+                            port,
+                            "Temp: {}, Hum: {}, Seq: {}, Uptime: {} ms, Quality: ok\r\n",
+                            temp, hum, seq, uptime_ms
+                        ),
+                        None => write!(port, "Temp: -, Hum: -, Seq: {}, Uptime: {} ms, Quality: timeout\r\n", seq, uptime_ms),
+                    }
+                    .unwrap();
+                    seq += 1;
+                }
+                let mut byte = [0u8];
+                match port.read(&mut byte) {
+                    Ok(1) if byte[0] == b'\n' || byte[0] == b'\r' => {
+                        let command = String::from_utf8(std::mem::take(&mut line)).unwrap();
+                        respond(&mut port, &command, &mut bounds, &mut locked);
+                    }
+                    Ok(1) => line.push(byte[0]),
+                    _ => {}
+                }
+            }
+        });
+        FakeDevice { stop, thread: Some(thread) }
+    }
+}
+
+impl Drop for FakeDevice {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread.take().unwrap().join().unwrap();
+    }
+}
+
+fn respond(port: &mut TTYPort, command: &str, bounds: &mut [f32; 4], locked: &mut bool) {
+    let [c, o, h, cr] = *bounds;
+    if command == "lock" {
+        *locked = true;
+        write!(port, "OK locked\r\n").unwrap();
+    } else if let Some(pin) = command.strip_prefix("unlock ") {
+        if pin == "2468" {
+            *locked = false;
+            write!(port, "OK unlocked\r\n").unwrap();
+        } else {
+            write!(port, "ERR 8 locked: wrong pin\r\n").unwrap();
+        }
+    } else if command.starts_with('s') && *locked {
+        write!(port, "ERR 8 locked: unlock first\r\n").unwrap();
+    } else if command == "g" {
+        write!(port, "Cold: {}, Optimal: {},  High: {}, Critical: {}\r\n", c, o, h, cr).unwrap();
+    } else if let Some(values) = command.strip_prefix('s') {
+        write!(port, "Got s: {}\r\n", values).unwrap();
+        let parsed: Vec<f32> = values.split(',').filter_map(|v| v.parse().ok()).collect();
+        if parsed.len() == 4 && parsed.windows(2).all(|w| w[0] < w[1]) {
+            bounds.copy_from_slice(&parsed);
+            let [c, o, h, cr] = *bounds;
+            write!(port, "Temp values changed: Low: {}, Optimal: {},  High: {}, Critical: {}\r\n", c, o, h, cr).unwrap();
+        } else {
+            write!(port, "Temp values not changed: boundaries must be increasing\r\n").unwrap();
+        }
+    } else if command == "version" {
+        write!(port, "OK version 0.1.0\r\n").unwrap();
+    } else if command == "caps" {
+        write!(port, "OK {}\r\n", caps::CAPS).unwrap();
+    } else if !command.is_empty() {
+        write!(port, "ERR 1 unknown command: {}\r\n", command).unwrap();
+    }
+}
+
+fn connect(readings: Vec<Option<(f32, f32)>>) -> (FakeDevice, TTYPort) {
+    let (master, slave) = TTYPort::pair().expect("pseudo-terminal pair");
+    (FakeDevice::spawn(master, readings), slave)
+}
+
+fn thermoctl(port: &TTYPort, args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_thermoctl"))
+        .arg("--port")
+        .arg(port.name().unwrap())
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn receives_periodic_readings() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0)), None]);
+    let mut device = Device::new(port);
+    assert_eq!(device.next_reading().unwrap(), Some(Reading { temperature: 21.5, humidity: 40.0 }));
+    assert_eq!(device.next_reading().unwrap(), None);
+}
+
+#[test]
+fn reads_and_changes_bounds() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0))]);
+    let mut device = Device::new(port);
+    assert_eq!(device.get_bounds().unwrap(), Bounds { cold: 0.0, optimal: 25.0, hot: 30.0, critical: 35.0 });
+
+    let warmer = Bounds { cold: 5.0, optimal: 20.0, hot: 28.5, critical: 40.0 };
+    assert_eq!(device.set_bounds(&warmer).unwrap(), warmer);
+    assert_eq!(device.get_bounds().unwrap(), warmer);
+
+    let unordered = Bounds { cold: 30.0, optimal: 20.0, hot: 28.5, critical: 40.0 };
+    assert!(matches!(device.set_bounds(&unordered), Err(Error::Rejected(_))));
+    assert_eq!(device.get_bounds().unwrap(), warmer);
+}
+
+#[test]
+fn unlocks_before_changing_bounds() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0))]);
+    let mut device = Device::new(port);
+    device.lock().unwrap();
+    let warmer = Bounds { cold: 5.0, optimal: 20.0, hot: 28.5, critical: 40.0 };
+    assert!(matches!(device.set_bounds(&warmer), Err(Error::Rejected(_))));
+    assert!(matches!(device.unlock("1111"), Err(Error::Rejected(_))));
+    device.unlock("2468").unwrap();
+    assert_eq!(device.set_bounds(&warmer).unwrap(), warmer);
+}
+
+#[test]
+fn reads_firmware_version() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0))]);
+    let mut device = Device::new(port);
+    assert_eq!(device.version().unwrap().as_deref(), Some("0.1.0"));
+}
+
+#[test]
+fn reads_and_checks_capabilities() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0))]);
+    let mut device = Device::new(port);
+    let body = device.caps().unwrap().unwrap();
+    let caps = thermoctl::parse_caps(&body).unwrap();
+    assert_eq!(caps.protocol, PROTOCOL_VERSION);
+    assert!(caps.commands.contains("get-bounds"));
+    assert!(caps.sensors.contains("dht11"));
+
+    let newer = "caps protocol=2.0 commands=help";
+    assert!(matches!(thermoctl::parse_caps(newer), Err(Error::Incompatible(_))));
+    assert!(matches!(thermoctl::parse_caps("caps commands=help"), Err(Error::BadAnswer(_))));
+}
+
+#[test]
+fn times_out_without_a_device() {
+    let (master, port) = TTYPort::pair().unwrap();
+    let mut device = Device::new(port).timeout(Duration::from_millis(200));
+    assert!(matches!(device.get_bounds(), Err(Error::Timeout(_))));
+    drop(master);
+}
+
+#[test]
+fn cli_prints_and_sets_bounds() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0))]);
+    let output = thermoctl(&port, &["get-bounds"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "0,25,30,35\n");
+
+    let output = thermoctl(&port, &["set-bounds", "1,20,30,45"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "1,20,30,45\n");
+
+    let output = thermoctl(&port, &["set-bounds", "1,20,30"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn cli_logs_readings_to_csv() {
+    let (_fake, port) = connect(vec![Some((21.5, 40.0)), None, Some((22.0, 41.0))]);
+    let csv = std::env::temp_dir().join(format!("thermoctl-{}.csv", std::process::id()));
+    let output = thermoctl(&port, &["log", "--csv", csv.to_str().unwrap(), "--count", "3"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let content = std::fs::read_to_string(&csv).unwrap();
+    std::fs::remove_file(&csv).ok();
+    let rows: Vec<Vec<&str>> = content.lines().map(|l| l.split(',').collect()).collect();
+    assert_eq!(rows[0], ["time_s", "temperature", "humidity"]);
+    assert_eq!(rows.len(), 4);
+    // Odczyt zaczyna się w dowolnym miejscu cyklu pomiarów, ale kolejność jest zachowana
+    let values: Vec<[&str; 2]> = rows[1..].iter().map(|r| [r[1], r[2]]).collect();
+    let cycle = [["21.5", "40"], ["", ""], ["22", "41"]];
+    let start = cycle.iter().position(|v| *v == values[0]).unwrap();
+    for (i, value) in values.iter().enumerate() {
+        assert_eq!(*value, cycle[(start + i) % 3]);
+    }
+}
@@ -0,0 +1,98 @@
+use core::cmp::min;
+
+/// Pojemność każdego z dwóch buforów nadawania przez DMA (bajty)
+pub const DMA_BUFFER: usize = 256;
+
+/// Bufory nadawania przez DMA: jeden jest wysyłany, a drugi wypełniany kolejnymi zapisami
+pub type DmaBuffers = [[u8; DMA_BUFFER]; 2];
+
+/// Kanał DMA przesyłający bajty z pamięci do rejestru nadawczego interfejsu.
+/// Zaimplementowany dla kanałów DMA1 z HAL (`TxChannel`), w testach zastępowany atrapą.
+pub trait TxDma {
+    /// Czy trwa przesyłanie. Zakończone przesyłanie jest zatrzymywane, a flagi
+    /// jego przerwania czyszczone, dzięki czemu kanał jest gotowy do `start`.
+    fn is_busy(&mut self) -> bool;
+
+    /// Rozpoczęcie przesyłania `len` bajtów spod adresu `data` na wolnym kanale.
+    /// Po zakończeniu kanał zgłasza przerwanie, w którym należy wywołać `SerialPort::on_dma_interrupt`.
+    ///
+    /// # Safety
+    /// Pamięć `data..data + len` nie może być zmieniana do końca przesyłania (`is_busy` == false).
+    unsafe fn start(&mut self, data: *const u8, len: usize);
+}
+
+/// Port bez DMA, bajty wysyłane są w przerwaniu TXE z bufora pierścieniowego
+pub enum NoDma {}
+
+impl TxDma for NoDma {
+    fn is_busy(&mut self) -> bool {
+        match *self {}
+    }
+
+    unsafe fn start(&mut self, _: *const u8, _: usize) {
+        match *self {}
+    }
+}
+
+// Podwójny bufor: zapisy trafiają do bufora `fill`, drugi bufor może być w tym czasie wysyłany
+pub(crate) struct DoubleBuffer<D> {
+    channel: D,
+    buffers: &'static mut DmaBuffers,
+    fill: usize,
+    len: usize,
+}
+
+impl<D: TxDma> DoubleBuffer<D> {
+    pub(crate) fn new(channel: D, buffers: &'static mut DmaBuffers) -> Self {
+        DoubleBuffer { channel, buffers, fill: 0, len: 0 }
+    }
+
+    // Zapis bajtów do bufora, pełny bufor jest wysyłany od razu, gdy kanał jest wolny.
+    // Zwraca liczbę zapisanych bajtów, reszta nie zmieściła się w żadnym buforze.
+    pub(crate) fn write(&mut self, mut bytes: &[u8]) -> usize {
+        let total = bytes.len();
+        loop {
+            let n = min(DMA_BUFFER - self.len, bytes.len());
+            self.buffers[self.fill][self.len..self.len + n].copy_from_slice(&bytes[..n]);
+            self.len += n;
+            bytes = &bytes[n..];
+            if bytes.is_empty() || !self.start() {
+                break;
+            }
+        }
+        self.start();
+        total - bytes.len()
+    }
+
+    // Wysłanie wypełnianego bufora i zamiana buforów, false gdy kanał jest zajęty lub bufor pusty.
+    // Kanał sprawdzany jest zawsze, także przy pustym buforze: `is_busy` kasuje flagi zakończonego
+    // przesyłania, bez tego przerwanie kanału zgłaszałoby się bez końca.
+    pub(crate) fn start(&mut self) -> bool {
+        let busy = self.channel.is_busy();
+        if busy || self.len == 0 {
+            return false;
+        }
+        // Bufor nie jest zmieniany do końca przesyłania, kolejne zapisy trafiają do drugiego
+        unsafe { self.channel.start(self.buffers[self.fill].as_ptr(), self.len) };
+        self.fill ^= 1;
+        self.len = 0;
+        true
+    }
+
+    #[cfg(test)]
+    pub(crate) fn channel_mut(&mut self) -> &mut D {
+        &mut self.channel
+    }
+
+    // Liczba bajtów, które `write` przyjmie bez odrzucania: reszta wypełnianego bufora
+    // oraz drugi bufor, jeśli kanał jest wolny i pełny bufor zostanie od razu wysłany
+    pub(crate) fn room(&mut self) -> usize {
+        let busy = self.channel.is_busy();
+        DMA_BUFFER - self.len + if busy { 0 } else { DMA_BUFFER }
+    }
+
+    // Czy wszystkie zapisane bajty zostały przekazane do interfejsu
+    pub(crate) fn is_idle(&mut self) -> bool {
+        self.len == 0 && !self.channel.is_busy()
+    }
+}
@@ -0,0 +1,63 @@
+use core::convert::Infallible;
+use core::fmt;
+
+/// Rodzaj błędu odbioru zgłaszanego przez interfejs szeregowy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RxError {
+    /// Przepełnienie: bajt przyszedł, zanim odczytano poprzedni, co najmniej jeden bajt przepadł
+    Overrun,
+    /// Błąd ramki: brak bitu stopu (zła prędkość, przerwa w linii lub zakłócenie)
+    Framing,
+    /// Szum wykryty podczas próbkowania bitów
+    Noise,
+    /// Błąd parzystości
+    Parity,
+}
+
+/// Interfejsy bez błędów odbioru (np. atrapy w testach)
+impl From<Infallible> for RxError {
+    fn from(e: Infallible) -> Self {
+        match e {}
+    }
+}
+
+/// Liczniki błędów odbioru według rodzaju, zwiększane w przerwaniu
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RxErrors {
+    pub overrun: u32,
+    pub framing: u32,
+    pub noise: u32,
+    pub parity: u32,
+}
+
+impl RxErrors {
+    /// Zliczenie błędu, liczniki przechodzą przez zero zamiast blokować przerwanie
+    pub fn count(&mut self, error: RxError) {
+        let counter = match error {
+            RxError::Overrun => &mut self.overrun,
+            RxError::Framing => &mut self.framing,
+            RxError::Noise => &mut self.noise,
+            RxError::Parity => &mut self.parity,
+        };
+        *counter = counter.wrapping_add(1);
+    }
+
+    /// Suma błędów wszystkich rodzajów
+    pub fn total(&self) -> u32 {
+        self.overrun
+            .wrapping_add(self.framing)
+            .wrapping_add(self.noise)
+            .wrapping_add(self.parity)
+    }
+}
+
+/// Format "overrun=0 framing=2 noise=1 parity=0"
+impl fmt::Display for RxErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "overrun={} framing={} noise={} parity={}",
+            self.overrun, self.framing, self.noise, self.parity
+        )
+    }
+}
@@ -0,0 +1,157 @@
+use heapless::spsc::Queue;
+use heapless::Vec;
+use protocol::{DELIMITER, MAX_FRAME};
+
+// Liczba kompletnych ramek oczekujących na obsługę (pojemność kolejki to N - 1)
+const PENDING_FRAMES: usize = 4;
+
+/// Zakodowana ramka protokołu binarnego bez bajtu 0x00
+pub type Frame = Vec<u8, MAX_FRAME>;
+
+/// Komponent dzielący odebrane bajty na ramki protokołu binarnego zakończone bajtem 0x00.
+/// Działa jak LineReader: bajty dodawane są w przerwaniu, ramki dekodowane w pętli głównej.
+/// Ramki dłuższe niż MAX_FRAME są odrzucane w całości.
+/// # Examples
+/// ```
+/// use protocol::Message;
+/// use usart_1::FrameReader;
+///
+/// let mut buf = [0u8; protocol::MAX_FRAME];
+/// let encoded = protocol::encode_frame(&Message::GetStatus, &mut buf).unwrap();
+/// let mut frames = FrameReader::new();
+/// // W przerwaniu
+/// for byte in encoded {
+///     frames.push(*byte);
+/// }
+/// // W pętli głównej
+/// while let Some(mut frame) = frames.next_frame() {
+///     match protocol::decode_frame(&mut frame) {
+///         Ok(message) => assert_eq!(message, Message::GetStatus),
+///         Err(_) => { /* uszkodzona ramka */ }
+///     }
+/// }
+/// ```
+pub struct FrameReader {
+    current: Frame,
+    too_long: bool,
+    pending: Queue<Frame, PENDING_FRAMES>,
+    dropped: u32,
+}
+
+impl Default for FrameReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameReader {
+    pub const fn new() -> Self {
+        FrameReader {
+            current: Vec::new(),
+            too_long: false,
+            pending: Queue::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Funkcja dodająca odebrany bajt
+    pub fn push(&mut self, byte: u8) {
+        if byte == DELIMITER {
+            let frame = core::mem::take(&mut self.current);
+            if self.too_long {
+                self.too_long = false;
+                self.dropped = self.dropped.wrapping_add(1);
+            } else if !frame.is_empty() && self.pending.enqueue(frame).is_err() {
+                self.dropped = self.dropped.wrapping_add(1);
+            }
+        } else if !self.too_long && self.current.push(byte).is_err() {
+            self.too_long = true;
+            self.current.clear();
+        }
+    }
+
+    /// Funkcja zwracająca kolejną kompletną ramkę
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        self.pending.dequeue()
+    }
+
+    /// Funkcja odrzucająca niedokończoną ramkę, np. przy zmianie protokołu
+    pub fn reset(&mut self) {
+        self.current.clear();
+        self.too_long = false;
+    }
+
+    /// Liczba ramek odrzuconych z powodu długości lub pełnej kolejki
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_all(frames: &mut FrameReader, bytes: &[u8]) {
+        for &byte in bytes {
+            frames.push(byte);
+        }
+    }
+
+    #[test]
+    fn splits_frames_on_delimiter() {
+        let mut frames = FrameReader::new();
+        push_all(&mut frames, &[1, 2, 3, DELIMITER, DELIMITER, 4, DELIMITER, 5]);
+        assert_eq!(frames.next_frame().unwrap(), [1, 2, 3]);
+        // Puste ramki (kolejne bajty 0x00) są pomijane
+        assert_eq!(frames.next_frame().unwrap(), [4]);
+        assert!(frames.next_frame().is_none());
+        frames.push(DELIMITER);
+        assert_eq!(frames.next_frame().unwrap(), [5]);
+        assert_eq!(frames.dropped(), 0);
+    }
+
+    #[test]
+    fn drops_oversized_frames() {
+        let mut frames = FrameReader::new();
+        push_all(&mut frames, &[0xAA; MAX_FRAME + 10]);
+        push_all(&mut frames, &[DELIMITER, 1, DELIMITER]);
+        assert_eq!(frames.dropped(), 1);
+        // Reszta zbyt długiej ramki nie tworzy nowej, kolejna ramka jest odbierana poprawnie
+        assert_eq!(frames.next_frame().unwrap(), [1]);
+        assert!(frames.next_frame().is_none());
+
+        push_all(&mut frames, &[0xBB; MAX_FRAME]);
+        frames.push(DELIMITER);
+        assert_eq!(frames.next_frame().unwrap().len(), MAX_FRAME);
+    }
+
+    #[test]
+    fn full_queue_counts_dropped_frames() {
+        let mut frames = FrameReader::new();
+        for i in 1..=PENDING_FRAMES as u8 + 1 {
+            push_all(&mut frames, &[i, DELIMITER]);
+        }
+        assert_eq!(frames.dropped(), 2);
+        for i in 1..PENDING_FRAMES as u8 {
+            assert_eq!(frames.next_frame().unwrap(), [i]);
+        }
+        assert!(frames.next_frame().is_none());
+    }
+
+    #[test]
+    fn reset_discards_partial_frame_only() {
+        let mut frames = FrameReader::new();
+        push_all(&mut frames, &[1, DELIMITER, 2, 3]);
+        frames.reset();
+        push_all(&mut frames, &[4, DELIMITER]);
+        assert_eq!(frames.next_frame().unwrap(), [1]);
+        assert_eq!(frames.next_frame().unwrap(), [4]);
+
+        // Po resecie zbyt długiej ramki kolejna nie jest już pomijana
+        push_all(&mut frames, &[0xAA; MAX_FRAME + 1]);
+        frames.reset();
+        push_all(&mut frames, &[5, DELIMITER]);
+        assert_eq!(frames.next_frame().unwrap(), [5]);
+        assert_eq!(frames.dropped(), 0);
+    }
+}
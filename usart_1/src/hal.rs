@@ -0,0 +1,94 @@
+use core::sync::atomic::{compiler_fence, Ordering};
+use stm32f3xx_hal::dma::{self, Channel, Direction, Increment, OnChannel};
+use stm32f3xx_hal::serial::{Dma, Error, Event, Instance, Serial};
+
+use crate::{DriverEnable, RxError, SerialInterrupts, TxDma};
+
+// Czas włączenia nadajnika przed bitem startu i jego wyłączenia po bicie stopu,
+// w 1/16 bitu (maksymalnie 31). Jeden bit wystarcza transceiverom typu MAX485.
+const DE_ASSERT_TIME: u8 = 16;
+const DE_DEASSERT_TIME: u8 = 16;
+
+impl<Usart: Instance, Pins> SerialInterrupts for Serial<Usart, Pins> {
+    fn listen_rx(&mut self, enable: bool) {
+        self.configure_interrupt(Event::ReceiveDataRegisterNotEmpty, enable);
+    }
+
+    fn listen_tx(&mut self, enable: bool) {
+        self.configure_interrupt(Event::TransmitDataRegisterEmtpy, enable);
+    }
+}
+
+impl<Usart: Instance, Tx, Rx> DriverEnable for Serial<Usart, (Tx, Rx)> {
+    fn set_driver_enable(&mut self, enable: bool) {
+        // Bity DEM, DEP, DEAT i DEDT można zmieniać tylko przy wyłączonym interfejsie
+        let usart = unsafe { self.peripheral() };
+        let enabled = usart.cr1.read().ue().bit();
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr1.modify(|_, w| w.deat().bits(DE_ASSERT_TIME).dedt().bits(DE_DEASSERT_TIME));
+        usart.cr3.modify(|_, w| w.dem().bit(enable).dep().high());
+        usart.cr1.modify(|_, w| w.ue().bit(enabled));
+    }
+}
+
+impl From<Error> for RxError {
+    fn from(e: Error) -> Self {
+        match e {
+            Error::Overrun => RxError::Overrun,
+            Error::Noise => RxError::Noise,
+            Error::Parity => RxError::Parity,
+            // Błąd ramki i ewentualne nowe rodzaje błędów HAL
+            _ => RxError::Framing,
+        }
+    }
+}
+
+/// Kanał DMA nadawania interfejsu `Serial` z HAL (dla USART1 jest to `dma1::C4`)
+pub struct TxChannel<C> {
+    channel: C,
+}
+
+impl<C: Channel> TxChannel<C> {
+    /// Funkcja przygotowująca kanał do przesyłania bajtów do rejestru TDR i włączająca
+    /// żądania DMA nadajnika (DMAT). Przerwanie kanału zgłaszane jest po zakończeniu przesyłania.
+    pub fn new<Usart, Tx, Rx>(serial: &mut Serial<Usart, (Tx, Rx)>, mut channel: C) -> Self
+    where
+        Usart: Instance + Dma,
+        Serial<Usart, (Tx, Rx)>: OnChannel<C>,
+    {
+        // Jedyny dostęp do rejestrów poza HAL, odbiornik nie korzysta z DMA (DMAR pozostaje wyłączony)
+        let usart = unsafe { serial.peripheral() };
+        unsafe { channel.set_peripheral_address(&usart.tdr as *const _ as u32, Increment::Disable) };
+        channel.set_word_size::<u8>();
+        channel.set_direction(Direction::FromMemory);
+        channel.enable_interrupt(dma::Event::TransferComplete);
+        channel.enable_interrupt(dma::Event::TransferError);
+        usart.cr3.modify(|_, w| w.dmat().enabled());
+        TxChannel { channel }
+    }
+}
+
+impl<C: Channel> TxDma for TxChannel<C> {
+    fn is_busy(&mut self) -> bool {
+        if !self.channel.is_enabled() {
+            return false;
+        }
+        // Po błędzie przesyłania reszta bufora przepada, kanał jest zwalniany tak jak po zakończeniu
+        if self.channel.is_event_triggered(dma::Event::TransferComplete)
+            || self.channel.is_event_triggered(dma::Event::TransferError)
+        {
+            self.channel.disable();
+            self.channel.clear_events();
+            return false;
+        }
+        true
+    }
+
+    unsafe fn start(&mut self, data: *const u8, len: usize) {
+        // Zapis do bufora musi nastąpić przed odczytem przez DMA
+        compiler_fence(Ordering::Release);
+        self.channel.set_memory_address(data as u32, Increment::Enable);
+        self.channel.set_transfer_length(len as u16);
+        self.channel.enable();
+    }
+}
@@ -1,122 +1,432 @@
 #![crate_type = "dylib"]
 #![no_std]
-#![allow(non_snake_case)]
 
-pub use stm32f3xx_hal::pac::usart1;
-use heapless::String;
-use core;
+use core::cmp::min;
+use core::fmt::Write as _;
+use embedded_hal::serial;
+use heapless::spsc::Queue;
 
-/// Komponent opakowujący interfejs USART i dodający do niego funkcjonalność
+mod dma;
+mod error;
+mod frame;
+#[cfg(target_os = "none")]
+mod hal;
+mod line;
+mod rs485;
+mod shell;
+pub use dma::*;
+pub use error::*;
+pub use frame::*;
+#[cfg(target_os = "none")]
+pub use hal::TxChannel;
+pub use line::*;
+pub use rs485::*;
+pub use shell::*;
+pub use protocol::Message;
+
+/// Pojemność bufora odbiorczego (bajty)
+pub const RX_BUFFER: usize = 64;
+/// Pojemność bufora nadawczego (bajty)
+pub const TX_BUFFER: usize = 512;
+// Najdłuższy adres odpowiedzi przy `Reply::Addressed` ("@247 ")
+const ADDRESS_PREFIX: usize = 5;
+
+/// Protokół używany na porcie szeregowym
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+    /// Linie tekstowe (polecenia i `uprintln!`)
+    Text,
+    /// Ramki COBS + CRC-16 z crate `protocol`
+    Binary,
+    /// Slave Modbus RTU z crate `modbus`
+    Modbus,
+}
+
+/// Sterowanie przerwaniami interfejsu szeregowego, którego nie obejmują traity embedded-hal.
+/// Zaimplementowane dla `stm32f3xx_hal::serial::Serial` (USART1-3).
+pub trait SerialInterrupts {
+    /// Przerwanie po odebraniu bajtu (RXNE)
+    fn listen_rx(&mut self, enable: bool);
+    /// Przerwanie, gdy rejestr nadawczy jest pusty (TXE)
+    fn listen_tx(&mut self, enable: bool);
+}
+
+/// Komponent dodający do interfejsu szeregowego bufory pierścieniowe.
+/// Odbiór i nadawanie odbywa się w przerwaniu interfejsu, dzięki czemu zapis
+/// nie czeka na wysłanie kolejnych znaków. `S` to dowolny interfejs z traitami
+/// `embedded_hal::serial::{Read, Write}` i `SerialInterrupts`, np. `Serial` z HAL lub atrapa w testach.
+/// Po `with_dma` bajty wysyła kanał DMA `D` z podwójnego bufora, bez przerwania na każdy znak.
 /// # Examples
-/// ```
+/// ```ignore
 /// use core::fmt::Write;
 /// use usart_1::*;
 /// use init::*;
 /// 
-/// // Pobranie wskaźnika do USART
+/// // Pobranie interfejsu USART1 skonfigurowanego przez HAL
 /// let (usart, _) = init();
 /// // Utworzenie kompomentu
 /// let mut serial = SerialPort::new(usart);
+/// serial.enable_interrupt();
 /// 
 /// // Wysłanie wiadomości "2+2=4"
 /// uprintln!(serial, "2+2 = {}", 2+2);
 /// ```
-pub struct SerialPort {
-    pub usart1: &'static mut usart1::RegisterBlock,
+/// Obsługa przerwania
+/// ```ignore
+/// #[interrupt]
+/// fn USART1_EXTI25() {
+///     cortex_m::interrupt::free(|cs| {
+///         if let Some(ref mut serial) = SERIAL.borrow(cs).borrow_mut().as_mut() {
+///             serial.on_interrupt();
+///         }
+///     });
+/// }
+/// ```
+pub struct SerialPort<S, D = NoDma> {
+    serial: S,
+    rx: Queue<u8, RX_BUFFER>,
+    tx: Queue<u8, TX_BUFFER>,
+    // nadawanie przez DMA, None - nadawanie w przerwaniu TXE z bufora `tx`
+    dma: Option<DoubleBuffer<D>>,
+    // przerwanie TXE jest włączone, gdy w buforze TX są bajty do wysłania
+    tx_active: bool,
+    rx_dropped: u32,
+    tx_dropped: u32,
+    rx_errors: RxErrors,
+    // błąd odbioru od ostatniego `take_rx_error`
+    rx_error: bool,
+    // kolejny odczytany bajt był odebrany z błędem i jest pomijany
+    discard: bool,
+    protocol: Protocol,
+    reply: Reply,
+    // kolejny wysyłany bajt zaczyna linię (miejsce na adres przy `Reply::Addressed`)
+    line_start: bool,
 }
 
 /// Implementacja interfejsu Write dla komponentu SerialPort
-impl core::fmt::Write for SerialPort {
-    // nadpisanie funkcji write_str
+impl<S, D> core::fmt::Write for SerialPort<S, D>
+where
+    S: serial::Read<u8> + serial::Write<u8> + SerialInterrupts,
+    RxError: From<<S as serial::Read<u8>>::Error>,
+    D: TxDma,
+{
+    // nadpisanie funkcji write_str, znaki trafiają do bufora nadawczego
+    // gdy bufor jest pełny nadmiarowe znaki są odrzucane i zwracany jest błąd
     fn write_str(&mut self, s: &str) -> core::fmt::Result {
-        // pętla po wszystkich znakach w stringu
-        for c in s.bytes() {
-            // oczekiwanie na moment kidy rejestr TXE zostanie wyłączony
-            while self.usart1.isr.read().txe().bit_is_clear() {}
-            // wysłanie znaku
-            self.usart1.tdr.write(|w| w.tdr().bits(c as u16));
-        }
-        Ok(())
+        self.write_bytes(s.as_bytes())
     }
 }
 
-
-impl SerialPort {
+impl<S> SerialPort<S> {
     /// # Examples
-    /// ```
+    /// ```ignore
     /// use usart_1::*;
     /// use init::*;
     /// 
-    /// Pobieranie wskaźnika do USART
+    /// // Pobranie interfejsu USART1
     /// let (usart, _) = init();
     /// // Utworzenie kompomentu
     /// let mut serial = SerialPort::new(usart);
     /// ```
-    pub fn new(usart1: &'static mut usart1::RegisterBlock) -> Self {
-        SerialPort { usart1 }
+    pub fn new(serial: S) -> Self {
+        SerialPort {
+            serial,
+            rx: Queue::new(),
+            tx: Queue::new(),
+            dma: None,
+            tx_active: false,
+            rx_dropped: 0,
+            tx_dropped: 0,
+            rx_errors: RxErrors::default(),
+            rx_error: false,
+            discard: false,
+            protocol: Protocol::Text,
+            reply: Reply::Plain,
+            line_start: true,
+        }
     }
-    
-    /// Funkcja odczytująca znak z bufora RX
+
+    /// Funkcja przełączająca nadawanie na kanał DMA. Bufory muszą być statyczne,
+    /// bo DMA czyta je niezależnie od programu, np. z `cortex_m::singleton!`.
     /// # Examples
+    /// ```ignore
+    /// let buffers = cortex_m::singleton!(: DmaBuffers = [[0; DMA_BUFFER]; 2]).unwrap();
+    /// let channel = TxChannel::new(&mut usart, usart_dma);
+    /// let mut serial = SerialPort::new(usart).with_dma(channel, buffers);
     /// ```
-    /// let c = serial.read();
-    /// ```
-    pub fn read(&mut self) -> char {
-        char::from(self.usart1.rdr.read().rdr().bits() as u8)
+    pub fn with_dma<D: TxDma>(self, channel: D, buffers: &'static mut DmaBuffers) -> SerialPort<S, D> {
+        SerialPort {
+            serial: self.serial,
+            rx: self.rx,
+            tx: self.tx,
+            dma: Some(DoubleBuffer::new(channel, buffers)),
+            tx_active: self.tx_active,
+            rx_dropped: self.rx_dropped,
+            tx_dropped: self.tx_dropped,
+            rx_errors: self.rx_errors,
+            rx_error: self.rx_error,
+            discard: self.discard,
+            protocol: self.protocol,
+            reply: self.reply,
+            line_start: self.line_start,
+        }
+    }
+}
+
+impl<S, D> SerialPort<S, D>
+where
+    S: serial::Read<u8> + serial::Write<u8> + SerialInterrupts,
+    RxError: From<<S as serial::Read<u8>>::Error>,
+    D: TxDma,
+{
+    /// Funkcja zwracająca interfejs, np. do zmiany jego konfiguracji
+    pub fn free(self) -> S {
+        self.serial
     }
 
-    /// Funkcja odczutująca 10 znaków z bufora RX
-    /// <b> Uwaga! </b> Funkcja ta blockuje wątek aż do momentu odebrania 10 znaków lub odebrania znaaku ';'
+    /// Dostęp do interfejsu bez zwalniania portu, np. do zmiany prędkości.
+    /// Przed zmianą konfiguracji należy wywołać `flush`.
+    pub fn interface(&mut self) -> &mut S {
+        &mut self.serial
+    }
+
+    /// Funkcja obsługująca przerwanie: przenosi odebrane znaki do bufora RX
+    /// oraz wysyła kolejne znaki z bufora TX. Wywoływana w przerwaniu interfejsu.
+    /// Błędy odbioru są zliczane, a ich flagi czyszczone przez `read` interfejsu,
+    /// więc przerwanie nie zostaje zgłoszone ponownie, a odbiornik działa dalej.
+    pub fn on_interrupt(&mut self) {
+        loop {
+            match self.serial.read() {
+                Ok(byte) => {
+                    if core::mem::take(&mut self.discard) {
+                        continue;
+                    }
+                    if self.rx.enqueue(byte).is_err() {
+                        self.rx_dropped = self.rx_dropped.wrapping_add(1);
+                    }
+                }
+                Err(nb::Error::WouldBlock) => {
+                    self.discard = false;
+                    break;
+                }
+                Err(nb::Error::Other(e)) => {
+                    let error = RxError::from(e);
+                    self.rx_errors.count(error);
+                    self.rx_error = true;
+                    // Przy błędzie ramki, szumu i parzystości przekłamany bajt zostaje w rejestrze
+                    // danych i jest zwracany przez kolejny `read`. Przy przepełnieniu HAL sam go odrzuca.
+                    self.discard = error != RxError::Overrun;
+                }
+            }
+        }
+        if self.tx_active {
+            while let Some(&byte) = self.tx.peek() {
+                if self.serial.write(byte).is_err() {
+                    return;
+                }
+                self.tx.dequeue();
+            }
+            // bufor pusty - wyłączenie przerwania TXE do czasu kolejnego zapisu
+            self.tx_active = false;
+            self.serial.listen_tx(false);
+        }
+    }
+
+    /// Funkcja obsługująca przerwanie kanału DMA po zakończeniu przesyłania:
+    /// wysyła bufor wypełniony w tym czasie. Wywoływana w przerwaniu kanału (np. DMA1_CH4).
+    pub fn on_dma_interrupt(&mut self) {
+        if let Some(dma) = &mut self.dma {
+            dma.start();
+        }
+    }
+
+    /// Funkcja zapisująca bajty do bufora TX (lub bufora DMA), zwraca błąd gdy część bajtów się nie zmieściła
     /// # Examples
+    /// ```ignore
+    /// serial.write_bytes(b"OK\n").ok();
     /// ```
-    /// let command = serial.block_readln();
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> core::fmt::Result {
+        match self.reply {
+            Reply::Plain => self.write_raw(bytes),
+            Reply::Silent => Ok(()),
+            Reply::Addressed(address) => {
+                for line in bytes.split_inclusive(|&b| b == b'\n') {
+                    if self.line_start {
+                        let mut prefix: heapless::String<ADDRESS_PREFIX> = heapless::String::new();
+                        write!(prefix, "@{} ", address)?;
+                        self.write_raw(prefix.as_bytes())?;
+                    }
+                    self.write_raw(line)?;
+                    self.line_start = line.ends_with(b"\n");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Funkcja zapisująca tylko tyle początkowych bajtów, ile zmieści się w buforze TX (lub buforach DMA),
+    /// i zwracająca ich liczbę - nic nie jest odrzucane. Odpowiedź dłuższa niż bufory wysyłana jest
+    /// w kolejnych wywołaniach, między którymi przerwania (TXE, DMA) opróżniają bufory.
+    /// # Examples
+    /// ```ignore
+    /// let mut rest = HELP.as_bytes();
+    /// while !rest.is_empty() {
+    ///     // Przerwania są włączone między sekcjami krytycznymi
+    ///     let n = with_serial(|serial| serial.write_some(rest));
+    ///     rest = &rest[n..];
+    /// }
     /// ```
-    pub fn block_readln(&mut self) -> String<18> {
-        let mut buf: String<18> = String::new();
-        loop {
-            // oczekiwanie na moment rejestr RXNE zostanie wyłączony
-            //set timeout
-            while self.usart1.isr.read().rxne().bit_is_clear() {}
-            // pobranie znaku
-            let c = self.read();
-            // sprawdzenie czy znak jest konca wiadomości, jeśli nie to dodaję znak do bufora
-            if c == ';' {
+    pub fn write_some(&mut self, bytes: &[u8]) -> usize {
+        // Co najwyżej jedna linia, aby przy `Reply::Addressed` dopisać najwyżej jeden adres
+        let line = bytes.iter().position(|&b| b == b'\n').map_or(bytes.len(), |i| i + 1);
+        let prefix = match self.reply {
+            Reply::Addressed(_) if self.line_start => ADDRESS_PREFIX,
+            _ => 0,
+        };
+        let n = min(self.tx_room().saturating_sub(prefix), line);
+        if n > 0 {
+            self.write_bytes(&bytes[..n]).ok();
+        }
+        n
+    }
+
+    /// Liczba bajtów, które można zapisać bez odrzucania
+    pub fn tx_room(&mut self) -> usize {
+        match &mut self.dma {
+            Some(dma) => dma.room(),
+            None => self.tx.capacity() - self.tx.len(),
+        }
+    }
+
+    /// Adresat kolejnych odpowiedzi (magistrala RS-485), od nowej linii
+    pub fn set_reply(&mut self, reply: Reply) {
+        self.reply = reply;
+        self.line_start = true;
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) -> core::fmt::Result {
+        if let Some(dma) = &mut self.dma {
+            let written = dma.write(bytes);
+            if written < bytes.len() {
+                self.tx_dropped = self.tx_dropped.wrapping_add((bytes.len() - written) as u32);
+                return Err(core::fmt::Error);
+            }
+            return Ok(());
+        }
+        let mut result = Ok(());
+        for (i, byte) in bytes.iter().enumerate() {
+            if self.tx.enqueue(*byte).is_err() {
+                self.tx_dropped = self.tx_dropped.wrapping_add((bytes.len() - i) as u32);
+                result = Err(core::fmt::Error);
                 break;
             }
-            buf.push(c).ok();
         }
-        buf
+        // włączenie przerwania TXE rozpoczyna wysyłanie
+        if !self.tx_active && !self.tx.is_empty() {
+            self.tx_active = true;
+            self.serial.listen_tx(true);
+        }
+        result
     }
 
-    /// Funkcja aktywująca perzerwanie USART1_EXTI25
-    pub fn enable_interrupt(&mut self) {
-        self.usart1.cr1.modify(|_, w| w.rxneie().set_bit());
+    /// Funkcja kodująca wiadomość protokołu binarnego i zapisująca ramkę do bufora TX
+    /// # Examples
+    /// ```ignore
+    /// serial.send_message(&Message::Reading(Reading { temperature, humidity })).ok();
+    /// ```
+    pub fn send_message(&mut self, message: &Message) -> core::fmt::Result {
+        let mut buf = [0u8; protocol::MAX_FRAME];
+        let frame = protocol::encode_frame(message, &mut buf).map_err(|_| core::fmt::Error)?;
+        self.write_bytes(frame)
     }
 
-    /// Funkcja dezaktywująca perzerwanie USART1_EXTI25
-    pub fn clear_interrupt(&mut self) {
-        self.usart1.cr1.modify(|_, w| w.rxneie().clear_bit());
-        self.usart1.cr1.modify(|_, w| w.tcie().clear_bit());
+    /// Aktualny protokół
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
     }
 
-    /// Funkcja sprawdzająca czy zostały wysłane dane
-    pub fn ReciveDataRegisterNotEmpty(&mut self) -> bool {
-        self.usart1.cr1.read().rxneie().bit_is_set()
+    /// Zmiana protokołu, odebrane bajty należy kierować do LineReader, FrameReader lub RtuReceiver
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
     }
 
-    /// Funkcja resetująca rejest sprawdzający czy zostały wysłane dane
-    pub fn resetReciveDataRegisterNotEmpty(&mut self) {
-        self.usart1.cr1.modify(|_, w| w.rxneie().clear_bit());
+    /// Funkcja odczytująca bajt z bufora RX
+    pub fn read_byte(&mut self) -> Option<u8> {
+        self.rx.dequeue()
     }
 
-    /// Funkcja włączająca flagę zakonczenia transmisji
-    pub fn setTransmitionComplete(&mut self) {
-        self.usart1.cr1.modify(|_, w| w.tcie().set_bit());
+    /// Funkcja odczytująca znak z bufora RX
+    /// # Examples
+    /// ```ignore
+    /// if let Some(c) = serial.read() {
+    ///     uprintln!(serial, "Got {}", c);
+    /// }
+    /// ```
+    pub fn read(&mut self) -> Option<char> {
+        self.read_byte().map(char::from)
     }
 
-    /// Funkcja sprawdzająca stan flagi zakończenia transmisji
-    pub fn TransmitionComplete(&mut self) -> bool {
-        self.usart1.cr1.read().tcie().bit_is_set()
+    /// Funkcja czekająca na wysłanie całej zawartości bufora TX
+    /// <b> Uwaga! </b> Funkcja ta blokuje wątek, można ją wywołać z wyłączonymi przerwaniami.
+    pub fn flush(&mut self) {
+        if let Some(dma) = &mut self.dma {
+            while !dma.is_idle() {
+                dma.start();
+            }
+        }
+        while !self.tx.is_empty() {
+            self.on_interrupt();
+        }
+        // oczekiwanie na wysłanie ostatniego znaku z rejestru przesuwnego
+        nb::block!(self.serial.flush()).ok();
+    }
+
+    /// Czy cała zawartość bufora TX (lub buforów DMA) została wysłana, razem z ostatnim znakiem
+    /// z rejestru przesuwnego. W przeciwieństwie do `flush` nie blokuje, więc na koniec nadawania
+    /// można czekać z włączonymi przerwaniami.
+    pub fn tx_idle(&mut self) -> bool {
+        let dma_idle = self.dma.as_mut().is_none_or(|dma| dma.is_idle());
+        dma_idle && self.tx.is_empty() && self.serial.flush().is_ok()
+    }
+
+    /// Liczba bajtów odrzuconych z powodu przepełnienia bufora RX
+    pub fn rx_dropped(&self) -> u32 {
+        self.rx_dropped
+    }
+
+    /// Liczba bajtów odrzuconych z powodu przepełnienia bufora TX
+    pub fn tx_dropped(&self) -> u32 {
+        self.tx_dropped
+    }
+
+    /// Liczniki błędów odbioru zgłoszonych przez interfejs
+    pub fn rx_errors(&self) -> RxErrors {
+        self.rx_errors
+    }
+
+    /// Funkcja zwracająca, czy od poprzedniego wywołania wystąpił błąd odbioru.
+    /// Pozwala odrzucić niedokończoną linię, w której brakuje znaku (`LineReader::discard`).
+    pub fn take_rx_error(&mut self) -> bool {
+        core::mem::take(&mut self.rx_error)
+    }
+
+    /// Wyzerowanie liczników błędów odbioru i odrzuconych bajtów
+    pub fn clear_errors(&mut self) {
+        self.rx_errors = RxErrors::default();
+        self.rx_dropped = 0;
+        self.tx_dropped = 0;
+    }
+
+    /// Funkcja aktywująca przerwanie odbioru danych
+    pub fn enable_interrupt(&mut self) {
+        self.serial.listen_rx(true);
+    }
+
+    /// Funkcja dezaktywująca przerwania odbioru i nadawania
+    pub fn clear_interrupt(&mut self) {
+        self.serial.listen_rx(false);
+        self.serial.listen_tx(false);
+        self.tx_active = false;
     }
 }
 
@@ -130,7 +440,7 @@ macro_rules! uprint {
 
 /// Makro do wysłania wiadomości do USART z nową linijką i konkatenacją
 /// # Examples
-/// ```
+/// ```ignore
 /// // Biblioteka wymaga do konatenacji
 /// use core::fmt::Write;
 /// // Przyukład użycia makra
@@ -145,4 +455,304 @@ macro_rules! uprintln {
     ($serial:expr, $fmt:expr, $($arg:tt)*) => {
         usart_1::uprint!($serial, concat!($fmt, "\n"), $($arg)*)
     };
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use core::convert::Infallible;
+    use core::fmt::Write;
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    /// Atrapa interfejsu: `incoming` to bajty do odebrania lub błędy odbioru, `sent` to bajty wysłane,
+    /// rejestr nadawczy przyjmuje `tx_room` bajtów na jedno przerwanie
+    #[derive(Default)]
+    struct Mock {
+        incoming: VecDeque<Result<u8, RxError>>,
+        sent: Vec<u8>,
+        tx_room: usize,
+        rx_listening: bool,
+        tx_listening: bool,
+    }
+
+    impl serial::Read<u8> for Mock {
+        type Error = RxError;
+        fn read(&mut self) -> nb::Result<u8, RxError> {
+            self.incoming.pop_front().ok_or(nb::Error::WouldBlock)?.map_err(nb::Error::Other)
+        }
+    }
+
+    impl serial::Write<u8> for Mock {
+        type Error = Infallible;
+        fn write(&mut self, byte: u8) -> nb::Result<(), Infallible> {
+            if self.tx_room == 0 {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.tx_room -= 1;
+            self.sent.push(byte);
+            Ok(())
+        }
+        fn flush(&mut self) -> nb::Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    impl SerialInterrupts for Mock {
+        fn listen_rx(&mut self, enable: bool) {
+            self.rx_listening = enable;
+        }
+        fn listen_tx(&mut self, enable: bool) {
+            self.tx_listening = enable;
+        }
+    }
+
+    fn port() -> SerialPort<Mock> {
+        SerialPort::new(Mock::default())
+    }
+
+    fn receive(serial: &mut SerialPort<Mock>, bytes: &[u8]) {
+        serial.serial.incoming.extend(bytes.iter().map(|&b| Ok(b)));
+    }
+
+    #[test]
+    fn sends_buffered_bytes_from_the_interrupt() {
+        let mut serial = port();
+        uprint!(serial, "T: {}", 21);
+        assert!(serial.serial.tx_listening);
+        assert!(serial.serial.sent.is_empty());
+
+        serial.serial.tx_room = 3;
+        serial.on_interrupt();
+        assert_eq!(serial.serial.sent, b"T: ");
+        assert!(serial.serial.tx_listening);
+
+        serial.serial.tx_room = 10;
+        serial.on_interrupt();
+        assert_eq!(serial.serial.sent, b"T: 21");
+        assert!(!serial.serial.tx_listening);
+    }
+
+    #[test]
+    fn receives_bytes_into_the_rx_buffer() {
+        let mut serial = port();
+        serial.enable_interrupt();
+        assert!(serial.serial.rx_listening);
+        receive(&mut serial, b"g\n");
+        serial.on_interrupt();
+        assert_eq!(serial.read(), Some('g'));
+        assert_eq!(serial.read_byte(), Some(b'\n'));
+        assert_eq!(serial.read_byte(), None);
+    }
+
+    #[test]
+    fn counts_dropped_bytes() {
+        let mut serial = port();
+        receive(&mut serial, &[0; RX_BUFFER + 5]);
+        serial.on_interrupt();
+        // kolejka spsc mieści N - 1 elementów
+        assert_eq!(serial.rx_dropped(), 6);
+
+        let long = [b'x'; TX_BUFFER + 10];
+        assert!(serial.write_bytes(&long).is_err());
+        assert_eq!(serial.tx_dropped(), 11);
+    }
+
+    #[test]
+    fn flush_sends_everything() {
+        let mut serial = port();
+        serial.serial.tx_room = usize::MAX;
+        serial.write_bytes(b"OK reset\n").unwrap();
+        serial.flush();
+        assert_eq!(serial.serial.sent, b"OK reset\n");
+        assert!(!serial.serial.tx_listening);
+    }
+
+    #[test]
+    fn tx_idle_after_interrupts_send_everything() {
+        let mut serial = port();
+        assert!(serial.tx_idle());
+        serial.write_bytes(b"OK serial 1200\n").unwrap();
+        assert!(!serial.tx_idle());
+        serial.serial.tx_room = usize::MAX;
+        serial.on_interrupt();
+        assert!(serial.tx_idle());
+
+        let mut serial = dma_port();
+        serial.write_bytes(b"OK rs485 on\n").unwrap();
+        assert!(!serial.tx_idle());
+        serial.on_dma_interrupt();
+        serial.on_dma_interrupt();
+        assert!(serial.tx_idle());
+    }
+
+    #[test]
+    fn counts_receive_errors_and_drops_corrupted_bytes() {
+        let mut serial = port();
+        receive(&mut serial, b"ge");
+        serial.serial.incoming.push_back(Err(RxError::Framing));
+        // bajt odebrany z błędem ramki
+        receive(&mut serial, b"\xff");
+        serial.serial.incoming.push_back(Err(RxError::Overrun));
+        serial.serial.incoming.push_back(Err(RxError::Noise));
+        receive(&mut serial, b"?t\n");
+        serial.on_interrupt();
+
+        let mut received = Vec::new();
+        while let Some(byte) = serial.read_byte() {
+            received.push(byte);
+        }
+        assert_eq!(received, b"get\n");
+        assert_eq!(serial.rx_errors(), RxErrors { overrun: 1, framing: 1, noise: 1, parity: 0 });
+        assert_eq!(serial.rx_errors().total(), 3);
+        assert!(serial.take_rx_error());
+        assert!(!serial.take_rx_error());
+
+        serial.clear_errors();
+        assert_eq!(serial.rx_errors(), RxErrors::default());
+    }
+
+    #[test]
+    fn line_with_receive_error_is_discarded() {
+        let mut lines = LineReader::new();
+        for &byte in b"get bo" {
+            lines.push(byte, 0);
+        }
+        lines.discard();
+        for &byte in b"ds\nhelp\n" {
+            lines.push(byte, 0);
+        }
+        assert_eq!(lines.next_line(), Some(Err(LineError::Corrupted)));
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "help");
+        // błąd między liniami nie odrzuca kolejnej linii
+        lines.discard();
+        lines.push(b'g', 0);
+        lines.push(b'\n', 0);
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "g");
+    }
+
+    /// Atrapa kanału DMA: przesyłanie kończy się przy drugim sprawdzeniu `is_busy`,
+    /// które kasuje flagę przerwania tak jak `TxChannel`
+    #[derive(Default)]
+    struct MockDma {
+        sent: Vec<u8>,
+        polls_left: usize,
+        // Flaga zakończenia przesyłania niepotwierdzona przez `is_busy` (przerwanie zgłaszane ponownie)
+        flag_pending: bool,
+    }
+
+    impl TxDma for MockDma {
+        fn is_busy(&mut self) -> bool {
+            if self.polls_left == 0 {
+                self.flag_pending = false;
+                return false;
+            }
+            self.polls_left -= 1;
+            true
+        }
+
+        unsafe fn start(&mut self, data: *const u8, len: usize) {
+            assert!(!self.is_busy());
+            self.sent.extend_from_slice(core::slice::from_raw_parts(data, len));
+            self.polls_left = 2;
+            self.flag_pending = true;
+        }
+    }
+
+    fn dma_port() -> SerialPort<Mock, MockDma> {
+        let buffers = std::boxed::Box::leak(std::boxed::Box::new([[0; DMA_BUFFER]; 2]));
+        port().with_dma(MockDma::default(), buffers)
+    }
+
+    fn dma_sent(serial: &mut SerialPort<Mock, MockDma>) -> &[u8] {
+        &serial.dma.as_mut().unwrap().channel_mut().sent
+    }
+
+    #[test]
+    fn dma_sends_from_double_buffer() {
+        let mut serial = dma_port();
+        serial.write_bytes(b"T: 21").unwrap();
+        // pierwszy zapis trafia od razu do DMA, kolejne czekają w drugim buforze
+        assert_eq!(dma_sent(&mut serial), b"T: 21");
+        serial.write_bytes(b", H: ").unwrap();
+        serial.write_bytes(b"40").unwrap();
+        assert_eq!(dma_sent(&mut serial), b"T: 21");
+        assert!(!serial.serial.tx_listening);
+
+        serial.on_dma_interrupt();
+        serial.on_dma_interrupt();
+        assert_eq!(dma_sent(&mut serial), b"T: 21, H: 40");
+    }
+
+    #[test]
+    fn dma_interrupt_clears_flags_with_an_empty_fill_buffer() {
+        let mut serial = dma_port();
+        serial.write_bytes(b"OK\n").unwrap();
+        serial.on_dma_interrupt();
+        serial.on_dma_interrupt();
+        // przesyłanie zakończone, nic więcej do wysłania - flagi muszą zostać skasowane
+        serial.on_dma_interrupt();
+        let channel = serial.dma.as_mut().unwrap().channel_mut();
+        assert!(!channel.flag_pending);
+        assert_eq!(channel.sent, b"OK\n");
+    }
+
+    #[test]
+    fn dma_drops_bytes_when_both_buffers_are_full() {
+        let mut serial = dma_port();
+        let long = [b'x'; 3 * DMA_BUFFER];
+        assert!(serial.write_bytes(&long).is_err());
+        // jeden bufor w trakcie wysyłania i jeden pełny
+        assert_eq!(serial.tx_dropped(), DMA_BUFFER as u32);
+        serial.flush();
+        assert_eq!(dma_sent(&mut serial).len(), 2 * DMA_BUFFER);
+    }
+
+    #[test]
+    fn long_reply_is_written_in_parts_without_drops() {
+        let reply: Vec<u8> = (0..2 * DMA_BUFFER + 100).map(|i| b'a' + (i % 26) as u8).collect();
+        let mut serial = dma_port();
+        let mut rest = &reply[..];
+        while !rest.is_empty() {
+            let n = serial.write_some(rest);
+            rest = &rest[n..];
+            // przerwanie kanału DMA między kolejnymi częściami
+            serial.on_dma_interrupt();
+        }
+        serial.flush();
+        assert_eq!(serial.tx_dropped(), 0);
+        assert_eq!(dma_sent(&mut serial), &reply[..]);
+
+        // bez DMA bufor pierścieniowy opróżniany jest w przerwaniu TXE
+        let mut serial = port();
+        serial.set_reply(Reply::Addressed(247));
+        let mut rest = &reply[..];
+        while !rest.is_empty() {
+            let n = serial.write_some(rest);
+            rest = &rest[n..];
+            serial.serial.tx_room = 100;
+            serial.on_interrupt();
+        }
+        serial.serial.tx_room = usize::MAX;
+        serial.flush();
+        assert_eq!(serial.tx_dropped(), 0);
+        assert_eq!(serial.serial.sent[..5], *b"@247 ");
+        assert_eq!(serial.serial.sent[5..], reply[..]);
+    }
+
+    #[test]
+    fn prefixes_or_silences_replies() {
+        let mut serial = port();
+        serial.serial.tx_room = usize::MAX;
+        serial.set_reply(Reply::Addressed(17));
+        uprint!(serial, "OK reading temp={}", 23.5);
+        uprint!(serial, "\nsecond\n");
+        serial.set_reply(Reply::Silent);
+        uprint!(serial, "OK interval 2000\n");
+        serial.set_reply(Reply::Plain);
+        uprint!(serial, "Temp: 23.5\n");
+        serial.flush();
+        assert_eq!(serial.serial.sent, b"@17 OK reading temp=23.5\n@17 second\nTemp: 23.5\n");
+    }
+}
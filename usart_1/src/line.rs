@@ -0,0 +1,292 @@
+use core::fmt;
+use heapless::spsc::Queue;
+use heapless::String;
+
+/// Maksymalna długość linii polecenia (bez znaku końca linii)
+pub const LINE_LENGTH: usize = 48;
+/// Maksymalny odstęp między znakami jednej linii, po nim niedokończona linia jest odrzucana
+pub const CHAR_TIMEOUT_MS: u32 = 5_000;
+// Liczba kompletnych linii oczekujących na obsługę (pojemność kolejki to N - 1)
+const PENDING_LINES: usize = 5;
+
+/// Linia polecenia bez znaku końca linii
+pub type Line = String<LINE_LENGTH>;
+
+/// Błędy składania linii
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineError {
+    /// Linia dłuższa niż LINE_LENGTH, cała linia została odrzucona
+    TooLong,
+    /// Przerwa między znakami dłuższa niż CHAR_TIMEOUT_MS
+    Timeout,
+    /// Błąd odbioru (przepełnienie, ramka, szum) w trakcie linii, cała linia została odrzucona
+    Corrupted,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineError::TooLong => write!(f, "line too long (max {} chars)", LINE_LENGTH),
+            LineError::Timeout => write!(f, "no terminator within {} ms", CHAR_TIMEOUT_MS),
+            LineError::Corrupted => write!(f, "receive error, line discarded"),
+        }
+    }
+}
+
+/// Komponent składający odebrane bajty w linie zakończone znakiem '\n', '\r' lub ';'.
+/// Bajty dodawane są w przerwaniu, a kompletne linie odbierane w pętli głównej,
+/// dzięki czemu przerwanie nigdy nie czeka na kolejne znaki.
+/// # Examples
+/// ```
+/// use usart_1::LineReader;
+///
+/// let mut lines = LineReader::new();
+/// // W przerwaniu USART1_EXTI25, czas z zegara urządzenia w ms
+/// for byte in b"get-bounds\nver" {
+///     lines.push(*byte, 100);
+/// }
+/// // W pętli głównej, niedokończona linia jest odrzucana po CHAR_TIMEOUT_MS
+/// lines.poll(100 + usart_1::CHAR_TIMEOUT_MS);
+/// while let Some(line) = lines.next_line() {
+///     match line {
+///         Ok(line) => assert_eq!(line, "get-bounds"),
+///         Err(e) => println!("Command error: {}", e),
+///     }
+/// }
+/// ```
+pub struct LineReader {
+    current: Line,
+    // Błąd zgłaszany po znaku końca linii, do tego czasu reszta linii jest pomijana
+    skip: Option<LineError>,
+    last_byte_ms: u32,
+    pending: Queue<Result<Line, LineError>, PENDING_LINES>,
+    dropped: u32,
+}
+
+impl Default for LineReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineReader {
+    pub const fn new() -> Self {
+        LineReader {
+            current: String::new(),
+            skip: None,
+            last_byte_ms: 0,
+            pending: Queue::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Funkcja dodająca odebrany bajt, `now_ms` to aktualny czas z zegara monotonicznego
+    pub fn push(&mut self, byte: u8, now_ms: u32) {
+        self.poll(now_ms);
+        self.last_byte_ms = now_ms;
+        match byte {
+            b'\n' | b'\r' | b';' => self.finish(),
+            _ if self.skip.is_some() => {}
+            _ => {
+                if self.current.push(char::from(byte)).is_err() {
+                    // reszta linii jest pomijana aż do znaku końca linii
+                    self.skip = Some(LineError::TooLong);
+                    self.current.clear();
+                }
+            }
+        }
+    }
+
+    /// Funkcja odrzucająca niedokończoną linię po błędzie odbioru, bo brakuje w niej znaku
+    /// lub jest on przekłamany. Reszta linii jest pomijana, a po znaku końca linii
+    /// zgłaszany jest `LineError::Corrupted`. Błąd między liniami niczego nie odrzuca.
+    pub fn discard(&mut self) {
+        if !self.current.is_empty() {
+            self.current.clear();
+            self.skip = Some(LineError::Corrupted);
+        }
+    }
+
+    /// Funkcja odrzucająca niedokończoną linię bez zgłaszania błędu, np. przy zmianie protokołu.
+    /// Linie oczekujące na obsługę pozostają w kolejce.
+    pub fn reset(&mut self) {
+        self.current.clear();
+        self.skip = None;
+    }
+
+    /// Funkcja odrzucająca niedokończoną linię i wszystkie linie oczekujące na obsługę,
+    /// np. odebrane z poprzednimi parametrami portu
+    pub fn clear(&mut self) {
+        self.reset();
+        while self.pending.dequeue().is_some() {}
+    }
+
+    /// Funkcja odrzucająca niedokończoną linię po przekroczeniu CHAR_TIMEOUT_MS,
+    /// wywoływana również w pętli głównej, gdy nie przychodzą nowe znaki
+    pub fn poll(&mut self, now_ms: u32) {
+        let in_progress = !self.current.is_empty() || self.skip.is_some();
+        if in_progress && now_ms.wrapping_sub(self.last_byte_ms) > CHAR_TIMEOUT_MS {
+            self.current.clear();
+            self.skip = None;
+            self.emit(Err(LineError::Timeout));
+        }
+    }
+
+    /// Funkcja zwracająca kolejną kompletną linię lub błąd jej składania
+    pub fn next_line(&mut self) -> Option<Result<Line, LineError>> {
+        self.pending.dequeue()
+    }
+
+    /// Liczba linii odrzuconych, bo kolejka oczekujących linii była pełna
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    fn finish(&mut self) {
+        if let Some(e) = self.skip.take() {
+            self.emit(Err(e));
+        } else if !self.current.is_empty() {
+            // puste linie (np. "\r\n") są pomijane
+            let line = core::mem::take(&mut self.current);
+            self.emit(Ok(line));
+        }
+    }
+
+    fn emit(&mut self, line: Result<Line, LineError>) {
+        if self.pending.enqueue(line).is_err() {
+            self.dropped = self.dropped.wrapping_add(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_str(lines: &mut LineReader, text: &str, now_ms: u32) {
+        for byte in text.bytes() {
+            lines.push(byte, now_ms);
+        }
+    }
+
+    fn next_ok(lines: &mut LineReader) -> Line {
+        lines.next_line().unwrap().unwrap()
+    }
+
+    #[test]
+    fn splits_on_every_terminator_and_skips_empty_lines() {
+        let mut lines = LineReader::new();
+        push_str(&mut lines, "get bounds\r\nhelp;;status\n", 0);
+        assert_eq!(next_ok(&mut lines), "get bounds");
+        assert_eq!(next_ok(&mut lines), "help");
+        assert_eq!(next_ok(&mut lines), "status");
+        assert!(lines.next_line().is_none());
+    }
+
+    #[test]
+    fn pause_between_chars_reports_timeout() {
+        let mut lines = LineReader::new();
+        push_str(&mut lines, "get", 1_000);
+        lines.poll(1_000 + CHAR_TIMEOUT_MS);
+        assert!(lines.next_line().is_none());
+        lines.poll(1_001 + CHAR_TIMEOUT_MS);
+        assert_eq!(lines.next_line(), Some(Err(LineError::Timeout)));
+
+        // Po przekroczeniu czasu niedokończona linia jest odrzucona, a kolejna składana od nowa
+        push_str(&mut lines, "help\r", 10_000);
+        assert_eq!(next_ok(&mut lines), "help");
+        assert!(lines.next_line().is_none());
+    }
+
+    #[test]
+    fn idle_reader_never_times_out() {
+        let mut lines = LineReader::new();
+        push_str(&mut lines, "help\r", 0);
+        lines.poll(10 * CHAR_TIMEOUT_MS);
+        assert_eq!(next_ok(&mut lines), "help");
+        assert!(lines.next_line().is_none());
+    }
+
+    #[test]
+    fn long_line_is_skipped_up_to_terminator() {
+        let mut lines = LineReader::new();
+        for _ in 0..LINE_LENGTH + 10 {
+            lines.push(b'x', 0);
+        }
+        // Błąd zgłaszany dopiero po znaku końca linii, reszta linii nie tworzy nowej
+        assert!(lines.next_line().is_none());
+        push_str(&mut lines, "\rhelp\r", 0);
+        assert_eq!(lines.next_line(), Some(Err(LineError::TooLong)));
+        assert_eq!(next_ok(&mut lines), "help");
+
+        // Linia o długości dokładnie LINE_LENGTH mieści się w buforze
+        for _ in 0..LINE_LENGTH {
+            lines.push(b'y', 0);
+        }
+        lines.push(b'\n', 0);
+        assert_eq!(next_ok(&mut lines).len(), LINE_LENGTH);
+    }
+
+    #[test]
+    fn timeout_survives_clock_wraparound() {
+        let mut lines = LineReader::new();
+        push_str(&mut lines, "get", u32::MAX - 10);
+        // Po przepełnieniu licznika czas nadal liczony jest od ostatniego znaku
+        push_str(&mut lines, " bounds\r", 100);
+        assert_eq!(next_ok(&mut lines), "get bounds");
+
+        push_str(&mut lines, "get", u32::MAX - 10);
+        lines.poll(CHAR_TIMEOUT_MS);
+        assert_eq!(lines.next_line(), Some(Err(LineError::Timeout)));
+    }
+
+    #[test]
+    fn full_queue_counts_dropped_lines() {
+        let mut lines = LineReader::new();
+        for _ in 0..PENDING_LINES + 1 {
+            push_str(&mut lines, "help\r", 0);
+        }
+        assert_eq!(lines.dropped(), 2);
+        for _ in 0..PENDING_LINES - 1 {
+            assert_eq!(next_ok(&mut lines), "help");
+        }
+        assert!(lines.next_line().is_none());
+
+        // Błędy zajmują miejsce w kolejce tak samo jak linie
+        for _ in 0..PENDING_LINES {
+            push_str(&mut lines, "x", 0);
+            lines.discard();
+            lines.push(b'\r', 0);
+        }
+        assert_eq!(lines.dropped(), 3);
+        assert_eq!(lines.next_line(), Some(Err(LineError::Corrupted)));
+    }
+
+    #[test]
+    fn reset_discards_partial_line_without_error() {
+        let mut lines = LineReader::new();
+        push_str(&mut lines, "help\rget", 0);
+        lines.reset();
+        push_str(&mut lines, "status\r", 0);
+        assert_eq!(next_ok(&mut lines), "help");
+        assert_eq!(next_ok(&mut lines), "status");
+
+        // Pominięta zbyt długa linia nie zgłasza błędu ani przekroczenia czasu po resecie
+        for _ in 0..LINE_LENGTH + 1 {
+            lines.push(b'x', 0);
+        }
+        lines.reset();
+        lines.poll(10 * CHAR_TIMEOUT_MS);
+        assert!(lines.next_line().is_none());
+    }
+
+    #[test]
+    fn clear_discards_pending_lines() {
+        let mut lines = LineReader::new();
+        push_str(&mut lines, "get bounds\rhelp\rget", 0);
+        lines.clear();
+        assert!(lines.next_line().is_none());
+        push_str(&mut lines, "status\r", 0);
+        assert_eq!(next_ok(&mut lines), "status");
+    }
+}
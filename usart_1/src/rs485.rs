@@ -0,0 +1,26 @@
+/// Adresat odpowiedzi na magistrali RS-485, ustawiany na czas obsługi jednego polecenia
+/// # Examples
+/// ```ignore
+/// // Polecenie "@17 get reading" dla urządzenia o adresie 17
+/// serial.set_reply(Reply::Addressed(17));
+/// command::ok(&mut serial, format_args!("reading temp=23.4 hum=45")).ok(); // "@17 OK reading ..."
+/// serial.set_reply(Reply::Plain);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reply {
+    /// Odpowiedzi bez adresu (połączenie punkt-punkt)
+    Plain,
+    /// Każda linia odpowiedzi poprzedzona jest adresem urządzenia: `@17 OK ...`
+    Addressed(u8),
+    /// Odpowiedź nie jest wysyłana (polecenie rozgłoszeniowe lub dla innego urządzenia),
+    /// dzięki czemu urządzenia na magistrali nie nadają jednocześnie
+    Silent,
+}
+
+/// Półdupleksowa magistrala RS-485: nadajnik linii włączany jest sygnałem DE tylko na czas wysyłania.
+/// Zaimplementowane dla `Serial` z HAL przez sprzętowe sterowanie DE interfejsu (USART1: pin PA12),
+/// które zachowuje czasy przełączania niezależnie od przerwań i DMA.
+pub trait DriverEnable {
+    /// Włączenie lub wyłączenie sterowania DE, wywoływane przy pustym buforze nadawczym (`flush`)
+    fn set_driver_enable(&mut self, enable: bool);
+}
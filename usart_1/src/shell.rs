@@ -0,0 +1,271 @@
+use core::fmt::{self, Write};
+use heapless::{Deque, String};
+
+use crate::line::{Line, LineReader};
+
+/// Znak zachęty powłoki
+pub const PROMPT: &str = "> ";
+// Liczba zapamiętanych poleceń
+const HISTORY: usize = 4;
+
+// Stan odczytu sekwencji sterujących terminala (strzałki wysyłane są jako ESC [ A)
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Started,
+    Csi,
+}
+
+/// Interaktywna powłoka dla terminali (PuTTY, minicom): echo znaków, kasowanie
+/// backspace i Ctrl-U, historia poleceń pod strzałkami góra/dół oraz znak zachęty.
+/// Powłoka edytuje linię lokalnie i przekazuje ją do `LineReader` dopiero po Enter.
+/// Wyłączona powłoka przekazuje bajty bez zmian, co jest wymagane przez programy.
+/// # Examples
+/// ```ignore
+/// // W przerwaniu USART1_EXTI25
+/// while let Some(byte) = serial.read_byte() {
+///     shell.push(byte, clock::now_ms(), &mut lines, &mut serial);
+/// }
+/// // W pętli głównej, po odpowiedzi na polecenie
+/// shell.prompt(&mut serial).ok();
+/// ```
+pub struct Shell {
+    enabled: bool,
+    edit: Line,
+    history: Deque<Line, HISTORY>,
+    // Pozycja w historii liczona od najnowszego polecenia
+    browsing: Option<usize>,
+    escape: Escape,
+    // '\n' po '\r' (Enter w trybie CRLF) nie kończy kolejnej, pustej linii
+    after_cr: bool,
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shell {
+    pub const fn new() -> Self {
+        Shell {
+            enabled: false,
+            edit: String::new(),
+            history: Deque::new(),
+            browsing: None,
+            escape: Escape::None,
+            after_cr: false,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Włączenie lub wyłączenie powłoki, edytowana linia jest porzucana
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.edit.clear();
+        self.browsing = None;
+        self.escape = Escape::None;
+    }
+
+    /// Funkcja obsługująca odebrany bajt. Echo i poprawki linii zapisywane są do `echo`.
+    pub fn push<W: Write>(&mut self, byte: u8, now_ms: u32, lines: &mut LineReader, echo: &mut W) {
+        if !self.enabled {
+            lines.push(byte, now_ms);
+            return;
+        }
+        let after_cr = core::mem::replace(&mut self.after_cr, byte == b'\r');
+        match (self.escape, byte) {
+            (Escape::Started, b'[') => self.escape = Escape::Csi,
+            (Escape::Csi, b'A') => {
+                self.escape = Escape::None;
+                self.older(echo);
+            }
+            (Escape::Csi, b'B') => {
+                self.escape = Escape::None;
+                self.newer(echo);
+            }
+            // Pozostałe sekwencje (np. strzałki lewo/prawo) są pomijane
+            (Escape::Started, _) => self.escape = Escape::None,
+            (Escape::Csi, b'0'..=b'9' | b';') => {}
+            (Escape::Csi, _) => self.escape = Escape::None,
+            (Escape::None, 0x1B) => self.escape = Escape::Started,
+            (Escape::None, b'\n') if after_cr => {}
+            (Escape::None, b'\r' | b'\n') => {
+                echo.write_str("\r\n").ok();
+                // Pusta linia nie trafia do obsługi poleceń, więc zachętę wysyła powłoka
+                if !self.submit(now_ms, lines) {
+                    self.prompt(echo).ok();
+                }
+            }
+            // Backspace (terminale wysyłają 0x08 lub 0x7F)
+            (Escape::None, 0x08 | 0x7F) => {
+                if self.edit.pop().is_some() {
+                    echo.write_str("\x08 \x08").ok();
+                }
+            }
+            // Ctrl-U kasuje całą linię
+            (Escape::None, 0x15) => self.replace("", echo),
+            (Escape::None, 0x20..=0x7E) => {
+                if self.edit.push(char::from(byte)).is_ok() {
+                    echo.write_char(char::from(byte)).ok();
+                } else {
+                    // Linia osiągnęła LINE_LENGTH, terminal sygnalizuje to dzwonkiem
+                    echo.write_char('\x07').ok();
+                }
+            }
+            // Pozostałe znaki sterujące są ignorowane
+            (Escape::None, _) => {}
+        }
+    }
+
+    /// Funkcja wysyłająca znak zachęty razem z niedokończoną linią, np. po odpowiedzi
+    /// na polecenie lub po rekordzie telemetrii, który przerwał wpisywanie
+    pub fn prompt<W: Write>(&self, out: &mut W) -> fmt::Result {
+        if self.enabled {
+            out.write_str(PROMPT)?;
+            out.write_str(&self.edit)?;
+        }
+        Ok(())
+    }
+
+    /// Funkcja czyszcząca wiersz z zachętą przed wysłaniem innego tekstu,
+    /// po którym wiersz przywraca `prompt`
+    pub fn clear_line<W: Write>(&self, out: &mut W) -> fmt::Result {
+        if self.enabled {
+            out.write_str("\r\x1b[K")?;
+        }
+        Ok(())
+    }
+
+    // Przekazanie linii do `LineReader`, false gdy linia była pusta
+    fn submit(&mut self, now_ms: u32, lines: &mut LineReader) -> bool {
+        self.browsing = None;
+        let line = core::mem::take(&mut self.edit);
+        if line.trim().is_empty() {
+            return false;
+        }
+        for byte in line.bytes() {
+            lines.push(byte, now_ms);
+        }
+        lines.push(b'\r', now_ms);
+        if self.history.back() != Some(&line) {
+            if self.history.is_full() {
+                self.history.pop_front();
+            }
+            self.history.push_back(line).ok();
+        }
+        true
+    }
+
+    fn older<W: Write>(&mut self, echo: &mut W) {
+        let next = self.browsing.map_or(0, |i| i + 1);
+        if let Some(line) = self.history.iter().rev().nth(next).cloned() {
+            self.browsing = Some(next);
+            self.replace(&line, echo);
+        }
+    }
+
+    fn newer<W: Write>(&mut self, echo: &mut W) {
+        match self.browsing {
+            Some(0) => {
+                self.browsing = None;
+                self.replace("", echo);
+            }
+            Some(i) => {
+                if let Some(line) = self.history.iter().rev().nth(i - 1).cloned() {
+                    self.browsing = Some(i - 1);
+                    self.replace(&line, echo);
+                }
+            }
+            None => {}
+        }
+    }
+
+    // Zastąpienie edytowanej linii i przerysowanie jej od początku wiersza
+    fn replace<W: Write>(&mut self, line: &str, echo: &mut W) {
+        self.edit.clear();
+        self.edit.push_str(line).ok();
+        self.clear_line(echo).ok();
+        self.prompt(echo).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::string::String;
+
+    fn type_bytes(shell: &mut Shell, lines: &mut LineReader, bytes: &[u8]) -> String {
+        let mut echo = String::new();
+        for &byte in bytes {
+            shell.push(byte, 0, lines, &mut echo);
+        }
+        echo
+    }
+
+    fn enabled() -> Shell {
+        let mut shell = Shell::new();
+        shell.set_enabled(true);
+        shell
+    }
+
+    #[test]
+    fn disabled_shell_passes_bytes_through() {
+        let mut shell = Shell::new();
+        let mut lines = LineReader::new();
+        assert_eq!(type_bytes(&mut shell, &mut lines, b"get\x08bounds\r"), "");
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "get\x08bounds");
+    }
+
+    #[test]
+    fn echoes_and_edits_line() {
+        let mut shell = enabled();
+        let mut lines = LineReader::new();
+        let echo = type_bytes(&mut shell, &mut lines, b"get boundz\x7Fs\r\n");
+        assert_eq!(echo, "get boundz\x08 \x08s\r\n");
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "get bounds");
+        assert!(lines.next_line().is_none());
+
+        let echo = type_bytes(&mut shell, &mut lines, b"junk\x15help\r");
+        assert_eq!(echo, "junk\r\x1b[K> help\r\n");
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "help");
+    }
+
+    #[test]
+    fn empty_line_gets_new_prompt() {
+        let mut shell = enabled();
+        let mut lines = LineReader::new();
+        assert_eq!(type_bytes(&mut shell, &mut lines, b"  \r\n"), "  \r\n> ");
+        assert!(lines.next_line().is_none());
+    }
+
+    #[test]
+    fn recalls_history_with_arrows() {
+        let mut shell = enabled();
+        let mut lines = LineReader::new();
+        type_bytes(&mut shell, &mut lines, b"get bounds\rhelp\r");
+        let echo = type_bytes(&mut shell, &mut lines, b"\x1b[A\x1b[A\x1b[B");
+        assert_eq!(echo, "\r\x1b[K> help\r\x1b[K> get bounds\r\x1b[K> help");
+        type_bytes(&mut shell, &mut lines, b"\r");
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "get bounds");
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "help");
+        assert_eq!(lines.next_line().unwrap().unwrap().as_str(), "help");
+    }
+
+    #[test]
+    fn rings_bell_when_line_is_full() {
+        let mut shell = enabled();
+        let mut lines = LineReader::new();
+        let long = [b'x'; crate::line::LINE_LENGTH + 1];
+        let echo = type_bytes(&mut shell, &mut lines, &long);
+        assert!(echo.ends_with("x\x07"));
+        let mut out = String::new();
+        shell.prompt(&mut out).unwrap();
+        assert_eq!(out.len(), PROMPT.len() + crate::line::LINE_LENGTH);
+    }
+}
@@ -0,0 +1,249 @@
+#![crate_type = "dylib"]
+#![no_std]
+
+//! Sterownik pasków diod adresowalnych WS2812/SK6812 (RGB) wykorzystujący SPI.
+//! Każdy bit danych diody jest kodowany jako 4 bity SPI przy częstotliwości 3 MHz:
+//! `0` -> `1000` (0.33us stanu wysokiego), `1` -> `1110` (1us stanu wysokiego).
+//! Kodowanie nie zależy od sprzętu, więc można je testować na komputerze.
+
+use embedded_hal::blocking::spi::Write;
+
+/// Wymagana częstotliwość zegara SPI
+pub const SPI_FREQUENCY_HZ: u32 = 3_000_000;
+/// Liczba bajtów SPI potrzebna do zakodowania jednej diody (24 bity * 4)
+pub const BYTES_PER_LED: usize = 12;
+/// Liczba zerowych bajtów sygnału reset (140 * 8 / 3 MHz = 373us, WS2812B wymaga > 280us)
+pub const RESET_BYTES: usize = 140;
+
+const ZERO: u8 = 0b1000;
+const ONE: u8 = 0b1110;
+
+/// Kolor diody RGB
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const OFF: Rgb = Rgb::new(0, 0, 0);
+    pub const RED: Rgb = Rgb::new(255, 0, 0);
+    pub const GREEN: Rgb = Rgb::new(0, 255, 0);
+    pub const BLUE: Rgb = Rgb::new(0, 0, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    /// Interpolacja liniowa między dwoma kolorami, `t` = 0 daje `self`, `t` = 255 daje `other`
+    /// # Examples
+    /// ```
+    /// use ws2812::Rgb;
+    ///
+    /// // Kolor w połowie drogi między niebieskim a zielonym
+    /// let c = Rgb::BLUE.lerp(Rgb::GREEN, 128);
+    /// assert_eq!(c, Rgb::new(0, 128, 127));
+    /// ```
+    pub fn lerp(self, other: Rgb, t: u8) -> Rgb {
+        let mix = |a: u8, b: u8| -> u8 {
+            let (a, b, t) = (a as u16, b as u16, t as u16);
+            ((a * (255 - t) + b * t + 127) / 255) as u8
+        };
+        Rgb::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    /// Skalowanie jasności, 255 oznacza pełną jasność
+    pub fn scale(self, brightness: u8) -> Rgb {
+        Rgb::OFF.lerp(self, brightness)
+    }
+}
+
+/// Funkcja zwracająca liczbę zapalonych diod wskaźnika słupkowego o długości `len`:
+/// 0 dla `value` od `low` w dół, wszystkie od `high` w górę
+pub fn gauge(value: f32, low: f32, high: f32, len: usize) -> usize {
+    if value.is_nan() || value <= low {
+        return 0;
+    }
+    let scaled = (value - low) / (high - low) * len as f32;
+    let lit = scaled as usize;
+    // Zaokrąglenie w górę: każda wartość powyżej `low` zapala co najmniej jedną diodę
+    let lit = if (lit as f32) < scaled { lit + 1 } else { lit };
+    lit.min(len)
+}
+
+/// Funkcja zwracająca kolor temperatury: gradient od niebieskiego (`cold`)
+/// przez zielony (`optimal`) do czerwonego (`hot` i powyżej)
+pub fn temperature_color(temperature: f32, cold: f32, optimal: f32, hot: f32) -> Rgb {
+    // Położenie temperatury między dwiema granicami jako 0..=255
+    let fraction = |low: f32, high: f32| -> u8 {
+        let t = (temperature - low) / (high - low);
+        (t.clamp(0.0, 1.0) * 255.0) as u8
+    };
+    if temperature >= hot {
+        Rgb::RED
+    } else if temperature >= optimal {
+        Rgb::GREEN.lerp(Rgb::RED, fraction(optimal, hot))
+    } else {
+        Rgb::BLUE.lerp(Rgb::GREEN, fraction(cold, optimal))
+    }
+}
+
+/// Funkcja kodująca jeden bajt koloru na 4 bajty SPI (najstarszy bit pierwszy)
+pub fn encode_byte(byte: u8) -> [u8; 4] {
+    let mut out = [0u8; 4];
+    for (i, chunk) in out.iter_mut().enumerate() {
+        let bit = |n: usize| if byte & (0x80 >> n) != 0 { ONE } else { ZERO };
+        *chunk = (bit(2 * i) << 4) | bit(2 * i + 1);
+    }
+    out
+}
+
+/// Funkcja kodująca kolor diody, WS2812 i SK6812 oczekują kolejności GRB
+pub fn encode(color: Rgb) -> [u8; BYTES_PER_LED] {
+    let mut out = [0u8; BYTES_PER_LED];
+    for (chunk, byte) in out.chunks_mut(4).zip([color.g, color.r, color.b]) {
+        chunk.copy_from_slice(&encode_byte(byte));
+    }
+    out
+}
+
+/// Interfejs paska diod RGB, pozwala podmienić sterownik (np. na atrapę w testach)
+pub trait RgbWrite {
+    type Error;
+
+    /// Wysłanie kolorów wszystkich diod, od pierwszej w łańcuchu
+    fn write(&mut self, colors: &[Rgb]) -> Result<(), Self::Error>;
+}
+
+/// Sterownik WS2812 na dowolnym interfejsie SPI skonfigurowanym na `SPI_FREQUENCY_HZ`.
+/// Wykorzystywana jest tylko linia MOSI.
+/// # Examples
+/// ```ignore
+/// let mut strip = Ws2812::new(spi);
+/// strip.write(&[Rgb::RED, Rgb::GREEN, Rgb::BLUE]).ok();
+/// ```
+pub struct Ws2812<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> Ws2812<SPI>
+where
+    SPI: Write<u8>,
+{
+    pub fn new(spi: SPI) -> Self {
+        Ws2812 { spi }
+    }
+
+    /// Zwolnienie interfejsu SPI
+    pub fn free(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI> RgbWrite for Ws2812<SPI>
+where
+    SPI: Write<u8>,
+{
+    type Error = SPI::Error;
+
+    fn write(&mut self, colors: &[Rgb]) -> Result<(), Self::Error> {
+        for color in colors {
+            self.spi.write(&encode(*color))?;
+        }
+        // Stan niski dłuższy niż czas resetu zatwierdza nowe kolory
+        self.spi.write(&[0u8; RESET_BYTES])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate std;
+    use std::vec::Vec;
+
+    struct MockSpi {
+        written: Vec<u8>,
+    }
+
+    impl Write<u8> for MockSpi {
+        type Error = ();
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.written.extend_from_slice(words);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn encodes_all_zero_and_all_one_bytes() {
+        assert_eq!(encode_byte(0x00), [0x88; 4]);
+        assert_eq!(encode_byte(0xFF), [0xEE; 4]);
+    }
+
+    #[test]
+    fn encodes_msb_first() {
+        // 1010_0101
+        assert_eq!(encode_byte(0xA5), [0xE8, 0xE8, 0x8E, 0x8E]);
+        // 1000_0000
+        assert_eq!(encode_byte(0x80), [0xE8, 0x88, 0x88, 0x88]);
+    }
+
+    #[test]
+    fn encodes_color_in_grb_order() {
+        let out = encode(Rgb::new(0x01, 0x80, 0xFF));
+        assert_eq!(out[0..4], encode_byte(0x80));
+        assert_eq!(out[4..8], encode_byte(0x01));
+        assert_eq!(out[8..12], encode_byte(0xFF));
+    }
+
+    #[test]
+    fn write_sends_leds_then_reset() {
+        let mut strip = Ws2812::new(MockSpi { written: Vec::new() });
+        strip.write(&[Rgb::RED, Rgb::BLUE]).unwrap();
+        let written = strip.free().written;
+        assert_eq!(written.len(), 2 * BYTES_PER_LED + RESET_BYTES);
+        assert_eq!(written[..BYTES_PER_LED], encode(Rgb::RED));
+        assert_eq!(written[BYTES_PER_LED..2 * BYTES_PER_LED], encode(Rgb::BLUE));
+        assert!(written[2 * BYTES_PER_LED..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn lerp_hits_endpoints_and_midpoint() {
+        assert_eq!(Rgb::BLUE.lerp(Rgb::GREEN, 0), Rgb::BLUE);
+        assert_eq!(Rgb::BLUE.lerp(Rgb::GREEN, 255), Rgb::GREEN);
+        assert_eq!(Rgb::BLUE.lerp(Rgb::GREEN, 128), Rgb::new(0, 128, 127));
+    }
+
+    #[test]
+    fn scale_dims_color() {
+        assert_eq!(Rgb::RED.scale(255), Rgb::RED);
+        assert_eq!(Rgb::RED.scale(0), Rgb::OFF);
+        assert_eq!(Rgb::new(200, 100, 0).scale(128), Rgb::new(100, 50, 0));
+    }
+
+    #[test]
+    fn gauge_fills_from_low_to_high() {
+        assert_eq!(gauge(10.0, 10.0, 40.0, 8), 0);
+        assert_eq!(gauge(f32::NAN, 10.0, 40.0, 8), 0);
+        // Każda wartość powyżej `low` zapala co najmniej jedną diodę
+        assert_eq!(gauge(10.1, 10.0, 40.0, 8), 1);
+        assert_eq!(gauge(25.0, 10.0, 40.0, 8), 4);
+        assert_eq!(gauge(25.1, 10.0, 40.0, 8), 5);
+        assert_eq!(gauge(40.0, 10.0, 40.0, 8), 8);
+        assert_eq!(gauge(50.0, 10.0, 40.0, 8), 8);
+    }
+
+    #[test]
+    fn color_goes_from_blue_through_green_to_red() {
+        let color = |temperature| temperature_color(temperature, 10.0, 20.0, 30.0);
+        assert_eq!(color(0.0), Rgb::BLUE);
+        assert_eq!(color(10.0), Rgb::BLUE);
+        assert_eq!(color(20.0), Rgb::GREEN);
+        assert_eq!(color(30.0), Rgb::RED);
+        assert_eq!(color(45.0), Rgb::RED);
+        let middle = color(15.0);
+        assert!(middle.r == 0 && middle.g > 100 && middle.b > 100);
+    }
+}